@@ -2,19 +2,112 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface AspectRatio { 'height' : number, 'width' : number }
+export type AutocompleteKind = { 'Tag' : null } |
+  { 'Title' : null } |
+  { 'Creator' : null };
 export type AvatarUrl = string;
+export interface CaptionTrack {
+  'cue_count' : number,
+  'label' : string,
+  'is_default' : boolean,
+  'language' : string,
+  'url_path' : string,
+}
+export type CaptionTracksResponse = { 'Ok' : Array<CaptionTrack> } |
+  { 'Err' : string };
+export type ChannelSort = { 'Oldest' : null } |
+  { 'Newest' : null } |
+  { 'MostViewed' : null };
+export interface Chapter { 'start_sec' : number, 'title' : string }
+export interface ClipSource {
+  'start_sec' : number,
+  'end_sec' : number,
+  'source_video_id' : string,
+}
 export interface Comment {
   'commenter_principal' : Principal,
   'text' : string,
   'timestamp' : bigint,
   'video_id' : string,
 }
+export interface CommentPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<Comment>,
+}
+export type CommentPageResponse = { 'Ok' : CommentPage } |
+  { 'Err' : string };
 export type CommentResponse = { 'Ok' : Comment } |
   { 'Err' : string };
+export type ContentRating = { 'Teen' : null } |
+  { 'General' : null } |
+  { 'Mature' : null };
+export interface CreateVideoRequest {
+  'title' : Title,
+  'remix_of' : [] | [RemixSource],
+  'content_hash' : [] | [string],
+  'storage_ref' : [] | [StorageRef],
+  'tags' : Array<Tag>,
+  'details' : [] | [VideoDetails],
+  'renditions' : [] | [Array<Rendition>],
+  'idempotency_key' : [] | [IdempotencyKey],
+}
+export interface Credit {
+  'status' : CreditStatus,
+  'principal' : Principal,
+  'role' : CreditRole,
+  'share_bps' : number,
+}
+export interface CreditInput {
+  'principal' : Principal,
+  'role' : CreditRole,
+  'share_bps' : number,
+}
+export type CreditResponse = { 'Ok' : Credit } |
+  { 'Err' : string };
+export type CreditRole = { 'Editor' : null } |
+  { 'Music' : null } |
+  { 'Writer' : null } |
+  { 'CoCreator' : null } |
+  { 'Camera' : null } |
+  { 'Performer' : null };
+export type CreditStatus = { 'Accepted' : null } |
+  { 'Pending' : null };
+export type CreditsResponse = { 'Ok' : Array<Credit> } |
+  { 'Err' : string };
+export interface DuplicateFlag {
+  'duplicate_of' : string,
+  'uploader_principal' : Principal,
+  'flagged_at' : bigint,
+  'video_id' : string,
+}
+export interface DuplicateFlagPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<DuplicateFlag>,
+}
+export type DuplicateFlagPageResponse = { 'Ok' : DuplicateFlagPage } |
+  { 'Err' : string };
+export type DuplicateOfResponse = { 'Ok' : [] | [string] } |
+  { 'Err' : string };
+export type DuplicatePolicy = { 'Flag' : null } |
+  { 'Reject' : null };
 export type EmptyResponse = { 'Ok' : null } |
   { 'Err' : string };
 export type GetMyProfileResponse = { 'Ok' : UserProfile } |
   { 'Err' : string };
+export type HeaderField = [string, string];
+export interface HttpRequest {
+  'url' : string,
+  'method' : string,
+  'body' : Uint8Array | number[],
+  'headers' : Array<HeaderField>,
+}
+export interface HttpResponse {
+  'body' : Uint8Array | number[],
+  'headers' : Array<HeaderField>,
+  'upgrade' : [] | [boolean],
+  'status_code' : number,
+}
 export interface IPFSProxyError { 'message' : string, 'status_code' : number }
 export type IPFSProxyResponse = { 'Ok' : IPFSProxyResult } |
   { 'Err' : IPFSProxyError };
@@ -23,26 +116,234 @@ export interface IPFSProxyResult {
   'content_type' : string,
   'status_code' : number,
 }
-export type ListProfilesResponse = { 'Ok' : Array<[string, UserProfile]> } |
-  { 'Err' : string };
+export type IdempotencyKey = string;
+export type MatchMode = { 'All' : null } |
+  { 'Any' : null };
+export type MetadataChange = {
+    'ChaptersFromDescription' : {
+      'new' : [] | [boolean],
+      'old' : [] | [boolean],
+    }
+  } |
+  { 'Tags' : { 'new' : Array<Tag>, 'old' : Array<Tag> } } |
+  { 'Description' : { 'new' : [] | [string], 'old' : [] | [string] } } |
+  { 'PrimaryLanguage' : { 'new' : [] | [string], 'old' : [] | [string] } } |
+  {
+    'Chapters' : {
+      'new' : [] | [Array<Chapter>],
+      'old' : [] | [Array<Chapter>],
+    }
+  } |
+  { 'SoundId' : { 'new' : [] | [string], 'old' : [] | [string] } } |
+  { 'StorageRef' : { 'new' : [] | [StorageRef], 'old' : [] | [StorageRef] } } |
+  { 'Title' : { 'new' : string, 'old' : string } } |
+  { 'Renditions' : { 'new' : Array<Rendition>, 'old' : Array<Rendition> } } |
+  { 'DurationSec' : { 'new' : [] | [number], 'old' : [] | [number] } } |
+  {
+    'ContentRating' : {
+      'new' : [] | [ContentRating],
+      'old' : [] | [ContentRating],
+    }
+  } |
+  { 'AllowComments' : { 'new' : [] | [boolean], 'old' : [] | [boolean] } } |
+  {
+    'AspectRatio' : { 'new' : [] | [AspectRatio], 'old' : [] | [AspectRatio] }
+  } |
+  { 'AllowRemixes' : { 'new' : [] | [boolean], 'old' : [] | [boolean] } };
 export type Name = string;
+export type PinnedVideosResponse = { 'Ok' : Array<VideoId> } |
+  { 'Err' : string };
+export interface PlaylistPage {
+  'playlist' : PlaylistSummary,
+  'videos' : VideoPage,
+}
+export type PlaylistPageResponse = { 'Ok' : PlaylistPage } |
+  { 'Err' : string };
+export interface PlaylistSummary {
+  'title' : string,
+  'updated_at' : bigint,
+  'invited_collaborators' : Array<Principal>,
+  'description' : [] | [string],
+  'created_at' : bigint,
+  'playlist_id' : string,
+  'video_count' : number,
+  'collaborators' : Array<Principal>,
+  'owner_principal' : Principal,
+  'visibility' : PlaylistVisibility,
+}
+export interface PlaylistSummaryPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<PlaylistSummary>,
+}
+export type PlaylistSummaryPageResponse = { 'Ok' : PlaylistSummaryPage } |
+  { 'Err' : string };
+export type PlaylistSummaryResponse = { 'Ok' : PlaylistSummary } |
+  { 'Err' : string };
+export type PlaylistVisibility = { 'Private' : null } |
+  { 'Public' : null } |
+  { 'Unlisted' : null };
 export type Principal = Principal;
+export interface PrincipalPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<Principal>,
+}
+export type PrincipalPageResponse = { 'Ok' : PrincipalPage } |
+  { 'Err' : string };
+export type ProcessingStatus = { 'Uploading' : null } |
+  { 'Failed' : { 'reason' : string } } |
+  { 'Ready' : null } |
+  { 'Processing' : null };
+export interface ProfilePage {
+  'next_cursor' : [] | [string],
+  'items' : Array<[string, UserProfile]>,
+}
+export type ProfilePageResponse = { 'Ok' : ProfilePage } |
+  { 'Err' : string };
+export interface ProfileSearchPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<ProfileSearchResult>,
+}
+export type ProfileSearchPageResponse = { 'Ok' : ProfileSearchPage } |
+  { 'Err' : string };
+export interface ProfileSearchResult {
+  'principal' : Principal,
+  'video_count' : bigint,
+  'follower_count' : bigint,
+  'profile' : UserProfile,
+}
+export interface RelatedTag { 'tag' : string, 'shared_videos' : bigint }
+export interface RemixSource {
+  'remix_type' : RemixType,
+  'parent_video_id' : string,
+}
+export type RemixType = { 'Duet' : null } |
+  { 'Stitch' : null } |
+  { 'Reaction' : null };
+export interface Rendition {
+  'height' : number,
+  'storage_ref' : StorageRef,
+  'label' : string,
+  'width' : number,
+  'bitrate_kbps' : [] | [number],
+}
+export interface ResolvedClip {
+  'clip_id' : string,
+  'start_sec' : number,
+  'end_sec' : number,
+  'storage_ref' : [] | [StorageRef],
+  'source_video_id' : string,
+  'renditions' : Array<Rendition>,
+  'media_fragment' : string,
+}
+export type ResolvedClipResponse = { 'Ok' : ResolvedClip } |
+  { 'Err' : string };
 export type SaveMyProfileResponse = { 'Ok' : UserProfile } |
   { 'Err' : string };
-export type StorageRef = string;
+export interface SearchQuery {
+  'cursor' : [] | [string],
+  'uploaded_after' : [] | [bigint],
+  'sort' : [] | [SearchSort],
+  'text' : [] | [string],
+  'tags_all' : [] | [Array<Tag>],
+  'tags_any' : [] | [Array<Tag>],
+  'match_mode' : [] | [MatchMode],
+  'limit' : [] | [number],
+  'max_duration_sec' : [] | [number],
+  'language' : [] | [string],
+  'min_duration_sec' : [] | [number],
+  'uploader' : [] | [Principal],
+  'uploaded_before' : [] | [bigint],
+  'content_rating' : [] | [ContentRating],
+  'tags_none' : [] | [Array<Tag>],
+}
+export interface SearchResults {
+  'next_cursor' : [] | [string],
+  'did_you_mean' : [] | [string],
+  'videos' : Array<VideoMetadata>,
+}
+export type SearchResultsResponse = { 'Ok' : SearchResults } |
+  { 'Err' : string };
+export type SearchSort = { 'MostTipped' : null } |
+  { 'Relevance' : null } |
+  { 'MostLiked' : null } |
+  { 'Newest' : null } |
+  { 'MostViewed' : null };
+export interface Sound {
+  'title' : string,
+  'duration_sec' : number,
+  'sound_id' : string,
+  'source_video_id' : [] | [string],
+  'created_at' : bigint,
+  'use_count' : number,
+  'creator_principal' : Principal,
+}
+export interface SoundPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<Sound>,
+}
+export type SoundPageResponse = { 'Ok' : SoundPage } |
+  { 'Err' : string };
+export type SoundResponse = { 'Ok' : Sound } |
+  { 'Err' : string };
+export type StorageRef = { 'Url' : { 'url' : string } } |
+  { 'Ipfs' : { 'cid' : string, 'path' : [] | [string] } } |
+  { 'Canister' : { 'canister_id' : Principal, 'asset_key' : string } } |
+  { 'Livepeer' : { 'playback_id' : string } } |
+  { 'Unrecognized' : { 'value' : string } };
+export type StorageRefUpdate = { 'Set' : StorageRef } |
+  { 'Clear' : null };
+export interface Suggestion {
+  'id' : [] | [string],
+  'text' : string,
+  'popularity' : bigint,
+}
 export type Tag = string;
+export interface TagAlias { 'canonical' : string, 'alias' : string }
+export interface TagAliasPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<TagAlias>,
+}
+export type TagAliasPageResponse = { 'Ok' : TagAliasPage } |
+  { 'Err' : string };
+export interface TagPage {
+  'tag' : string,
+  'related_tags' : Array<RelatedTag>,
+  'video_count' : bigint,
+  'recent_videos' : VideoPage,
+  'total_views' : bigint,
+  'top_videos' : Array<VideoMetadata>,
+}
+export type TagPageResponse = { 'Ok' : TagPage } |
+  { 'Err' : string };
 export type Text = string;
 export interface TipRecord {
   'from_addr' : string,
   'to_addr' : string,
   'timestamp' : bigint,
+  'share_bps' : [] | [number],
   'tx_hash' : string,
   'amount' : bigint,
   'video_id' : string,
 }
+export interface TipRecordPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<TipRecord>,
+}
+export type TipRecordPageResponse = { 'Ok' : TipRecordPage } |
+  { 'Err' : string };
 export type TipRecordResponse = { 'Ok' : TipRecord } |
   { 'Err' : string };
 export type Title = string;
+export interface TrendingSound { 'sound' : Sound, 'recent_uses' : number }
+export interface TrendingTag { 'tag' : string, 'score' : number }
+export interface TrendingTags {
+  'tags' : Array<TrendingTag>,
+  'window' : TrendingWindow,
+  'refreshed_at' : bigint,
+}
+export type TrendingWindow = { 'Day' : null } |
+  { 'Hour' : null } |
+  { 'Week' : null };
 export type TxHash = string;
 export interface UserProfile {
   'evm_address' : string,
@@ -58,17 +359,66 @@ export interface VideoAnalytics {
 }
 export type VideoAnalyticsResponse = { 'Ok' : VideoAnalytics } |
   { 'Err' : string };
+export interface VideoDetails {
+  'duration_sec' : [] | [number],
+  'sound_id' : [] | [string],
+  'allow_remixes' : [] | [boolean],
+  'allow_comments' : [] | [boolean],
+  'description' : [] | [string],
+  'chapters' : [] | [Array<Chapter>],
+  'content_rating' : [] | [ContentRating],
+  'primary_language' : [] | [string],
+  'aspect_ratio' : [] | [AspectRatio],
+}
 export type VideoId = string;
 export interface VideoMetadata {
+  'clip_of' : [] | [ClipSource],
   'title' : string,
+  'duration_sec' : [] | [number],
+  'credits' : [] | [Array<Credit>],
   'uploader_principal' : Principal,
+  'sound_id' : [] | [string],
+  'allow_remixes' : [] | [boolean],
+  'allow_comments' : [] | [boolean],
+  'remix_of' : [] | [RemixSource],
+  'content_hash' : [] | [string],
   'storage_ref' : [] | [StorageRef],
   'tags' : Array<Tag>,
+  'description' : [] | [string],
+  'chapters_from_description' : [] | [boolean],
+  'chapters' : [] | [Array<Chapter>],
   'timestamp' : bigint,
+  'deleted_at' : [] | [bigint],
+  'renditions' : Array<Rendition>,
+  'captions' : [] | [Array<CaptionTrack>],
+  'content_rating' : [] | [ContentRating],
+  'processing_status' : [] | [ProcessingStatus],
+  'mentions' : [] | [Array<Principal>],
   'video_id' : string,
+  'primary_language' : [] | [string],
+  'aspect_ratio' : [] | [AspectRatio],
 }
 export type VideoMetadataResponse = { 'Ok' : VideoMetadata } |
   { 'Err' : string };
+export interface VideoPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<VideoMetadata>,
+}
+export type VideoPageResponse = { 'Ok' : VideoPage } |
+  { 'Err' : string };
+export interface VideoRevision {
+  'reverted_to' : [] | [number],
+  'editor_principal' : Principal,
+  'timestamp' : bigint,
+  'changes' : Array<MetadataChange>,
+  'revision' : number,
+}
+export interface VideoRevisionPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<VideoRevision>,
+}
+export type VideoRevisionPageResponse = { 'Ok' : VideoRevisionPage } |
+  { 'Err' : string };
 export interface WatchEvent {
   'user_principal' : Principal,
   'watch_duration_sec' : number,
@@ -77,58 +427,253 @@ export interface WatchEvent {
   'timestamp' : bigint,
   'video_id' : string,
 }
+export interface WatchEventPage {
+  'next_cursor' : [] | [string],
+  'items' : Array<WatchEvent>,
+}
+export type WatchEventPageResponse = { 'Ok' : WatchEventPage } |
+  { 'Err' : string };
 export interface _SERVICE {
+  'accept_playlist_invite' : ActorMethod<[string], PlaylistSummaryResponse>,
+  'accept_video_credit' : ActorMethod<[VideoId], CreditResponse>,
+  'add_moderator' : ActorMethod<[Principal], EmptyResponse>,
+  'add_playlist_collaborator' : ActorMethod<
+    [string, Principal],
+    PlaylistSummaryResponse
+  >,
+  'add_video_to_playlist' : ActorMethod<
+    [string, VideoId, [] | [number]],
+    PlaylistSummaryResponse
+  >,
+  'autocomplete' : ActorMethod<
+    [string, AutocompleteKind, [] | [number]],
+    Array<Suggestion>
+  >,
+  'create_clip' : ActorMethod<
+    [VideoId, string, number, number],
+    VideoMetadataResponse
+  >,
+  'create_playlist' : ActorMethod<
+    [string, [] | [string], PlaylistVisibility],
+    PlaylistSummaryResponse
+  >,
+  'create_sound' : ActorMethod<[string, [] | [string], number], SoundResponse>,
   'create_video_metadata' : ActorMethod<
-    [VideoId, Title, Array<Tag>, [] | [StorageRef]],
+    [CreateVideoRequest],
     VideoMetadataResponse
   >,
+  'decline_video_credit' : ActorMethod<[VideoId], EmptyResponse>,
   'delete_comment' : ActorMethod<[VideoId, bigint], EmptyResponse>,
+  'delete_playlist' : ActorMethod<[string], EmptyResponse>,
   'delete_video' : ActorMethod<[VideoId], EmptyResponse>,
   'follow_user' : ActorMethod<[Principal], EmptyResponse>,
-  'get_comments' : ActorMethod<[VideoId], Array<Comment>>,
-  'get_followers' : ActorMethod<[Principal], Array<Principal>>,
-  'get_following' : ActorMethod<[Principal], Array<Principal>>,
-  'get_my_comments' : ActorMethod<[], Array<Comment>>,
+  'get_channel_videos' : ActorMethod<
+    [Principal, ChannelSort, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'get_comments' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    CommentPageResponse
+  >,
+  'get_duplicate_of' : ActorMethod<[VideoId], DuplicateOfResponse>,
+  'get_duplicate_policy' : ActorMethod<[], DuplicatePolicy>,
+  'get_followers' : ActorMethod<
+    [Principal, [] | [string], [] | [number]],
+    PrincipalPageResponse
+  >,
+  'get_following' : ActorMethod<
+    [Principal, [] | [string], [] | [number]],
+    PrincipalPageResponse
+  >,
+  'get_my_comments' : ActorMethod<
+    [[] | [string], [] | [number]],
+    CommentPageResponse
+  >,
   'get_my_profile' : ActorMethod<[], GetMyProfileResponse>,
-  'get_my_received_tips' : ActorMethod<[], Array<TipRecord>>,
-  'get_my_sent_tips' : ActorMethod<[], Array<TipRecord>>,
-  'get_my_watch_events' : ActorMethod<[], Array<WatchEvent>>,
-  'get_tips_for_video' : ActorMethod<[VideoId], Array<TipRecord>>,
+  'get_my_received_tips' : ActorMethod<
+    [[] | [string], [] | [number]],
+    TipRecordPageResponse
+  >,
+  'get_my_sent_tips' : ActorMethod<
+    [[] | [string], [] | [number]],
+    TipRecordPageResponse
+  >,
+  'get_my_watch_events' : ActorMethod<
+    [[] | [string], [] | [number]],
+    WatchEventPageResponse
+  >,
+  'get_playlist' : ActorMethod<
+    [string, [] | [string], [] | [number]],
+    PlaylistPageResponse
+  >,
+  'get_sound' : ActorMethod<[string], SoundResponse>,
+  'get_tag_page' : ActorMethod<
+    [string, [] | [string], [] | [number]],
+    TagPageResponse
+  >,
+  'get_tip_splits' : ActorMethod<
+    [VideoId, TxHash, [] | [string], [] | [number]],
+    TipRecordPageResponse
+  >,
+  'get_tips_for_video' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    TipRecordPageResponse
+  >,
+  'get_trending_sounds' : ActorMethod<[[] | [number]], Array<TrendingSound>>,
+  'get_trending_tags' : ActorMethod<
+    [TrendingWindow, [] | [number]],
+    TrendingTags
+  >,
   'get_video_analytics' : ActorMethod<[VideoId], VideoAnalyticsResponse>,
+  'get_video_ancestry' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
   'get_video_metadata' : ActorMethod<[VideoId], VideoMetadataResponse>,
-  'get_watch_events' : ActorMethod<[VideoId], Array<WatchEvent>>,
+  'get_video_remixes' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'get_watch_events' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    WatchEventPageResponse
+  >,
   'has_pinata_jwt_configured' : ActorMethod<[], boolean>,
+  'has_webhook_secret_configured' : ActorMethod<[], boolean>,
+  'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
+  'http_request_update' : ActorMethod<[HttpRequest], HttpResponse>,
   'is_following' : ActorMethod<[Principal, Principal], boolean>,
-  'list_all_videos' : ActorMethod<[], Array<VideoMetadata>>,
-  'list_profiles' : ActorMethod<[], ListProfilesResponse>,
-  'list_videos_by_tag' : ActorMethod<[Tag], Array<VideoMetadata>>,
-  'list_videos_by_uploader' : ActorMethod<[Principal], Array<VideoMetadata>>,
+  'list_all_videos' : ActorMethod<
+    [[] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_clips_of_video' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_duplicate_flags' : ActorMethod<
+    [[] | [string], [] | [number]],
+    DuplicateFlagPageResponse
+  >,
+  'list_moderators' : ActorMethod<
+    [[] | [string], [] | [number]],
+    PrincipalPageResponse
+  >,
+  'list_my_pending_credits' : ActorMethod<
+    [[] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_my_playlists' : ActorMethod<
+    [[] | [string], [] | [number]],
+    PlaylistSummaryPageResponse
+  >,
+  'list_my_trash' : ActorMethod<
+    [[] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_playlists_by_owner' : ActorMethod<
+    [Principal, [] | [string], [] | [number]],
+    PlaylistSummaryPageResponse
+  >,
+  'list_profiles' : ActorMethod<
+    [[] | [string], [] | [number]],
+    ProfilePageResponse
+  >,
+  'list_tag_aliases' : ActorMethod<
+    [[] | [string], [] | [number]],
+    TagAliasPageResponse
+  >,
+  'list_video_revisions' : ActorMethod<
+    [VideoId, [] | [string], [] | [number]],
+    VideoRevisionPageResponse
+  >,
+  'list_videos_by_sound' : ActorMethod<
+    [string, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_videos_by_tag' : ActorMethod<
+    [Tag, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
+  'list_videos_by_uploader' : ActorMethod<
+    [Principal, [] | [string], [] | [number]],
+    VideoPageResponse
+  >,
   'log_watch_event' : ActorMethod<
     [VideoId, number, boolean, boolean],
     EmptyResponse
   >,
+  'move_video_in_playlist' : ActorMethod<
+    [string, VideoId, number],
+    PlaylistSummaryResponse
+  >,
+  'pin_video' : ActorMethod<[VideoId, [] | [number]], PinnedVideosResponse>,
   'post_comment' : ActorMethod<[VideoId, Text], CommentResponse>,
   'proxy_ipfs_content' : ActorMethod<[string], IPFSProxyResponse>,
   'record_tip' : ActorMethod<[VideoId, bigint, TxHash], TipRecordResponse>,
+  'remove_caption_track' : ActorMethod<
+    [VideoId, string],
+    CaptionTracksResponse
+  >,
+  'remove_moderator' : ActorMethod<[Principal], EmptyResponse>,
+  'remove_playlist_collaborator' : ActorMethod<
+    [string, Principal],
+    PlaylistSummaryResponse
+  >,
+  'remove_tag_alias' : ActorMethod<[string], EmptyResponse>,
+  'remove_video_from_playlist' : ActorMethod<
+    [string, VideoId],
+    PlaylistSummaryResponse
+  >,
+  'resolve_clip' : ActorMethod<[VideoId], ResolvedClipResponse>,
+  'restore_video' : ActorMethod<[VideoId], VideoMetadataResponse>,
+  'revert_video_metadata' : ActorMethod<
+    [VideoId, number],
+    VideoMetadataResponse
+  >,
   'save_my_profile' : ActorMethod<[Name, AvatarUrl], SaveMyProfileResponse>,
-  'search_videos' : ActorMethod<
-    [string, [] | [number], [] | [number]],
-    Array<VideoMetadata>
+  'search_profiles' : ActorMethod<
+    [string, [] | [string], [] | [number]],
+    ProfileSearchPageResponse
   >,
-  'search_videos_by_tags' : ActorMethod<
-    [Array<string>, [] | [number], [] | [number]],
-    Array<VideoMetadata>
+  'search_sounds' : ActorMethod<
+    [string, [] | [string], [] | [number]],
+    SoundPageResponse
   >,
+  'search_videos' : ActorMethod<[SearchQuery], SearchResultsResponse>,
+  'set_duplicate_policy' : ActorMethod<[DuplicatePolicy], EmptyResponse>,
   'set_pinata_jwt' : ActorMethod<
     [string, Principal],
     { 'Ok' : null } |
       { 'Err' : string }
   >,
+  'set_tag_alias' : ActorMethod<[string, string], EmptyResponse>,
+  'set_video_credits' : ActorMethod<
+    [VideoId, Array<CreditInput>],
+    CreditsResponse
+  >,
+  'set_webhook_secret' : ActorMethod<[string], EmptyResponse>,
   'unfollow_user' : ActorMethod<[Principal], EmptyResponse>,
+  'unpin_video' : ActorMethod<[VideoId], PinnedVideosResponse>,
+  'update_playlist' : ActorMethod<
+    [string, [] | [string], [] | [string], [] | [PlaylistVisibility]],
+    PlaylistSummaryResponse
+  >,
   'update_video_metadata' : ActorMethod<
-    [VideoId, [] | [Title], [] | [Array<Tag>], [] | [StorageRef]],
+    [
+      VideoId,
+      [] | [Title],
+      [] | [Array<Tag>],
+      [] | [StorageRefUpdate],
+      [] | [Array<Rendition>],
+      [] | [VideoDetails],
+    ],
     VideoMetadataResponse
   >,
+  'upsert_caption_track' : ActorMethod<
+    [VideoId, string, string, boolean, string],
+    CaptionTracksResponse
+  >,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
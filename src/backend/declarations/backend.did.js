@@ -1,28 +1,213 @@
 export const idlFactory = ({ IDL }) => {
+  const Principal = IDL.Principal;
+  const PlaylistVisibility = IDL.Variant({
+    'Private' : IDL.Null,
+    'Public' : IDL.Null,
+    'Unlisted' : IDL.Null,
+  });
+  const PlaylistSummary = IDL.Record({
+    'title' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'invited_collaborators' : IDL.Vec(Principal),
+    'description' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'playlist_id' : IDL.Text,
+    'video_count' : IDL.Nat32,
+    'collaborators' : IDL.Vec(Principal),
+    'owner_principal' : Principal,
+    'visibility' : PlaylistVisibility,
+  });
+  const PlaylistSummaryResponse = IDL.Variant({
+    'Ok' : PlaylistSummary,
+    'Err' : IDL.Text,
+  });
   const VideoId = IDL.Text;
-  const Title = IDL.Text;
+  const CreditStatus = IDL.Variant({
+    'Accepted' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const CreditRole = IDL.Variant({
+    'Editor' : IDL.Null,
+    'Music' : IDL.Null,
+    'Writer' : IDL.Null,
+    'CoCreator' : IDL.Null,
+    'Camera' : IDL.Null,
+    'Performer' : IDL.Null,
+  });
+  const Credit = IDL.Record({
+    'status' : CreditStatus,
+    'principal' : Principal,
+    'role' : CreditRole,
+    'share_bps' : IDL.Nat32,
+  });
+  const CreditResponse = IDL.Variant({ 'Ok' : Credit, 'Err' : IDL.Text });
+  const EmptyResponse = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const AutocompleteKind = IDL.Variant({
+    'Tag' : IDL.Null,
+    'Title' : IDL.Null,
+    'Creator' : IDL.Null,
+  });
+  const Suggestion = IDL.Record({
+    'id' : IDL.Opt(IDL.Text),
+    'text' : IDL.Text,
+    'popularity' : IDL.Nat64,
+  });
+  const ClipSource = IDL.Record({
+    'start_sec' : IDL.Nat32,
+    'end_sec' : IDL.Nat32,
+    'source_video_id' : IDL.Text,
+  });
+  const RemixType = IDL.Variant({
+    'Duet' : IDL.Null,
+    'Stitch' : IDL.Null,
+    'Reaction' : IDL.Null,
+  });
+  const RemixSource = IDL.Record({
+    'remix_type' : RemixType,
+    'parent_video_id' : IDL.Text,
+  });
+  const StorageRef = IDL.Variant({
+    'Url' : IDL.Record({ 'url' : IDL.Text }),
+    'Ipfs' : IDL.Record({ 'cid' : IDL.Text, 'path' : IDL.Opt(IDL.Text) }),
+    'Canister' : IDL.Record({
+      'canister_id' : Principal,
+      'asset_key' : IDL.Text,
+    }),
+    'Livepeer' : IDL.Record({ 'playback_id' : IDL.Text }),
+    'Unrecognized' : IDL.Record({ 'value' : IDL.Text }),
+  });
   const Tag = IDL.Text;
-  const StorageRef = IDL.Text;
-  const Principal = IDL.Principal;
+  const Chapter = IDL.Record({ 'start_sec' : IDL.Nat32, 'title' : IDL.Text });
+  const Rendition = IDL.Record({
+    'height' : IDL.Nat32,
+    'storage_ref' : StorageRef,
+    'label' : IDL.Text,
+    'width' : IDL.Nat32,
+    'bitrate_kbps' : IDL.Opt(IDL.Nat32),
+  });
+  const CaptionTrack = IDL.Record({
+    'cue_count' : IDL.Nat32,
+    'label' : IDL.Text,
+    'is_default' : IDL.Bool,
+    'language' : IDL.Text,
+    'url_path' : IDL.Text,
+  });
+  const ContentRating = IDL.Variant({
+    'Teen' : IDL.Null,
+    'General' : IDL.Null,
+    'Mature' : IDL.Null,
+  });
+  const ProcessingStatus = IDL.Variant({
+    'Uploading' : IDL.Null,
+    'Failed' : IDL.Record({ 'reason' : IDL.Text }),
+    'Ready' : IDL.Null,
+    'Processing' : IDL.Null,
+  });
+  const AspectRatio = IDL.Record({ 'height' : IDL.Nat32, 'width' : IDL.Nat32 });
   const VideoMetadata = IDL.Record({
+    'clip_of' : IDL.Opt(ClipSource),
     'title' : IDL.Text,
+    'duration_sec' : IDL.Opt(IDL.Nat32),
+    'credits' : IDL.Opt(IDL.Vec(Credit)),
     'uploader_principal' : Principal,
+    'sound_id' : IDL.Opt(IDL.Text),
+    'allow_remixes' : IDL.Opt(IDL.Bool),
+    'allow_comments' : IDL.Opt(IDL.Bool),
+    'remix_of' : IDL.Opt(RemixSource),
+    'content_hash' : IDL.Opt(IDL.Text),
     'storage_ref' : IDL.Opt(StorageRef),
     'tags' : IDL.Vec(Tag),
+    'description' : IDL.Opt(IDL.Text),
+    'chapters_from_description' : IDL.Opt(IDL.Bool),
+    'chapters' : IDL.Opt(IDL.Vec(Chapter)),
     'timestamp' : IDL.Nat64,
+    'deleted_at' : IDL.Opt(IDL.Nat64),
+    'renditions' : IDL.Vec(Rendition),
+    'captions' : IDL.Opt(IDL.Vec(CaptionTrack)),
+    'content_rating' : IDL.Opt(ContentRating),
+    'processing_status' : IDL.Opt(ProcessingStatus),
+    'mentions' : IDL.Opt(IDL.Vec(Principal)),
     'video_id' : IDL.Text,
+    'primary_language' : IDL.Opt(IDL.Text),
+    'aspect_ratio' : IDL.Opt(AspectRatio),
   });
   const VideoMetadataResponse = IDL.Variant({
     'Ok' : VideoMetadata,
     'Err' : IDL.Text,
   });
-  const EmptyResponse = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const Sound = IDL.Record({
+    'title' : IDL.Text,
+    'duration_sec' : IDL.Nat32,
+    'sound_id' : IDL.Text,
+    'source_video_id' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'use_count' : IDL.Nat32,
+    'creator_principal' : Principal,
+  });
+  const SoundResponse = IDL.Variant({ 'Ok' : Sound, 'Err' : IDL.Text });
+  const Title = IDL.Text;
+  const VideoDetails = IDL.Record({
+    'duration_sec' : IDL.Opt(IDL.Nat32),
+    'sound_id' : IDL.Opt(IDL.Text),
+    'allow_remixes' : IDL.Opt(IDL.Bool),
+    'allow_comments' : IDL.Opt(IDL.Bool),
+    'description' : IDL.Opt(IDL.Text),
+    'chapters' : IDL.Opt(IDL.Vec(Chapter)),
+    'content_rating' : IDL.Opt(ContentRating),
+    'primary_language' : IDL.Opt(IDL.Text),
+    'aspect_ratio' : IDL.Opt(AspectRatio),
+  });
+  const IdempotencyKey = IDL.Text;
+  const CreateVideoRequest = IDL.Record({
+    'title' : Title,
+    'remix_of' : IDL.Opt(RemixSource),
+    'content_hash' : IDL.Opt(IDL.Text),
+    'storage_ref' : IDL.Opt(StorageRef),
+    'tags' : IDL.Vec(Tag),
+    'details' : IDL.Opt(VideoDetails),
+    'renditions' : IDL.Opt(IDL.Vec(Rendition)),
+    'idempotency_key' : IDL.Opt(IdempotencyKey),
+  });
+  const ChannelSort = IDL.Variant({
+    'Oldest' : IDL.Null,
+    'Newest' : IDL.Null,
+    'MostViewed' : IDL.Null,
+  });
+  const VideoPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(VideoMetadata),
+  });
+  const VideoPageResponse = IDL.Variant({ 'Ok' : VideoPage, 'Err' : IDL.Text });
   const Comment = IDL.Record({
     'commenter_principal' : Principal,
     'text' : IDL.Text,
     'timestamp' : IDL.Nat64,
     'video_id' : IDL.Text,
   });
+  const CommentPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(Comment),
+  });
+  const CommentPageResponse = IDL.Variant({
+    'Ok' : CommentPage,
+    'Err' : IDL.Text,
+  });
+  const DuplicateOfResponse = IDL.Variant({
+    'Ok' : IDL.Opt(IDL.Text),
+    'Err' : IDL.Text,
+  });
+  const DuplicatePolicy = IDL.Variant({
+    'Flag' : IDL.Null,
+    'Reject' : IDL.Null,
+  });
+  const PrincipalPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(Principal),
+  });
+  const PrincipalPageResponse = IDL.Variant({
+    'Ok' : PrincipalPage,
+    'Err' : IDL.Text,
+  });
   const UserProfile = IDL.Record({
     'evm_address' : IDL.Text,
     'avatar_url' : IDL.Text,
@@ -36,10 +221,19 @@ export const idlFactory = ({ IDL }) => {
     'from_addr' : IDL.Text,
     'to_addr' : IDL.Text,
     'timestamp' : IDL.Nat64,
+    'share_bps' : IDL.Opt(IDL.Nat32),
     'tx_hash' : IDL.Text,
     'amount' : IDL.Nat64,
     'video_id' : IDL.Text,
   });
+  const TipRecordPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(TipRecord),
+  });
+  const TipRecordPageResponse = IDL.Variant({
+    'Ok' : TipRecordPage,
+    'Err' : IDL.Text,
+  });
   const WatchEvent = IDL.Record({
     'user_principal' : Principal,
     'watch_duration_sec' : IDL.Nat32,
@@ -48,6 +242,51 @@ export const idlFactory = ({ IDL }) => {
     'timestamp' : IDL.Nat64,
     'video_id' : IDL.Text,
   });
+  const WatchEventPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(WatchEvent),
+  });
+  const WatchEventPageResponse = IDL.Variant({
+    'Ok' : WatchEventPage,
+    'Err' : IDL.Text,
+  });
+  const PlaylistPage = IDL.Record({
+    'playlist' : PlaylistSummary,
+    'videos' : VideoPage,
+  });
+  const PlaylistPageResponse = IDL.Variant({
+    'Ok' : PlaylistPage,
+    'Err' : IDL.Text,
+  });
+  const RelatedTag = IDL.Record({
+    'tag' : IDL.Text,
+    'shared_videos' : IDL.Nat64,
+  });
+  const TagPage = IDL.Record({
+    'tag' : IDL.Text,
+    'related_tags' : IDL.Vec(RelatedTag),
+    'video_count' : IDL.Nat64,
+    'recent_videos' : VideoPage,
+    'total_views' : IDL.Nat64,
+    'top_videos' : IDL.Vec(VideoMetadata),
+  });
+  const TagPageResponse = IDL.Variant({ 'Ok' : TagPage, 'Err' : IDL.Text });
+  const TxHash = IDL.Text;
+  const TrendingSound = IDL.Record({
+    'sound' : Sound,
+    'recent_uses' : IDL.Nat32,
+  });
+  const TrendingWindow = IDL.Variant({
+    'Day' : IDL.Null,
+    'Hour' : IDL.Null,
+    'Week' : IDL.Null,
+  });
+  const TrendingTag = IDL.Record({ 'tag' : IDL.Text, 'score' : IDL.Float64 });
+  const TrendingTags = IDL.Record({
+    'tags' : IDL.Vec(TrendingTag),
+    'window' : TrendingWindow,
+    'refreshed_at' : IDL.Nat64,
+  });
   const VideoAnalytics = IDL.Record({
     'total_likes' : IDL.Nat64,
     'total_unique_viewers' : IDL.Nat64,
@@ -59,8 +298,127 @@ export const idlFactory = ({ IDL }) => {
     'Ok' : VideoAnalytics,
     'Err' : IDL.Text,
   });
-  const ListProfilesResponse = IDL.Variant({
-    'Ok' : IDL.Vec(IDL.Tuple(IDL.Text, UserProfile)),
+  const HeaderField = IDL.Tuple(IDL.Text, IDL.Text);
+  const HttpRequest = IDL.Record({
+    'url' : IDL.Text,
+    'method' : IDL.Text,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HeaderField),
+  });
+  const HttpResponse = IDL.Record({
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HeaderField),
+    'upgrade' : IDL.Opt(IDL.Bool),
+    'status_code' : IDL.Nat16,
+  });
+  const DuplicateFlag = IDL.Record({
+    'duplicate_of' : IDL.Text,
+    'uploader_principal' : Principal,
+    'flagged_at' : IDL.Nat64,
+    'video_id' : IDL.Text,
+  });
+  const DuplicateFlagPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(DuplicateFlag),
+  });
+  const DuplicateFlagPageResponse = IDL.Variant({
+    'Ok' : DuplicateFlagPage,
+    'Err' : IDL.Text,
+  });
+  const PlaylistSummaryPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(PlaylistSummary),
+  });
+  const PlaylistSummaryPageResponse = IDL.Variant({
+    'Ok' : PlaylistSummaryPage,
+    'Err' : IDL.Text,
+  });
+  const ProfilePage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(IDL.Tuple(IDL.Text, UserProfile)),
+  });
+  const ProfilePageResponse = IDL.Variant({
+    'Ok' : ProfilePage,
+    'Err' : IDL.Text,
+  });
+  const TagAlias = IDL.Record({ 'canonical' : IDL.Text, 'alias' : IDL.Text });
+  const TagAliasPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(TagAlias),
+  });
+  const TagAliasPageResponse = IDL.Variant({
+    'Ok' : TagAliasPage,
+    'Err' : IDL.Text,
+  });
+  const MetadataChange = IDL.Variant({
+    'ChaptersFromDescription' : IDL.Record({
+      'new' : IDL.Opt(IDL.Bool),
+      'old' : IDL.Opt(IDL.Bool),
+    }),
+    'Tags' : IDL.Record({ 'new' : IDL.Vec(Tag), 'old' : IDL.Vec(Tag) }),
+    'Description' : IDL.Record({
+      'new' : IDL.Opt(IDL.Text),
+      'old' : IDL.Opt(IDL.Text),
+    }),
+    'PrimaryLanguage' : IDL.Record({
+      'new' : IDL.Opt(IDL.Text),
+      'old' : IDL.Opt(IDL.Text),
+    }),
+    'Chapters' : IDL.Record({
+      'new' : IDL.Opt(IDL.Vec(Chapter)),
+      'old' : IDL.Opt(IDL.Vec(Chapter)),
+    }),
+    'SoundId' : IDL.Record({
+      'new' : IDL.Opt(IDL.Text),
+      'old' : IDL.Opt(IDL.Text),
+    }),
+    'StorageRef' : IDL.Record({
+      'new' : IDL.Opt(StorageRef),
+      'old' : IDL.Opt(StorageRef),
+    }),
+    'Title' : IDL.Record({ 'new' : IDL.Text, 'old' : IDL.Text }),
+    'Renditions' : IDL.Record({
+      'new' : IDL.Vec(Rendition),
+      'old' : IDL.Vec(Rendition),
+    }),
+    'DurationSec' : IDL.Record({
+      'new' : IDL.Opt(IDL.Nat32),
+      'old' : IDL.Opt(IDL.Nat32),
+    }),
+    'ContentRating' : IDL.Record({
+      'new' : IDL.Opt(ContentRating),
+      'old' : IDL.Opt(ContentRating),
+    }),
+    'AllowComments' : IDL.Record({
+      'new' : IDL.Opt(IDL.Bool),
+      'old' : IDL.Opt(IDL.Bool),
+    }),
+    'AspectRatio' : IDL.Record({
+      'new' : IDL.Opt(AspectRatio),
+      'old' : IDL.Opt(AspectRatio),
+    }),
+    'AllowRemixes' : IDL.Record({
+      'new' : IDL.Opt(IDL.Bool),
+      'old' : IDL.Opt(IDL.Bool),
+    }),
+  });
+  const VideoRevision = IDL.Record({
+    'reverted_to' : IDL.Opt(IDL.Nat32),
+    'editor_principal' : Principal,
+    'timestamp' : IDL.Nat64,
+    'changes' : IDL.Vec(MetadataChange),
+    'revision' : IDL.Nat32,
+  });
+  const VideoRevisionPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(VideoRevision),
+  });
+  const VideoRevisionPageResponse = IDL.Variant({
+    'Ok' : VideoRevisionPage,
+    'Err' : IDL.Text,
+  });
+  const PinnedVideosResponse = IDL.Variant({
+    'Ok' : IDL.Vec(VideoId),
     'Err' : IDL.Text,
   });
   const Text = IDL.Text;
@@ -78,51 +436,316 @@ export const idlFactory = ({ IDL }) => {
     'Ok' : IPFSProxyResult,
     'Err' : IPFSProxyError,
   });
-  const TxHash = IDL.Text;
   const TipRecordResponse = IDL.Variant({ 'Ok' : TipRecord, 'Err' : IDL.Text });
+  const CaptionTracksResponse = IDL.Variant({
+    'Ok' : IDL.Vec(CaptionTrack),
+    'Err' : IDL.Text,
+  });
+  const ResolvedClip = IDL.Record({
+    'clip_id' : IDL.Text,
+    'start_sec' : IDL.Nat32,
+    'end_sec' : IDL.Nat32,
+    'storage_ref' : IDL.Opt(StorageRef),
+    'source_video_id' : IDL.Text,
+    'renditions' : IDL.Vec(Rendition),
+    'media_fragment' : IDL.Text,
+  });
+  const ResolvedClipResponse = IDL.Variant({
+    'Ok' : ResolvedClip,
+    'Err' : IDL.Text,
+  });
   const Name = IDL.Text;
   const AvatarUrl = IDL.Text;
   const SaveMyProfileResponse = IDL.Variant({
     'Ok' : UserProfile,
     'Err' : IDL.Text,
   });
+  const ProfileSearchResult = IDL.Record({
+    'principal' : IDL.Principal,
+    'video_count' : IDL.Nat64,
+    'follower_count' : IDL.Nat64,
+    'profile' : UserProfile,
+  });
+  const ProfileSearchPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(ProfileSearchResult),
+  });
+  const ProfileSearchPageResponse = IDL.Variant({
+    'Ok' : ProfileSearchPage,
+    'Err' : IDL.Text,
+  });
+  const SoundPage = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'items' : IDL.Vec(Sound),
+  });
+  const SoundPageResponse = IDL.Variant({ 'Ok' : SoundPage, 'Err' : IDL.Text });
+  const SearchSort = IDL.Variant({
+    'MostTipped' : IDL.Null,
+    'Relevance' : IDL.Null,
+    'MostLiked' : IDL.Null,
+    'Newest' : IDL.Null,
+    'MostViewed' : IDL.Null,
+  });
+  const MatchMode = IDL.Variant({ 'All' : IDL.Null, 'Any' : IDL.Null });
+  const SearchQuery = IDL.Record({
+    'cursor' : IDL.Opt(IDL.Text),
+    'uploaded_after' : IDL.Opt(IDL.Nat64),
+    'sort' : IDL.Opt(SearchSort),
+    'text' : IDL.Opt(IDL.Text),
+    'tags_all' : IDL.Opt(IDL.Vec(Tag)),
+    'tags_any' : IDL.Opt(IDL.Vec(Tag)),
+    'match_mode' : IDL.Opt(MatchMode),
+    'limit' : IDL.Opt(IDL.Nat32),
+    'max_duration_sec' : IDL.Opt(IDL.Nat32),
+    'language' : IDL.Opt(IDL.Text),
+    'min_duration_sec' : IDL.Opt(IDL.Nat32),
+    'uploader' : IDL.Opt(Principal),
+    'uploaded_before' : IDL.Opt(IDL.Nat64),
+    'content_rating' : IDL.Opt(ContentRating),
+    'tags_none' : IDL.Opt(IDL.Vec(Tag)),
+  });
+  const SearchResults = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'did_you_mean' : IDL.Opt(IDL.Text),
+    'videos' : IDL.Vec(VideoMetadata),
+  });
+  const SearchResultsResponse = IDL.Variant({
+    'Ok' : SearchResults,
+    'Err' : IDL.Text,
+  });
+  const CreditInput = IDL.Record({
+    'principal' : Principal,
+    'role' : CreditRole,
+    'share_bps' : IDL.Nat32,
+  });
+  const CreditsResponse = IDL.Variant({
+    'Ok' : IDL.Vec(Credit),
+    'Err' : IDL.Text,
+  });
+  const StorageRefUpdate = IDL.Variant({
+    'Set' : StorageRef,
+    'Clear' : IDL.Null,
+  });
   return IDL.Service({
+    'accept_playlist_invite' : IDL.Func(
+        [IDL.Text],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'accept_video_credit' : IDL.Func([VideoId], [CreditResponse], []),
+    'add_moderator' : IDL.Func([Principal], [EmptyResponse], []),
+    'add_playlist_collaborator' : IDL.Func(
+        [IDL.Text, Principal],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'add_video_to_playlist' : IDL.Func(
+        [IDL.Text, VideoId, IDL.Opt(IDL.Nat32)],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'autocomplete' : IDL.Func(
+        [IDL.Text, AutocompleteKind, IDL.Opt(IDL.Nat32)],
+        [IDL.Vec(Suggestion)],
+        ['query'],
+      ),
+    'create_clip' : IDL.Func(
+        [VideoId, IDL.Text, IDL.Nat32, IDL.Nat32],
+        [VideoMetadataResponse],
+        [],
+      ),
+    'create_playlist' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), PlaylistVisibility],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'create_sound' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Nat32],
+        [SoundResponse],
+        [],
+      ),
     'create_video_metadata' : IDL.Func(
-        [VideoId, Title, IDL.Vec(Tag), IDL.Opt(StorageRef)],
+        [CreateVideoRequest],
         [VideoMetadataResponse],
         [],
       ),
+    'decline_video_credit' : IDL.Func([VideoId], [EmptyResponse], []),
     'delete_comment' : IDL.Func([VideoId, IDL.Nat64], [EmptyResponse], []),
+    'delete_playlist' : IDL.Func([IDL.Text], [EmptyResponse], []),
     'delete_video' : IDL.Func([VideoId], [EmptyResponse], []),
     'follow_user' : IDL.Func([Principal], [EmptyResponse], []),
-    'get_comments' : IDL.Func([VideoId], [IDL.Vec(Comment)], ['query']),
-    'get_followers' : IDL.Func([Principal], [IDL.Vec(Principal)], ['query']),
-    'get_following' : IDL.Func([Principal], [IDL.Vec(Principal)], ['query']),
-    'get_my_comments' : IDL.Func([], [IDL.Vec(Comment)], ['query']),
+    'get_channel_videos' : IDL.Func(
+        [Principal, ChannelSort, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'get_comments' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [CommentPageResponse],
+        ['query'],
+      ),
+    'get_duplicate_of' : IDL.Func([VideoId], [DuplicateOfResponse], ['query']),
+    'get_duplicate_policy' : IDL.Func([], [DuplicatePolicy], ['query']),
+    'get_followers' : IDL.Func(
+        [Principal, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PrincipalPageResponse],
+        ['query'],
+      ),
+    'get_following' : IDL.Func(
+        [Principal, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PrincipalPageResponse],
+        ['query'],
+      ),
+    'get_my_comments' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [CommentPageResponse],
+        ['query'],
+      ),
     'get_my_profile' : IDL.Func([], [GetMyProfileResponse], ['query']),
-    'get_my_received_tips' : IDL.Func([], [IDL.Vec(TipRecord)], ['query']),
-    'get_my_sent_tips' : IDL.Func([], [IDL.Vec(TipRecord)], ['query']),
-    'get_my_watch_events' : IDL.Func([], [IDL.Vec(WatchEvent)], ['query']),
-    'get_tips_for_video' : IDL.Func([VideoId], [IDL.Vec(TipRecord)], ['query']),
+    'get_my_received_tips' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TipRecordPageResponse],
+        ['query'],
+      ),
+    'get_my_sent_tips' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TipRecordPageResponse],
+        ['query'],
+      ),
+    'get_my_watch_events' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [WatchEventPageResponse],
+        ['query'],
+      ),
+    'get_playlist' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PlaylistPageResponse],
+        ['query'],
+      ),
+    'get_sound' : IDL.Func([IDL.Text], [SoundResponse], ['query']),
+    'get_tag_page' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TagPageResponse],
+        ['query'],
+      ),
+    'get_tip_splits' : IDL.Func(
+        [VideoId, TxHash, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TipRecordPageResponse],
+        ['query'],
+      ),
+    'get_tips_for_video' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TipRecordPageResponse],
+        ['query'],
+      ),
+    'get_trending_sounds' : IDL.Func(
+        [IDL.Opt(IDL.Nat32)],
+        [IDL.Vec(TrendingSound)],
+        ['query'],
+      ),
+    'get_trending_tags' : IDL.Func(
+        [TrendingWindow, IDL.Opt(IDL.Nat32)],
+        [TrendingTags],
+        ['query'],
+      ),
     'get_video_analytics' : IDL.Func(
         [VideoId],
         [VideoAnalyticsResponse],
         ['query'],
       ),
+    'get_video_ancestry' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
     'get_video_metadata' : IDL.Func(
         [VideoId],
         [VideoMetadataResponse],
         ['query'],
       ),
-    'get_watch_events' : IDL.Func([VideoId], [IDL.Vec(WatchEvent)], ['query']),
+    'get_video_remixes' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'get_watch_events' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [WatchEventPageResponse],
+        ['query'],
+      ),
     'has_pinata_jwt_configured' : IDL.Func([], [IDL.Bool], ['query']),
+    'has_webhook_secret_configured' : IDL.Func([], [IDL.Bool], ['query']),
+    'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
+    'http_request_update' : IDL.Func([HttpRequest], [HttpResponse], []),
     'is_following' : IDL.Func([Principal, Principal], [IDL.Bool], ['query']),
-    'list_all_videos' : IDL.Func([], [IDL.Vec(VideoMetadata)], ['query']),
-    'list_profiles' : IDL.Func([], [ListProfilesResponse], ['query']),
-    'list_videos_by_tag' : IDL.Func([Tag], [IDL.Vec(VideoMetadata)], ['query']),
+    'list_all_videos' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'list_clips_of_video' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'list_duplicate_flags' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [DuplicateFlagPageResponse],
+        ['query'],
+      ),
+    'list_moderators' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PrincipalPageResponse],
+        ['query'],
+      ),
+    'list_my_pending_credits' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'list_my_playlists' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PlaylistSummaryPageResponse],
+        ['query'],
+      ),
+    'list_my_trash' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'list_playlists_by_owner' : IDL.Func(
+        [Principal, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [PlaylistSummaryPageResponse],
+        ['query'],
+      ),
+    'list_profiles' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [ProfilePageResponse],
+        ['query'],
+      ),
+    'list_tag_aliases' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [TagAliasPageResponse],
+        ['query'],
+      ),
+    'list_video_revisions' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoRevisionPageResponse],
+        ['query'],
+      ),
+    'list_videos_by_sound' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
+    'list_videos_by_tag' : IDL.Func(
+        [Tag, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
+        ['query'],
+      ),
     'list_videos_by_uploader' : IDL.Func(
-        [Principal],
-        [IDL.Vec(VideoMetadata)],
+        [Principal, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [VideoPageResponse],
         ['query'],
       ),
     'log_watch_event' : IDL.Func(
@@ -130,6 +753,16 @@ export const idlFactory = ({ IDL }) => {
         [EmptyResponse],
         [],
       ),
+    'move_video_in_playlist' : IDL.Func(
+        [IDL.Text, VideoId, IDL.Nat32],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'pin_video' : IDL.Func(
+        [VideoId, IDL.Opt(IDL.Nat32)],
+        [PinnedVideosResponse],
+        [],
+      ),
     'post_comment' : IDL.Func([VideoId, Text], [CommentResponse], []),
     'proxy_ipfs_content' : IDL.Func([IDL.Text], [IPFSProxyResponse], []),
     'record_tip' : IDL.Func(
@@ -137,32 +770,92 @@ export const idlFactory = ({ IDL }) => {
         [TipRecordResponse],
         [],
       ),
+    'remove_caption_track' : IDL.Func(
+        [VideoId, IDL.Text],
+        [CaptionTracksResponse],
+        [],
+      ),
+    'remove_moderator' : IDL.Func([Principal], [EmptyResponse], []),
+    'remove_playlist_collaborator' : IDL.Func(
+        [IDL.Text, Principal],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'remove_tag_alias' : IDL.Func([IDL.Text], [EmptyResponse], []),
+    'remove_video_from_playlist' : IDL.Func(
+        [IDL.Text, VideoId],
+        [PlaylistSummaryResponse],
+        [],
+      ),
+    'resolve_clip' : IDL.Func([VideoId], [ResolvedClipResponse], ['query']),
+    'restore_video' : IDL.Func([VideoId], [VideoMetadataResponse], []),
+    'revert_video_metadata' : IDL.Func(
+        [VideoId, IDL.Nat32],
+        [VideoMetadataResponse],
+        [],
+      ),
     'save_my_profile' : IDL.Func(
         [Name, AvatarUrl],
         [SaveMyProfileResponse],
         [],
       ),
-    'search_videos' : IDL.Func(
-        [IDL.Text, IDL.Opt(IDL.Nat32), IDL.Opt(IDL.Nat32)],
-        [IDL.Vec(VideoMetadata)],
+    'search_profiles' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [ProfileSearchPageResponse],
         ['query'],
       ),
-    'search_videos_by_tags' : IDL.Func(
-        [IDL.Vec(IDL.Text), IDL.Opt(IDL.Nat32), IDL.Opt(IDL.Nat32)],
-        [IDL.Vec(VideoMetadata)],
+    'search_sounds' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text), IDL.Opt(IDL.Nat32)],
+        [SoundPageResponse],
+        ['query'],
+      ),
+    'search_videos' : IDL.Func(
+        [SearchQuery],
+        [SearchResultsResponse],
         ['query'],
       ),
+    'set_duplicate_policy' : IDL.Func([DuplicatePolicy], [EmptyResponse], []),
     'set_pinata_jwt' : IDL.Func(
         [IDL.Text, Principal],
         [IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text })],
         [],
       ),
+    'set_tag_alias' : IDL.Func([IDL.Text, IDL.Text], [EmptyResponse], []),
+    'set_video_credits' : IDL.Func(
+        [VideoId, IDL.Vec(CreditInput)],
+        [CreditsResponse],
+        [],
+      ),
+    'set_webhook_secret' : IDL.Func([IDL.Text], [EmptyResponse], []),
     'unfollow_user' : IDL.Func([Principal], [EmptyResponse], []),
+    'unpin_video' : IDL.Func([VideoId], [PinnedVideosResponse], []),
+    'update_playlist' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Opt(IDL.Text),
+          IDL.Opt(IDL.Text),
+          IDL.Opt(PlaylistVisibility),
+        ],
+        [PlaylistSummaryResponse],
+        [],
+      ),
     'update_video_metadata' : IDL.Func(
-        [VideoId, IDL.Opt(Title), IDL.Opt(IDL.Vec(Tag)), IDL.Opt(StorageRef)],
+        [
+          VideoId,
+          IDL.Opt(Title),
+          IDL.Opt(IDL.Vec(Tag)),
+          IDL.Opt(StorageRefUpdate),
+          IDL.Opt(IDL.Vec(Rendition)),
+          IDL.Opt(VideoDetails),
+        ],
         [VideoMetadataResponse],
         [],
       ),
+    'upsert_caption_track' : IDL.Func(
+        [VideoId, IDL.Text, IDL.Text, IDL.Bool, IDL.Text],
+        [CaptionTracksResponse],
+        [],
+      ),
   });
 };
 export const init = ({ IDL }) => { return []; };
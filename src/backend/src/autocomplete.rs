@@ -0,0 +1,113 @@
+use candid::{CandidType, Deserialize};
+
+const MAX_NORMALIZED_LENGTH: usize = 100;
+
+/// Longest prefix entries are ranked under. Longer prefixes are matched among
+/// the entries ranked under their first characters.
+pub const MAX_RANKED_PREFIX_CHARS: usize = 10;
+
+// Separates the normalized text from the id in title and profile keys. It
+// sorts before every printable character, so "ab" entries come before "abc".
+const KEY_SEPARATOR: char = '\u{0}';
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum AutocompleteKind {
+    Tag,
+    Title,
+    Creator,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub text: String,
+    pub id: Option<String>, // Video id for titles, principal for creators
+    pub popularity: u64,    // Videos for tags, views for titles, followers for creators
+}
+
+/// Lowercases and collapses whitespace so suggestions match however the prefix is typed
+pub fn normalize_for_autocomplete(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(MAX_NORMALIZED_LENGTH)
+        .collect()
+}
+
+/// Key for an entry whose text isn't unique, such as a title or profile name
+pub fn entry_key(text: &str, id: &str) -> String {
+    format!("{}{}{}", normalize_for_autocomplete(text), KEY_SEPARATOR, id)
+}
+
+/// Id part of an `entry_key`
+pub fn entry_id(key: &str) -> &str {
+    key.rsplit(KEY_SEPARATOR).next().unwrap_or_default()
+}
+
+/// Key prefix of the entries ranked under the first characters of `prefix`
+pub fn ranked_prefix(prefix: &str) -> String {
+    let ranked: String = prefix.chars().take(MAX_RANKED_PREFIX_CHARS).collect();
+    format!("{}{}", ranked, KEY_SEPARATOR)
+}
+
+/// Keys ranking an entry (a tag or an `entry_key`) under each prefix of its
+/// text, most popular first and then in entry order
+pub fn rank_keys(entry: &str, popularity: u64) -> Vec<String> {
+    let text = entry.split(KEY_SEPARATOR).next().unwrap_or_default();
+    text.char_indices()
+        .take(MAX_RANKED_PREFIX_CHARS)
+        .map(|(index, c)| {
+            format!(
+                "{}{}{:020}{}{}",
+                &text[..index + c.len_utf8()],
+                KEY_SEPARATOR,
+                u64::MAX - popularity,
+                KEY_SEPARATOR,
+                entry
+            )
+        })
+        .collect()
+}
+
+/// Popularity and entry of a key made by `rank_keys`
+pub fn parse_rank_key(key: &str) -> Option<(u64, &str)> {
+    let mut parts = key.splitn(3, KEY_SEPARATOR);
+    let inverted = parts.nth(1)?.parse::<u64>().ok()?;
+    Some((u64::MAX - inverted, parts.next()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_for_autocomplete() {
+        assert_eq!(normalize_for_autocomplete("  Rust   Tutorial "), "rust tutorial");
+        assert_eq!(normalize_for_autocomplete(&"a".repeat(150)).len(), 100);
+    }
+
+    #[test]
+    fn test_entry_keys() {
+        let key = entry_key("Rust  Tips", "abc123");
+        assert!(key.starts_with("rust tips"));
+        assert_eq!(entry_id(&key), "abc123");
+
+        // Shorter text sorts before longer text sharing its prefix
+        assert!(entry_key("rust", "zzz") < entry_key("rust tips", "aaa"));
+    }
+
+    #[test]
+    fn test_rank_keys() {
+        let entry = entry_key("Rust Tips and Tricks", "abc123");
+        let keys = rank_keys(&entry, 7);
+        assert_eq!(keys.len(), MAX_RANKED_PREFIX_CHARS);
+        assert!(keys[0].starts_with(&ranked_prefix("r")));
+        assert!(keys[9].starts_with(&ranked_prefix("rust tips and")));
+        assert_eq!(parse_rank_key(&keys[3]), Some((7, entry.as_str())));
+
+        // More popular entries sort first under a prefix
+        assert!(rank_keys("rusty", 8)[0] < rank_keys("rust", 7)[0]);
+        assert_eq!(rank_keys("go", 1).len(), 2);
+    }
+}
@@ -0,0 +1,240 @@
+use candid::{CandidType, Deserialize};
+
+const MAX_LABEL_LENGTH: usize = 64;
+
+/// A subtitle track attached to a video. The WebVTT body is stored separately
+/// and served at `url_path`.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct CaptionTrack {
+    pub language: String, // BCP 47 tag, e.g. "en" or "pt-BR"
+    pub label: String,    // Shown in the player's track menu
+    pub is_default: bool,
+    pub url_path: String,
+    pub cue_count: u32,
+}
+
+/// Path the track is served at through http_request
+pub fn caption_url_path(video_id: &str, language: &str) -> String {
+    format!("/captions/{}/{}.vtt", video_id, language)
+}
+
+/// Checks the language is a well-formed BCP 47 tag (primary language plus optional subtags)
+pub fn validate_language(language: &str) -> Result<(), String> {
+    let mut subtags = language.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    let primary_valid =
+        (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+    let rest_valid = subtags.all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+
+    if primary_valid && rest_valid && language.len() <= 35 {
+        Ok(())
+    } else {
+        Err(format!("Invalid language tag: {}", language))
+    }
+}
+
+pub fn validate_label(label: &str) -> Result<(), String> {
+    if label.trim().is_empty() || label.len() > MAX_LABEL_LENGTH {
+        return Err(format!(
+            "Caption label must be between 1 and {} characters",
+            MAX_LABEL_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Validates WebVTT syntax and returns the number of cues.
+///
+/// Checks the WEBVTT signature, that every cue has a well-formed timing line
+/// with start <= end, and that cues are ordered by start time. NOTE, STYLE and
+/// REGION blocks are skipped. Errors name the offending line.
+pub fn validate_webvtt(vtt: &str) -> Result<u32, String> {
+    let vtt = vtt.strip_prefix('\u{feff}').unwrap_or(vtt);
+    let lines: Vec<&str> = vtt.lines().collect();
+
+    let signature = lines.first().copied().unwrap_or_default();
+    let signature_valid = signature == "WEBVTT"
+        || signature.starts_with("WEBVTT ")
+        || signature.starts_with("WEBVTT\t");
+    if !signature_valid {
+        return Err("Line 1: file must start with WEBVTT".to_string());
+    }
+
+    // Skip the header, which runs until the first blank line
+    let mut index = 1;
+    while index < lines.len() && !lines[index].trim().is_empty() {
+        index += 1;
+    }
+
+    let mut cue_count = 0u32;
+    let mut last_start = 0u64;
+    while index < lines.len() {
+        if lines[index].trim().is_empty() {
+            index += 1;
+            continue;
+        }
+
+        let block_start = index;
+        let mut block_end = index;
+        while block_end < lines.len() && !lines[block_end].trim().is_empty() {
+            block_end += 1;
+        }
+        let block = &lines[block_start..block_end];
+        index = block_end;
+
+        let first = block[0];
+        if first.starts_with("NOTE") || first == "STYLE" || first == "REGION" {
+            continue;
+        }
+
+        // An optional cue identifier may precede the timing line
+        let (timing_offset, timing) = if first.contains("-->") {
+            (0, first)
+        } else {
+            match block.get(1) {
+                Some(line) => (1, *line),
+                None => {
+                    return Err(format!(
+                        "Line {}: cue is missing a timing line",
+                        block_start + 1
+                    ))
+                }
+            }
+        };
+        let line_number = block_start + timing_offset + 1;
+
+        let (start, end) = parse_timing_line(timing)
+            .map_err(|e| format!("Line {}: {}", line_number, e))?;
+        if end < start {
+            return Err(format!("Line {}: cue ends before it starts", line_number));
+        }
+        if start < last_start {
+            return Err(format!("Line {}: cues must be ordered by start time", line_number));
+        }
+        if let Some(offset) = block[timing_offset + 1..].iter().position(|l| l.contains("-->")) {
+            return Err(format!(
+                "Line {}: cue text cannot contain \"-->\"",
+                line_number + offset + 1
+            ));
+        }
+
+        last_start = start;
+        cue_count += 1;
+    }
+
+    if cue_count == 0 {
+        return Err("Caption track has no cues".to_string());
+    }
+    Ok(cue_count)
+}
+
+/// Parses "00:01.000 --> 00:04.000 align:start" into start and end milliseconds
+fn parse_timing_line(line: &str) -> Result<(u64, u64), String> {
+    let (start, rest) = line
+        .split_once("-->")
+        .ok_or_else(|| "expected a timing line".to_string())?;
+    let end = rest
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| "missing cue end time".to_string())?;
+
+    Ok((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+/// Parses "mm:ss.ttt" or "hh:mm:ss.ttt" into milliseconds
+fn parse_timestamp(timestamp: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid timestamp \"{}\"", timestamp);
+
+    let (clock, millis) = timestamp.split_once('.').ok_or_else(invalid)?;
+    if millis.len() != 3 || !millis.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let parts: Vec<&str> = clock.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [minutes, seconds] => ("0", *minutes, *seconds),
+        [hours, minutes, seconds] if hours.len() >= 2 => (*hours, *minutes, *seconds),
+        _ => return Err(invalid()),
+    };
+    if minutes.len() != 2 || seconds.len() != 2 {
+        return Err(invalid());
+    }
+
+    let number = |value: &str| -> Result<u64, String> {
+        if value.chars().all(|c| c.is_ascii_digit()) {
+            value.parse::<u64>().map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    };
+    let (hours, minutes, seconds) = (number(hours)?, number(minutes)?, number(seconds)?);
+    if minutes > 59 || seconds > 59 {
+        return Err(invalid());
+    }
+
+    Ok(((hours * 60 + minutes) * 60 + seconds) * 1000 + number(millis)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_VTT: &str = "WEBVTT - English subtitles
+
+NOTE This is a comment
+
+intro
+00:00.000 --> 00:02.500
+Hello there!
+
+00:02.500 --> 00:05.000 align:start position:10%
+This is a <i>short</i> video
+with two lines
+
+01:00:00.000 --> 01:00:01.000
+The end
+";
+
+    #[test]
+    fn test_valid_webvtt() {
+        assert_eq!(validate_webvtt(VALID_VTT), Ok(3));
+        assert_eq!(validate_webvtt(&format!("\u{feff}{}", VALID_VTT)), Ok(3));
+    }
+
+    #[test]
+    fn test_invalid_webvtt() {
+        assert!(validate_webvtt("").is_err());
+        assert!(validate_webvtt("1\n00:00.000 --> 00:01.000\nNo signature").is_err());
+        assert!(validate_webvtt("WEBVTT\n").is_err());
+
+        let bad_timestamp = "WEBVTT\n\n00:00.00 --> 00:01.000\nHello";
+        assert_eq!(
+            validate_webvtt(bad_timestamp),
+            Err("Line 3: invalid timestamp \"00:00.00\"".to_string())
+        );
+
+        let reversed = "WEBVTT\n\n00:05.000 --> 00:01.000\nHello";
+        assert!(validate_webvtt(reversed).is_err());
+
+        let out_of_order = "WEBVTT\n\n00:05.000 --> 00:06.000\nA\n\n00:01.000 --> 00:02.000\nB";
+        assert!(validate_webvtt(out_of_order).is_err());
+
+        let bad_seconds = "WEBVTT\n\n00:61.000 --> 01:02.000\nHello";
+        assert!(validate_webvtt(bad_seconds).is_err());
+
+        let missing_timing = "WEBVTT\n\njust some text";
+        assert!(validate_webvtt(missing_timing).is_err());
+    }
+
+    #[test]
+    fn test_validate_language() {
+        assert!(validate_language("en").is_ok());
+        assert!(validate_language("pt-BR").is_ok());
+        assert!(validate_language("zh-Hant-TW").is_ok());
+        assert!(validate_language("").is_err());
+        assert!(validate_language("english").is_err());
+        assert!(validate_language("en/../x").is_err());
+    }
+}
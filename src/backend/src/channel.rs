@@ -0,0 +1,285 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::{
+    page::{collect_page, decode_cursor, encode_cursor, Page},
+    video_metadata::VideoMetadata,
+};
+
+pub const MAX_PINNED_VIDEOS: usize = 3;
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ChannelSort {
+    Newest,
+    Oldest,
+    MostViewed,
+}
+
+impl ChannelSort {
+    fn tag(&self) -> &'static str {
+        match self {
+            ChannelSort::Newest => "n",
+            ChannelSort::Oldest => "o",
+            ChannelSort::MostViewed => "v",
+        }
+    }
+
+    /// Ascending sort key; the video id breaks ties so every position is unique
+    fn key(&self, entry: &ChannelEntry) -> String {
+        let value = match self {
+            ChannelSort::Newest => u64::MAX - entry.timestamp,
+            ChannelSort::Oldest => entry.timestamp,
+            ChannelSort::MostViewed => u64::MAX - entry.views,
+        };
+        format!("{}:{:020}:{}", self.tag(), value, entry.video_id)
+    }
+}
+
+/// What ordering a creator's video on their channel takes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelEntry {
+    pub video_id: String,
+    pub timestamp: u64,
+    pub views: u64,
+}
+
+/// Videos a creator pinned to the top of their channel, in display order
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PinnedVideos(pub Vec<String>);
+
+impl PinnedVideos {
+    /// Pins a video at `position` (clamped to the end), or at the end
+    pub fn pin(&mut self, video_id: String, position: Option<u32>) -> Result<(), String> {
+        if self.0.contains(&video_id) {
+            return Err("Video is already pinned".to_string());
+        }
+        if self.0.len() >= MAX_PINNED_VIDEOS {
+            return Err(format!("You can pin at most {} videos", MAX_PINNED_VIDEOS));
+        }
+        let position = position
+            .map(|p| (p as usize).min(self.0.len()))
+            .unwrap_or(self.0.len());
+        self.0.insert(position, video_id);
+        Ok(())
+    }
+
+    /// Unpins a video, returning whether it was pinned
+    pub fn unpin(&mut self, video_id: &str) -> bool {
+        let count_before = self.0.len();
+        self.0.retain(|id| id != video_id);
+        self.0.len() != count_before
+    }
+}
+
+/// Builds one page of a channel.
+///
+/// Pinned videos come first on the first page, in pin order, and count towards
+/// `limit`. The remaining videos follow in `sort` order. The cursor records the
+/// sort key of the last video returned, so videos uploaded between requests
+/// don't shift later pages. `load` returns the videos that can be shown; only
+/// the ones on the page are loaded.
+pub fn channel_page(
+    entries: Vec<ChannelEntry>,
+    pinned: &PinnedVideos,
+    sort: ChannelSort,
+    cursor: Option<&str>,
+    limit: usize,
+    load: impl Fn(&str) -> Option<VideoMetadata>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", sort.tag());
+    let after = match cursor {
+        Some(cursor) => {
+            let key = decode_cursor(cursor)?;
+            if !key.starts_with(&prefix) {
+                return Err("Invalid cursor".to_string());
+            }
+            Some(key)
+        }
+        None => None,
+    };
+
+    let mut items = Vec::new();
+    if after.is_none() {
+        items.extend(pinned.0.iter().filter_map(|video_id| load(video_id)).take(limit));
+    }
+
+    let mut keyed: Vec<(String, ChannelEntry)> = entries
+        .into_iter()
+        .filter(|entry| !pinned.0.contains(&entry.video_id))
+        .map(|entry| (sort.key(&entry), entry))
+        .filter(|(key, _)| after.as_ref().is_none_or(|after| key > after))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut rest = keyed
+        .into_iter()
+        .filter_map(|(key, entry)| load(&entry.video_id).map(|metadata| (key, metadata)))
+        .peekable();
+
+    let remaining = limit.saturating_sub(items.len());
+    if remaining == 0 {
+        // The first page was filled by pinned videos alone; continue from the start
+        let next_cursor = rest.peek().map(|_| encode_cursor(&prefix));
+        return Ok(Page { items, next_cursor });
+    }
+    let page = collect_page(rest, remaining);
+    items.extend(page.items);
+    Ok(Page {
+        items,
+        next_cursor: page.next_cursor,
+    })
+}
+
+impl Storable for PinnedVideos {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(&self.0).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Self(Decode!(bytes.as_ref(), Vec<String>).unwrap())
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 500,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn video(video_id: &str, timestamp: u64) -> VideoMetadata {
+        VideoMetadata {
+            video_id: video_id.to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: vec![],
+            title: video_id.to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    fn ids(page: &Page<VideoMetadata>) -> Vec<&str> {
+        page.items.iter().map(|v| v.video_id.as_str()).collect()
+    }
+
+    fn entry(video_id: &str, timestamp: u64, views: u64) -> ChannelEntry {
+        ChannelEntry {
+            video_id: video_id.to_string(),
+            timestamp,
+            views,
+        }
+    }
+
+    fn channel() -> Vec<ChannelEntry> {
+        vec![
+            entry("a", 100, 5),
+            entry("b", 200, 50),
+            entry("c", 300, 0),
+            entry("d", 400, 20),
+            entry("e", 500, 1),
+        ]
+    }
+
+    /// Every video but "hidden" can be shown
+    fn load(video_id: &str) -> Option<VideoMetadata> {
+        (video_id != "hidden").then(|| video(video_id, 0))
+    }
+
+    #[test]
+    fn test_pinned_first_then_sorted_pages() {
+        let pinned = PinnedVideos(vec!["c".to_string(), "a".to_string()]);
+
+        let first = channel_page(channel(), &pinned, ChannelSort::Newest, None, 3, load).unwrap();
+        assert_eq!(ids(&first), vec!["c", "a", "e"]);
+
+        let second =
+            channel_page(channel(), &pinned, ChannelSort::Newest, first.next_cursor.as_deref(), 3, load).unwrap();
+        assert_eq!(ids(&second), vec!["d", "b"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let none = PinnedVideos::default();
+
+        let oldest = channel_page(channel(), &none, ChannelSort::Oldest, None, 10, load).unwrap();
+        assert_eq!(ids(&oldest), vec!["a", "b", "c", "d", "e"]);
+
+        let most_viewed = channel_page(channel(), &none, ChannelSort::MostViewed, None, 2, load).unwrap();
+        assert_eq!(ids(&most_viewed), vec!["b", "d"]);
+        let next = channel_page(
+            channel(),
+            &none,
+            ChannelSort::MostViewed,
+            most_viewed.next_cursor.as_deref(),
+            10,
+            load,
+        )
+        .unwrap();
+        assert_eq!(ids(&next), vec!["a", "e", "c"]);
+    }
+
+    #[test]
+    fn test_new_uploads_dont_shift_pages() {
+        let none = PinnedVideos::default();
+        let first = channel_page(channel(), &none, ChannelSort::Newest, None, 2, load).unwrap();
+
+        let mut videos = channel();
+        videos.push(entry("f", 600, 0));
+        videos.push(entry("hidden", 250, 0));
+        let second =
+            channel_page(videos, &none, ChannelSort::Newest, first.next_cursor.as_deref(), 2, load).unwrap();
+
+        assert_eq!(ids(&second), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn test_pinned_fill_first_page_and_cursor_checks() {
+        let pinned = PinnedVideos(vec!["c".to_string(), "a".to_string()]);
+
+        let first = channel_page(channel(), &pinned, ChannelSort::Newest, None, 2, load).unwrap();
+        assert_eq!(ids(&first), vec!["c", "a"]);
+        let second =
+            channel_page(channel(), &pinned, ChannelSort::Newest, first.next_cursor.as_deref(), 10, load).unwrap();
+        assert_eq!(ids(&second), vec!["e", "d", "b"]);
+
+        // Cursors from another sort order are rejected
+        assert!(channel_page(channel(), &pinned, ChannelSort::Oldest, first.next_cursor.as_deref(), 2, load).is_err());
+        assert!(channel_page(channel(), &pinned, ChannelSort::Newest, Some("garbage"), 2, load).is_err());
+    }
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let mut pinned = PinnedVideos::default();
+        pinned.pin("a".to_string(), None).unwrap();
+        pinned.pin("b".to_string(), Some(0)).unwrap();
+        pinned.pin("c".to_string(), None).unwrap();
+        assert_eq!(pinned.0, vec!["b", "a", "c"]);
+
+        assert!(pinned.pin("d".to_string(), None).is_err());
+        assert!(pinned.unpin("a"));
+        assert!(pinned.pin("b".to_string(), None).is_err());
+    }
+}
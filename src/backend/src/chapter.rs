@@ -0,0 +1,168 @@
+use candid::{CandidType, Deserialize};
+
+const MAX_CHAPTERS: usize = 100;
+const MAX_CHAPTER_TITLE_LENGTH: usize = 100;
+
+/// A navigation marker; the chapter runs until the next marker or the end of the video
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start_sec: u32,
+    pub title: String,
+}
+
+/// Validates chapter markers and returns them sorted by start time.
+///
+/// Every marker must start before the end of the video (when the duration is
+/// known) and no two markers may start at the same second, since the second
+/// one would have zero length.
+pub fn validate_chapters(mut chapters: Vec<Chapter>, duration_sec: Option<u32>) -> Result<Vec<Chapter>, String> {
+    if chapters.len() > MAX_CHAPTERS {
+        return Err(format!("A video can have at most {} chapters", MAX_CHAPTERS));
+    }
+
+    for chapter in chapters.iter_mut() {
+        chapter.title = chapter.title.trim().to_string();
+        if chapter.title.is_empty() || chapter.title.chars().count() > MAX_CHAPTER_TITLE_LENGTH {
+            return Err(format!(
+                "Chapter titles must be between 1 and {} characters",
+                MAX_CHAPTER_TITLE_LENGTH
+            ));
+        }
+        if let Some(duration) = duration_sec {
+            if chapter.start_sec >= duration {
+                return Err(format!(
+                    "Chapter \"{}\" starts after the end of the video",
+                    chapter.title
+                ));
+            }
+        }
+    }
+
+    chapters.sort_by_key(|chapter| chapter.start_sec);
+    if let Some(pair) = chapters.windows(2).find(|pair| pair[0].start_sec == pair[1].start_sec) {
+        return Err(format!(
+            "Chapters \"{}\" and \"{}\" overlap",
+            pair[0].title, pair[1].title
+        ));
+    }
+
+    Ok(chapters)
+}
+
+/// Extracts chapter markers from lines that start with a timestamp, e.g.
+/// "2:30 Setup" or "1:02:03 - Outro". Returns markers in description order.
+pub fn parse_chapters(description: &str) -> Vec<Chapter> {
+    description
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches(|c: char| c.is_whitespace() || "-*•(".contains(c));
+            let (timestamp, rest) = line.split_once(char::is_whitespace)?;
+            let start_sec = parse_timestamp(timestamp.trim_end_matches(')'))?;
+            let title = rest
+                .trim()
+                .trim_start_matches(|c: char| "-–—:|".contains(c))
+                .trim();
+            if title.is_empty() {
+                return None;
+            }
+            Some(Chapter {
+                start_sec,
+                title: title.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses "m:ss", "mm:ss" or "h:mm:ss" into seconds
+fn parse_timestamp(timestamp: &str) -> Option<u32> {
+    let parts: Vec<&str> = timestamp.split(':').collect();
+    let well_formed = parts
+        .iter()
+        .all(|part| (1..=2).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()));
+    if !well_formed {
+        return None;
+    }
+
+    let number = |part: &str| part.parse::<u32>().ok();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [minutes, seconds] if seconds.len() == 2 => (0, number(minutes)?, number(seconds)?),
+        [hours, minutes, seconds] if minutes.len() == 2 && seconds.len() == 2 => {
+            (number(hours)?, number(minutes)?, number(seconds)?)
+        }
+        _ => return None,
+    };
+    if (parts.len() == 3 && minutes > 59) || seconds > 59 {
+        return None;
+    }
+
+    Some((hours * 60 + minutes) * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start_sec: u32, title: &str) -> Chapter {
+        Chapter {
+            start_sec,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_sorts_chapters() {
+        let chapters = vec![chapter(90, "Outro"), chapter(0, "Intro"), chapter(30, "Main")];
+
+        let validated = validate_chapters(chapters, Some(120)).unwrap();
+
+        assert_eq!(
+            validated,
+            vec![chapter(0, "Intro"), chapter(30, "Main"), chapter(90, "Outro")]
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_chapters() {
+        // Past the end of the video
+        assert!(validate_chapters(vec![chapter(0, "Intro"), chapter(120, "Outro")], Some(120)).is_err());
+        // Same start time
+        assert!(validate_chapters(vec![chapter(10, "A"), chapter(10, "B")], None).is_err());
+        // Empty title
+        assert!(validate_chapters(vec![chapter(0, "  ")], None).is_err());
+        // Unknown duration only checks ordering
+        assert!(validate_chapters(vec![chapter(0, "Intro"), chapter(5000, "Late")], None).is_ok());
+    }
+
+    #[test]
+    fn test_parse_chapters_from_description() {
+        let description = "My trip to the coast!
+
+0:00 Intro
+2:30 - Setting up camp
+(10:05) Sunset
+1:02:03 | Night sky
+Not a chapter 3:00
+4:5 Bad timestamp
+";
+
+        assert_eq!(
+            parse_chapters(description),
+            vec![
+                chapter(0, "Intro"),
+                chapter(150, "Setting up camp"),
+                chapter(605, "Sunset"),
+                chapter(3723, "Night sky"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("2:30"), Some(150));
+        assert_eq!(parse_timestamp("02:30"), Some(150));
+        assert_eq!(parse_timestamp("1:00:00"), Some(3600));
+        assert_eq!(parse_timestamp("2:60"), None);
+        assert_eq!(parse_timestamp("1:5:00"), None);
+        assert_eq!(parse_timestamp("abc"), None);
+    }
+}
@@ -0,0 +1,96 @@
+use candid::{CandidType, Deserialize};
+
+use crate::storage_ref::{Rendition, StorageRef};
+
+pub const MAX_CLIP_LENGTH_SEC: u32 = 60;
+
+/// Segment of a source video that a clip plays
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClipSource {
+    pub source_video_id: String,
+    pub start_sec: u32,
+    pub end_sec: u32,
+}
+
+/// What a player needs to play a clip: the source media and the segment to play
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResolvedClip {
+    pub clip_id: String,
+    pub source_video_id: String,
+    pub storage_ref: Option<StorageRef>, // Url references carry the fragment already
+    pub renditions: Vec<Rendition>,
+    pub start_sec: u32,
+    pub end_sec: u32,
+    pub media_fragment: String, // e.g. "t=30,45"
+}
+
+/// Checks the segment is non-empty, short enough and inside the source video
+pub fn validate_clip_range(start_sec: u32, end_sec: u32, source_duration_sec: u32) -> Result<(), String> {
+    if start_sec >= end_sec {
+        return Err("Clip must end after it starts".to_string());
+    }
+    if end_sec - start_sec > MAX_CLIP_LENGTH_SEC {
+        return Err(format!(
+            "Clips can be at most {} seconds long",
+            MAX_CLIP_LENGTH_SEC
+        ));
+    }
+    if end_sec > source_duration_sec {
+        return Err("Clip ends after the end of the source video".to_string());
+    }
+    Ok(())
+}
+
+/// Media fragment (W3C Media Fragments URI) for the segment
+pub fn media_fragment(start_sec: u32, end_sec: u32) -> String {
+    format!("t={},{}", start_sec, end_sec)
+}
+
+/// Adds the fragment to URL references, which players can seek with directly
+pub fn with_fragment(storage_ref: StorageRef, fragment: &str) -> StorageRef {
+    match storage_ref {
+        StorageRef::Url { url } => {
+            let base = url.split('#').next().unwrap_or_default();
+            StorageRef::Url {
+                url: format!("{}#{}", base, fragment),
+            }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_clip_range() {
+        assert!(validate_clip_range(30, 45, 120).is_ok());
+        assert!(validate_clip_range(60, 120, 120).is_ok());
+        assert!(validate_clip_range(45, 45, 120).is_err());
+        assert!(validate_clip_range(50, 40, 120).is_err());
+        assert!(validate_clip_range(0, 61, 120).is_err());
+        assert!(validate_clip_range(110, 121, 120).is_err());
+    }
+
+    #[test]
+    fn test_with_fragment() {
+        let fragment = media_fragment(30, 45);
+        assert_eq!(fragment, "t=30,45");
+
+        let url = StorageRef::Url {
+            url: "https://example.com/video.mp4#t=1,2".to_string(),
+        };
+        assert_eq!(
+            with_fragment(url, &fragment),
+            StorageRef::Url {
+                url: "https://example.com/video.mp4#t=30,45".to_string()
+            }
+        );
+
+        let livepeer = StorageRef::Livepeer {
+            playback_id: "f5eese9wwl88k4g8".to_string(),
+        };
+        assert_eq!(with_fragment(livepeer.clone(), &fragment), livepeer);
+    }
+}
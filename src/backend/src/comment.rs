@@ -13,7 +13,7 @@ pub struct Comment {
 }
 
 impl Storable for Comment {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
@@ -32,7 +32,7 @@ impl Storable for Comment {
 pub struct CommentList(pub Vec<Comment>);
 
 impl Storable for CommentList {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.0).unwrap())
     }
 
@@ -109,10 +109,11 @@ mod tests {
         ];
 
         // Test to_bytes for vector
-        let bytes = comments.to_bytes();
+        let list = CommentList(comments.clone());
+        let bytes = list.to_bytes();
         
         // Test from_bytes for vector
-        let deserialized_comments = Vec::<Comment>::from_bytes(bytes);
+        let deserialized_comments = CommentList::from_bytes(bytes).0;
         
         // Verify they match
         assert_eq!(comments, deserialized_comments);
@@ -123,7 +124,7 @@ mod tests {
         map.insert("video123".to_string(), comments);
         
         // Verify we can add multiple comments to the same key
-        let mut comments_for_video = map.get("video123".to_string()).unwrap().clone();
+        let mut comments_for_video = map.get("video123").unwrap().clone();
         comments_for_video.push(Comment {
             commenter_principal: principal,
             video_id: "video123".to_string(),
@@ -134,10 +135,10 @@ mod tests {
         map.insert("video123".to_string(), comments_for_video);
         
         // Verify we have 3 comments now
-        assert_eq!(map.get("video123".to_string()).unwrap().len(), 3);
+        assert_eq!(map.get("video123").unwrap().len(), 3);
         
         // Verify the chronological ordering
-        let video_comments = map.get("video123".to_string()).unwrap();
+        let video_comments = map.get("video123").unwrap();
         for i in 1..video_comments.len() {
             assert!(video_comments[i].timestamp > video_comments[i-1].timestamp);
         }
@@ -0,0 +1,96 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+const MAX_VALUE_SIZE: u32 = 300;
+
+/// What `create_video_metadata` does when an upload's content hash is already indexed.
+/// Defaults to Flag since the hash is reported by the uploader.
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum DuplicatePolicy {
+    Reject,
+    #[default]
+    Flag, // Accept the upload and record it for moderators
+}
+
+/// An upload whose content hash matches an earlier video. The hash is reported
+/// by the uploader, so a flag is a hint for moderators rather than proof.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct DuplicateFlag {
+    pub video_id: String,
+    pub duplicate_of: String, // The first video uploaded with the same content hash
+    pub uploader_principal: Principal,
+    pub flagged_at: u64,
+}
+
+/// Checks a hex encoded SHA-256 digest and returns it in lowercase
+pub fn normalize_content_hash(hash: &str) -> Result<String, String> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Content hash must be a hex encoded SHA-256 digest".to_string());
+    }
+    Ok(hash.to_ascii_lowercase())
+}
+
+impl Storable for DuplicatePolicy {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 50,
+        is_fixed_size: false,
+    };
+}
+
+impl Storable for DuplicateFlag {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_content_hash() {
+        let hash = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(normalize_content_hash(hash), Ok(hash.to_ascii_lowercase()));
+
+        assert!(normalize_content_hash("").is_err());
+        assert!(normalize_content_hash(&hash[..63]).is_err());
+        assert!(normalize_content_hash(&format!("{}g", &hash[..63])).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_flag_serialization() {
+        let principal_bytes = [
+            10, 116, 101, 115, 116, 45, 112, 114, 105, 110, 99, 105, 112, 97, 108,
+        ];
+        let flag = DuplicateFlag {
+            video_id: "5Hx9qYtK".to_string(),
+            duplicate_of: "2NEpo7TZ".to_string(),
+            uploader_principal: Principal::from_slice(&principal_bytes),
+            flagged_at: 1234567890,
+        };
+
+        assert_eq!(DuplicateFlag::from_bytes(flag.to_bytes()), flag);
+        assert_eq!(
+            DuplicatePolicy::from_bytes(DuplicatePolicy::Reject.to_bytes()),
+            DuplicatePolicy::Reject
+        );
+    }
+}
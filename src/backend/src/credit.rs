@@ -0,0 +1,158 @@
+use candid::{CandidType, Deserialize, Principal};
+
+pub const MAX_CREDITS_PER_VIDEO: usize = 10;
+pub const TOTAL_SHARE_BPS: u32 = 10_000; // 100%
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CreditRole {
+    CoCreator,
+    Performer,
+    Editor,
+    Camera,
+    Music,
+    Writer,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CreditStatus {
+    Pending,  // Waiting for the co-creator to accept
+    Accepted, // Shown on the video and paid a share of tips
+}
+
+/// A collaborator credited on a video
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Credit {
+    pub principal: Principal,
+    pub role: CreditRole,
+    pub share_bps: u32, // Share of tips in basis points, the uploader keeps the rest
+    pub status: CreditStatus,
+}
+
+/// A credit as proposed by the uploader
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct CreditInput {
+    pub principal: Principal,
+    pub role: CreditRole,
+    pub share_bps: u32,
+}
+
+/// Validates proposed credits and merges them with the current ones.
+///
+/// Credits whose role and share are unchanged keep their status; new or
+/// changed credits must be accepted (again) by the co-creator.
+pub fn merge_credits(
+    uploader: &Principal,
+    current: &[Credit],
+    proposed: Vec<CreditInput>,
+) -> Result<Vec<Credit>, String> {
+    if proposed.len() > MAX_CREDITS_PER_VIDEO {
+        return Err(format!(
+            "A video can credit at most {} co-creators",
+            MAX_CREDITS_PER_VIDEO
+        ));
+    }
+
+    let mut total_bps = 0u32;
+    let mut credits: Vec<Credit> = Vec::with_capacity(proposed.len());
+    for input in proposed {
+        if input.principal == *uploader {
+            return Err("The uploader can't be credited as a co-creator".to_string());
+        }
+        if credits.iter().any(|credit| credit.principal == input.principal) {
+            return Err(format!("{} is credited more than once", input.principal));
+        }
+        total_bps = total_bps.saturating_add(input.share_bps);
+        if total_bps > TOTAL_SHARE_BPS {
+            return Err("Credit shares can't add up to more than 10000 basis points".to_string());
+        }
+
+        let unchanged = current.iter().find(|credit| {
+            credit.principal == input.principal
+                && credit.role == input.role
+                && credit.share_bps == input.share_bps
+        });
+        credits.push(Credit {
+            principal: input.principal,
+            role: input.role,
+            share_bps: input.share_bps,
+            status: unchanged.map(|c| c.status).unwrap_or(CreditStatus::Pending),
+        });
+    }
+
+    Ok(credits)
+}
+
+/// Splits `amount` by basis-point shares, rounding each share down.
+/// Returns the amount for each share and what is left for the uploader.
+pub fn split_amount(amount: u64, shares_bps: &[u32]) -> (Vec<u64>, u64) {
+    let parts: Vec<u64> = shares_bps
+        .iter()
+        .map(|bps| (amount as u128 * *bps as u128 / TOTAL_SHARE_BPS as u128) as u64)
+        .collect();
+    let remainder = amount - parts.iter().sum::<u64>();
+    (parts, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(byte: u8) -> Principal {
+        Principal::from_slice(&[byte; 10])
+    }
+
+    fn input(byte: u8, share_bps: u32) -> CreditInput {
+        CreditInput {
+            principal: principal(byte),
+            role: CreditRole::Performer,
+            share_bps,
+        }
+    }
+
+    #[test]
+    fn test_merge_credits_keeps_accepted_status() {
+        let uploader = principal(0);
+        let current = vec![
+            Credit {
+                principal: principal(1),
+                role: CreditRole::Performer,
+                share_bps: 2000,
+                status: CreditStatus::Accepted,
+            },
+            Credit {
+                principal: principal(2),
+                role: CreditRole::Performer,
+                share_bps: 1000,
+                status: CreditStatus::Accepted,
+            },
+        ];
+
+        let merged = merge_credits(&uploader, &current, vec![input(1, 2000), input(2, 1500), input(3, 500)]).unwrap();
+
+        let statuses: Vec<CreditStatus> = merged.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![CreditStatus::Accepted, CreditStatus::Pending, CreditStatus::Pending]
+        );
+    }
+
+    #[test]
+    fn test_merge_credits_validation() {
+        let uploader = principal(0);
+
+        assert!(merge_credits(&uploader, &[], vec![input(0, 100)]).is_err());
+        assert!(merge_credits(&uploader, &[], vec![input(1, 100), input(1, 200)]).is_err());
+        assert!(merge_credits(&uploader, &[], vec![input(1, 6000), input(2, 4001)]).is_err());
+        assert!(merge_credits(&uploader, &[], vec![input(1, 6000), input(2, 4000)]).is_ok());
+        assert!(merge_credits(&uploader, &[], (1..=11).map(|b| input(b, 10)).collect()).is_err());
+    }
+
+    #[test]
+    fn test_split_amount() {
+        assert_eq!(split_amount(1000, &[2500, 1000]), (vec![250, 100], 650));
+        // Rounding dust goes to the uploader
+        assert_eq!(split_amount(10, &[3333, 3333]), (vec![3, 3], 4));
+        assert_eq!(split_amount(u64::MAX, &[10_000]), (vec![u64::MAX], 0));
+        assert_eq!(split_amount(500, &[]), (vec![], 500));
+    }
+}
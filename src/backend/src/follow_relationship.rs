@@ -21,7 +21,7 @@ pub struct FollowRelationship {
 pub struct FollowRelationshipList(pub Vec<FollowRelationship>);
 
 impl Storable for FollowRelationshipList {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
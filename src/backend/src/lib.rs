@@ -6,20 +6,51 @@ mod watch_event;
 mod tip_record;
 mod comment;
 mod follow_relationship;
+mod video_id;
+mod scheduler;
+mod video_revision;
+mod storage_ref;
+mod processing_status;
+mod caption_track;
+mod chapter;
+mod content_hash;
+mod playlist;
+mod remix;
+mod sound;
+mod page;
+mod channel;
+mod credit;
+mod clip;
+mod search_index;
+mod autocomplete;
+mod video_stats;
+mod search_query;
+mod tag;
+mod trending;
 
 // Re-export IPFS proxy methods as needed
 // These are currently not used directly but are available via canister interface
 // use service::ipfs_proxy::{proxy_ipfs_content, has_pinata_jwt_configured, set_pinata_jwt};
 
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
-use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
 use std::cell::RefCell;
-use user_profile::UserProfile;
+use user_profile::{ProfileStats, UserProfile};
 use video_metadata::VideoMetadata;
-use watch_event::WatchEventList;
-use tip_record::TipRecordList;
-use comment::CommentList;
+use watch_event::{WatchEvent, WatchEventList};
+use tip_record::{TipRecord, TipRecordList};
+use comment::{Comment, CommentList};
 use follow_relationship::{FollowRelationship, FollowRelationshipList};
+use video_id::VideoIdState;
+use video_revision::VideoRevision;
+use content_hash::{DuplicateFlag, DuplicatePolicy};
+use playlist::Playlist;
+use sound::Sound;
+use channel::PinnedVideos;
+use search_index::IndexedDocument;
+use video_stats::VideoStats;
+use tag::TagStats;
+use trending::{TagActivity, TrendingBoards};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -39,19 +70,25 @@ thread_local! {
         )
     );
 
-    static WATCH_LOG: RefCell<StableBTreeMap<String, WatchEventList, Memory>> = RefCell::new(
+    // Per-video lists from before watch events were stored one per entry,
+    // drained into WATCH_LOG on upgrade
+    static LEGACY_WATCH_LOG: RefCell<StableBTreeMap<String, WatchEventList, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))),
         )
     );
 
-    static TIP_RECORDS: RefCell<StableBTreeMap<String, TipRecordList, Memory>> = RefCell::new(
+    // Per-video lists from before tips were stored one per entry, drained
+    // into TIP_RECORDS on upgrade
+    static LEGACY_TIP_RECORDS: RefCell<StableBTreeMap<String, TipRecordList, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
         )
     );
 
-    static COMMENTS: RefCell<StableBTreeMap<String, CommentList, Memory>> = RefCell::new(
+    // Per-video lists from before comments were stored one per entry, drained
+    // into COMMENTS on upgrade
+    static LEGACY_COMMENTS: RefCell<StableBTreeMap<String, CommentList, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4))),
         )
@@ -62,4 +99,451 @@ thread_local! {
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5))),
         )
     );
+
+    static VIDEO_ID_STATE: RefCell<StableCell<VideoIdState, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6))),
+            VideoIdState::default(),
+        ).expect("Failed to initialize video id state")
+    );
+
+    // Maps "principal:idempotency_key" to the video id created for that request
+    static VIDEO_IDEMPOTENCY_KEYS: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7))),
+        )
+    );
+
+    // Soft-deleted videos awaiting purge, keyed "expires_at:video_id" with the
+    // expiry (seconds) zero-padded so the next to expire sort first
+    static VIDEO_TRASH: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8))),
+        )
+    );
+
+    // Principals granted the moderator role, mapped to when it was granted
+    static MODERATORS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9))),
+        )
+    );
+
+    // Shared secret for verifying Livepeer webhook signatures, empty until configured
+    static WEBHOOK_SECRET: RefCell<StableCell<String, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11))),
+            String::new(),
+        ).expect("Failed to initialize webhook secret")
+    );
+
+    // WebVTT caption files keyed by "video_id:language"
+    static CAPTION_CONTENT: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12))),
+        )
+    );
+
+    // Uploads by SHA-256 content hash, keyed "content_hash:timestamp:video_id" with the
+    // upload time zero-padded so the first upload of a file sorts first
+    static CONTENT_HASH_INDEX: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+        )
+    );
+
+    static DUPLICATE_POLICY: RefCell<StableCell<DuplicatePolicy, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14))),
+            DuplicatePolicy::default(),
+        ).expect("Failed to initialize duplicate policy")
+    );
+
+    // Uploads whose content hash matches an earlier video, keyed by video id
+    static DUPLICATE_FLAGS: RefCell<StableBTreeMap<String, DuplicateFlag, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15))),
+        )
+    );
+
+    static PLAYLISTS: RefCell<StableBTreeMap<String, Playlist, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16))),
+        )
+    );
+
+    static PLAYLIST_ID_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17))),
+            0,
+        ).expect("Failed to initialize playlist id counter")
+    );
+
+    // Playlist membership keyed by "video_id:playlist_id", mapped to when the video was added
+    static PLAYLIST_ENTRIES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18))),
+        )
+    );
+
+    // Remixes keyed by "parent_video_id:video_id", mapped to when the remix was created
+    static REMIX_LINKS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19))),
+        )
+    );
+
+    static SOUNDS: RefCell<StableBTreeMap<String, Sound, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20))),
+        )
+    );
+
+    static SOUND_ID_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21))),
+            0,
+        ).expect("Failed to initialize sound id counter")
+    );
+
+    // Sound usage keyed by "sound_id:video_id", mapped to when the video picked the sound
+    static SOUND_USES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22))),
+        )
+    );
+
+    // Videos each creator pinned to their channel, keyed by principal
+    static CHANNEL_PINS: RefCell<StableBTreeMap<String, PinnedVideos, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(23))),
+        )
+    );
+
+    // Clips keyed by "source_video_id:clip_id", mapped to when the clip was created
+    static CLIP_LINKS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24))),
+        )
+    );
+
+    // Search index postings keyed by "term\0inverted frequency\0video_id", mapped
+    // to the term's weighted frequency, so a term's strongest videos come first
+    static SEARCH_POSTINGS: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25))),
+        )
+    );
+
+    // Indexed terms per video, keyed by video id
+    static SEARCH_DOCUMENTS: RefCell<StableBTreeMap<String, IndexedDocument, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(26))),
+        )
+    );
+
+    // Sum of indexed document lengths, for the average length BM25 normalizes by
+    static SEARCH_TOTAL_LENGTH: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(27))),
+            0,
+        ).expect("Failed to initialize search index length")
+    );
+
+    // Search vocabulary: each indexed term, mapped to how many videos use it
+    static SEARCH_TERMS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(28))),
+        )
+    );
+
+    // Trigram index over the vocabulary for typo-tolerant search, keyed by "trigram:term"
+    static SEARCH_TRIGRAMS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(29))),
+        )
+    );
+
+    // Autocomplete: normalized tag, mapped to how many videos use it
+    static AUTOCOMPLETE_TAGS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(30))),
+        )
+    );
+
+    // Autocomplete: "normalized title\0video_id", mapped to the video's views
+    static AUTOCOMPLETE_TITLES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(31))),
+        )
+    );
+
+    // Autocomplete: "normalized profile name\0principal", mapped to the creator's followers
+    static AUTOCOMPLETE_PROFILES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(32))),
+        )
+    );
+
+    // Followers keyed by "followed:follower", mapped to when the follow happened
+    static FOLLOWERS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(33))),
+        )
+    );
+
+    // View, like and tip totals per video
+    static VIDEO_STATS: RefCell<StableBTreeMap<String, VideoStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(34))),
+        )
+    );
+
+    // Profile terms: "term:principal" for each word of a profile's name and its address
+    static PROFILE_TERMS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(35))),
+        )
+    );
+
+    // Videos keyed by "uploader:video_id", mapped to the upload timestamp
+    static UPLOADER_VIDEOS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(36))),
+        )
+    );
+
+    // Tag aliases, mapped to the canonical tag videos are stored with
+    static TAG_ALIASES: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(37))),
+        )
+    );
+
+    // Aliases by canonical tag: "canonical\0alias"
+    static TAG_ALIAS_TARGETS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(67))),
+        )
+    );
+
+    // Last video id the re-tag job processed; None when no pass is pending.
+    // Starts as a pass from the beginning so tags stored before they were
+    // normalized are cleaned up.
+    static TAG_RETAG_CURSOR: RefCell<StableCell<Option<String>, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(38))),
+            Some(String::new()),
+        ).expect("Failed to initialize re-tag cursor")
+    );
+
+    // Video count and total views per tag
+    static TAG_STATS: RefCell<StableBTreeMap<String, TagStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(39))),
+        )
+    );
+
+    // Videos per tag, newest first: "tag\0inverted timestamp\0video_id"
+    static TAG_RECENT_VIDEOS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(40))),
+        )
+    );
+
+    // Videos per tag, most viewed first: "tag\0inverted views\0video_id"
+    static TAG_TOP_VIDEOS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(41))),
+        )
+    );
+
+    // Tag co-occurrence: "tag\0other tag", mapped to the videos carrying both
+    // among their first few tags
+    static TAG_PAIRS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(42))),
+        )
+    );
+
+    // Decayed upload and view activity per tag, for trending tags
+    static TAG_ACTIVITY: RefCell<StableBTreeMap<String, TagActivity, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(43))),
+        )
+    );
+
+    // Each video's own decayed activity, taken back from its tags while it
+    // sits in the trash
+    static VIDEO_ACTIVITY: RefCell<StableBTreeMap<String, TagActivity, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(66))),
+        )
+    );
+
+    // Trending tag leaderboards as of the last refresh
+    static TRENDING_TAGS: RefCell<StableCell<TrendingBoards, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(44))),
+            TrendingBoards::default(),
+        ).expect("Failed to initialize trending tags")
+    );
+
+    // Livepeer playback id mapped to the video whose storage reference uses it
+    static PLAYBACK_IDS: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(45))),
+        )
+    );
+
+    // Playlists by who can edit them, keyed "principal:playlist_id" for the owner
+    // and each collaborator
+    static PLAYLISTS_BY_EDITOR: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(46))),
+        )
+    );
+
+    // Videos by credited co-creator, keyed "principal:video_id", whether the
+    // credit is pending or accepted
+    static CREDITED_VIDEOS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(47))),
+        )
+    );
+
+    // Comments keyed "video_id:timestamp:seq" (see page::log_key), oldest first
+    static COMMENTS: RefCell<StableBTreeMap<String, Comment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(48))),
+        )
+    );
+
+    // Comments by commenter, keyed "principal:timestamp:comment_key" and mapped
+    // to the comment's key in COMMENTS
+    static COMMENTS_BY_USER: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(49))),
+        )
+    );
+
+    // Watch events keyed "video_id:timestamp:seq", oldest first
+    static WATCH_LOG: RefCell<StableBTreeMap<String, WatchEvent, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(50))),
+        )
+    );
+
+    // Watch events by viewer, keyed "principal:timestamp:event_key" and mapped
+    // to the event's key in WATCH_LOG
+    static WATCH_EVENTS_BY_USER: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(51))),
+        )
+    );
+
+    // Tip entries keyed "video_id:timestamp:seq", oldest first
+    static TIP_RECORDS: RefCell<StableBTreeMap<String, TipRecord, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(52))),
+        )
+    );
+
+    // Tip entries by sender address, keyed "address:timestamp:tip_key" and
+    // mapped to the entry's key in TIP_RECORDS
+    static TIPS_BY_SENDER: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(53))),
+        )
+    );
+
+    // Tip entries by recipient address, keyed like TIPS_BY_SENDER
+    static TIPS_BY_RECIPIENT: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(54))),
+        )
+    );
+
+    // Tip entries by transaction, keyed "video_id:tx_hash:tip_key" and mapped
+    // to the entry's key in TIP_RECORDS. Redacted tips are left out.
+    static TIP_SPLITS: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(65))),
+        )
+    );
+
+    // Sounds ranked by use count, keyed "inverted_use_count:sound_id" so the
+    // most used sort first
+    static SOUNDS_BY_USE_COUNT: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(55))),
+        )
+    );
+
+    // Follower and video counts by creator principal
+    static PROFILE_STATS: RefCell<StableBTreeMap<String, ProfileStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(56))),
+        )
+    );
+
+    // Metadata revisions keyed "video_id:revision", oldest first
+    static VIDEO_REVISIONS: RefCell<StableBTreeMap<String, VideoRevision, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(57))),
+        )
+    );
+
+    // Listed and processing videos across the catalog, one ranking per search
+    // sort: "sort:inverted rank:video_id", best first. Trashed videos are left out.
+    static VIDEO_RANKINGS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(58))),
+        )
+    );
+
+    // Profile search: "prefix\0inverted followers\0lowercase name\0principal"
+    // for each prefix of a profile's terms, most followed first
+    static PROFILE_RANKS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(59))),
+        )
+    );
+
+    // Autocomplete rankings: "prefix\0inverted popularity\0entry" for each
+    // prefix of an entry's text up to autocomplete::MAX_RANKED_PREFIX_CHARS,
+    // where the entry is the key in AUTOCOMPLETE_TAGS, _TITLES or _PROFILES
+    static AUTOCOMPLETE_TAG_RANKS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(60))),
+        )
+    );
+
+    static AUTOCOMPLETE_TITLE_RANKS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(61))),
+        )
+    );
+
+    static AUTOCOMPLETE_PROFILE_RANKS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(62))),
+        )
+    );
+
+    // Related tags, most shared first: "tag\0inverted shared videos\0other tag",
+    // mapped to the shared videos
+    static TAG_RELATED: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(63))),
+        )
+    );
+
+    // Sound search: "term\0inverted use count\0sound_id" for each word of a
+    // sound's title, most used first
+    static SOUND_TERMS: RefCell<StableBTreeMap<String, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(64))),
+        )
+    );
 }
@@ -0,0 +1,207 @@
+use candid::{CandidType, Deserialize};
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+use std::ops::Bound;
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// One page of a cursor-paginated list. Pass `next_cursor` back to get the
+/// following page; it is None on the last page.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Page size for a requested limit, capped at MAX_PAGE_SIZE
+pub fn page_size(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE) as usize
+}
+
+/// Wraps the key of the last item returned into an opaque cursor
+pub fn encode_cursor(key: &str) -> String {
+    key.bytes().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Recovers the key a cursor was made from
+pub fn decode_cursor(cursor: &str) -> Result<String, String> {
+    let invalid = || "Invalid cursor".to_string();
+    if !cursor.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| cursor.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Range start for a scan over keys beginning with `prefix`, resuming after
+/// the cursor if there is one
+pub fn start_after(cursor: Option<&str>, prefix: &str) -> Result<Bound<String>, String> {
+    match cursor {
+        Some(cursor) => {
+            let key = decode_cursor(cursor)?;
+            if !key.starts_with(prefix) {
+                return Err("Invalid cursor".to_string());
+            }
+            Ok(Bound::Excluded(key))
+        }
+        None => Ok(Bound::Included(prefix.to_string())),
+    }
+}
+
+/// Position to resume a list stored as a single value from
+pub fn start_index(cursor: Option<&str>) -> Result<usize, String> {
+    match cursor {
+        Some(cursor) => decode_cursor(cursor)?
+            .parse::<usize>()
+            .map(|index| index + 1)
+            .map_err(|_| "Invalid cursor".to_string()),
+        None => Ok(0),
+    }
+}
+
+/// Takes up to `limit` items from `entries`, which yields (key, item) pairs
+/// in key order after the cursor. The cursor for the next page is the key
+/// of the last item taken.
+pub fn collect_page<T>(entries: impl Iterator<Item = (String, T)>, limit: usize) -> Page<T> {
+    let mut entries = entries.peekable();
+    let mut items = Vec::with_capacity(limit);
+    let mut last_key = None;
+    while items.len() < limit {
+        match entries.next() {
+            Some((key, item)) => {
+                last_key = Some(key);
+                items.push(item);
+            }
+            None => break,
+        }
+    }
+    let next_cursor = match entries.peek() {
+        Some(_) => last_key.map(|key| encode_cursor(&key)),
+        None => None,
+    };
+    Page { items, next_cursor }
+}
+
+/// Takes up to `limit` items like `collect_page`, from entries that may be
+/// left out of the page (a None item). Reads at most `max_scanned` entries,
+/// so when few entries make it in the page can come back short, with a
+/// cursor to read on from.
+pub fn collect_scanned_page<T>(
+    entries: impl Iterator<Item = (String, Option<T>)>,
+    limit: usize,
+    max_scanned: usize,
+) -> Page<T> {
+    let mut entries = entries.peekable();
+    let mut items = Vec::with_capacity(limit);
+    let mut last_key = None;
+    let mut scanned = 0;
+    while items.len() < limit && scanned < max_scanned {
+        match entries.next() {
+            Some((key, item)) => {
+                last_key = Some(key);
+                items.extend(item);
+                scanned += 1;
+            }
+            None => break,
+        }
+    }
+    let next_cursor = match entries.peek() {
+        Some(_) => last_key.map(|key| encode_cursor(&key)),
+        None => None,
+    };
+    Page { items, next_cursor }
+}
+
+/// Key of an entry in a log kept under `prefix` (such as a video id), ordered
+/// by time. `seq` tells apart entries logged in the same second.
+pub fn log_key(prefix: &str, timestamp: u64, seq: u32) -> String {
+    format!("{}:{:020}:{:010}", prefix, timestamp, seq)
+}
+
+/// Key for a new entry logged under `prefix` at `timestamp`, after any
+/// entries already logged in the same second
+pub fn next_log_key<V: Storable, M: Memory>(
+    log: &StableBTreeMap<String, V, M>,
+    prefix: &str,
+    timestamp: u64,
+) -> String {
+    let second = format!("{}:{:020}:", prefix, timestamp);
+    let seq = log
+        .range(second.clone()..)
+        .take_while(|(key, _)| key.starts_with(&second))
+        .last()
+        .and_then(|(key, _)| key[second.len()..].parse::<u32>().ok())
+        .map_or(0, |seq| seq + 1);
+    log_key(prefix, timestamp, seq)
+}
+
+/// Key of a per-user index entry pointing at `entry_key`, ordering the
+/// user's entries by time
+pub fn owner_key(owner: &str, timestamp: u64, entry_key: &str) -> String {
+    format!("{}:{:020}:{}", owner, timestamp, entry_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = encode_cursor("2vxsx-fae:abc");
+        assert!(cursor.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(decode_cursor(&cursor).unwrap(), "2vxsx-fae:abc");
+        assert!(decode_cursor("xyz").is_err());
+        assert!(decode_cursor("zz").is_err());
+    }
+
+    #[test]
+    fn test_next_log_key() {
+        use ic_stable_structures::DefaultMemoryImpl;
+
+        let mut log: StableBTreeMap<String, u8, _> = StableBTreeMap::init(DefaultMemoryImpl::default());
+        let first = next_log_key(&log, "video1", 5);
+        assert_eq!(first, log_key("video1", 5, 0));
+        log.insert(first, 0);
+        let second = next_log_key(&log, "video1", 5);
+        assert_eq!(second, log_key("video1", 5, 1));
+        log.insert(second.clone(), 1);
+        // Other seconds and other videos start over
+        assert_eq!(next_log_key(&log, "video1", 6), log_key("video1", 6, 0));
+        assert_eq!(next_log_key(&log, "video", 5), log_key("video", 5, 0));
+        // Entries sort by time, then by order logged
+        assert!(second < log_key("video1", 10, 0));
+    }
+
+    #[test]
+    fn test_collect_scanned_page() {
+        let entries = || (0..10).map(|i| (format!("{}", i), (i % 3 == 0).then_some(i)));
+
+        let page = collect_scanned_page(entries(), 2, 100);
+        assert_eq!(page.items, vec![0, 3]);
+        assert_eq!(decode_cursor(&page.next_cursor.unwrap()).unwrap(), "3");
+
+        // Cut short by the scan limit, with a cursor after the last entry read
+        let page = collect_scanned_page(entries(), 5, 5);
+        assert_eq!(page.items, vec![0, 3]);
+        assert_eq!(decode_cursor(&page.next_cursor.unwrap()).unwrap(), "4");
+
+        let page = collect_scanned_page(entries(), 5, 100);
+        assert_eq!(page.items, vec![0, 3, 6, 9]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_start_after_checks_prefix() {
+        let cursor = encode_cursor("alice:bob");
+        assert_eq!(
+            start_after(Some(&cursor), "alice:").unwrap(),
+            Bound::Excluded("alice:bob".to_string())
+        );
+        assert!(start_after(Some(&cursor), "carol:").is_err());
+        assert_eq!(start_after(None, "alice:").unwrap(), Bound::Included("alice:".to_string()));
+    }
+}
@@ -0,0 +1,235 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::{page::Page, video_metadata::VideoMetadata};
+
+const MAX_TITLE_LENGTH: usize = 150;
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+pub const MAX_PLAYLIST_VIDEOS: usize = 500;
+pub const MAX_COLLABORATORS: usize = 20;
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PlaylistVisibility {
+    Public,   // Listed on the owner's channel
+    Unlisted, // Readable by anyone with the id
+    Private,  // Owner and collaborators only
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Playlist {
+    pub playlist_id: String,
+    pub owner_principal: Principal,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: PlaylistVisibility,
+    pub collaborators: Vec<Principal>, // Can add, remove and reorder videos
+    pub video_ids: Vec<String>,        // In playback order
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub invited_collaborators: Option<Vec<Principal>>, // Become collaborators once they accept
+}
+
+/// Playlist fields without the video list, returned by listing and editing endpoints
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlaylistSummary {
+    pub playlist_id: String,
+    pub owner_principal: Principal,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: PlaylistVisibility,
+    pub collaborators: Vec<Principal>,
+    pub invited_collaborators: Vec<Principal>,
+    pub video_count: u32,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// One page of a playlist's videos
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlaylistPage {
+    pub playlist: PlaylistSummary,
+    pub videos: Page<VideoMetadata>,
+}
+
+pub fn validate_title(title: &str) -> Result<(), String> {
+    if title.trim().is_empty() || title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(format!(
+            "Playlist title must be between 1 and {} characters",
+            MAX_TITLE_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_description(description: &str) -> Result<(), String> {
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!(
+            "Playlist description cannot exceed {} characters",
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+impl Playlist {
+    /// Invitees can see a private playlist before accepting
+    pub fn can_view(&self, principal: &Principal) -> bool {
+        self.visibility != PlaylistVisibility::Private || self.can_edit(principal) || self.is_invited(principal)
+    }
+
+    /// Whether the principal was invited to collaborate and hasn't accepted yet
+    pub fn is_invited(&self, principal: &Principal) -> bool {
+        self.invited_collaborators.iter().flatten().any(|invited| invited == principal)
+    }
+
+    /// Whether the principal may change the playlist's videos
+    pub fn can_edit(&self, principal: &Principal) -> bool {
+        self.owner_principal == *principal || self.collaborators.contains(principal)
+    }
+
+    /// Inserts a video at `position` (clamped to the end), or appends it
+    pub fn add_video(&mut self, video_id: String, position: Option<u32>) -> Result<(), String> {
+        if self.video_ids.contains(&video_id) {
+            return Err("Video is already in the playlist".to_string());
+        }
+        if self.video_ids.len() >= MAX_PLAYLIST_VIDEOS {
+            return Err(format!(
+                "A playlist can hold at most {} videos",
+                MAX_PLAYLIST_VIDEOS
+            ));
+        }
+
+        let position = position
+            .map(|p| (p as usize).min(self.video_ids.len()))
+            .unwrap_or(self.video_ids.len());
+        self.video_ids.insert(position, video_id);
+        Ok(())
+    }
+
+    /// Removes a video, returning whether it was in the playlist
+    pub fn remove_video(&mut self, video_id: &str) -> bool {
+        let count_before = self.video_ids.len();
+        self.video_ids.retain(|id| id != video_id);
+        self.video_ids.len() != count_before
+    }
+
+    /// Moves a video to `position` (clamped to the end)
+    pub fn move_video(&mut self, video_id: &str, position: u32) -> Result<(), String> {
+        let current = self
+            .video_ids
+            .iter()
+            .position(|id| id == video_id)
+            .ok_or_else(|| "Video is not in the playlist".to_string())?;
+
+        let video_id = self.video_ids.remove(current);
+        let position = (position as usize).min(self.video_ids.len());
+        self.video_ids.insert(position, video_id);
+        Ok(())
+    }
+
+    pub fn summary(&self) -> PlaylistSummary {
+        PlaylistSummary {
+            playlist_id: self.playlist_id.clone(),
+            owner_principal: self.owner_principal,
+            title: self.title.clone(),
+            description: self.description.clone(),
+            visibility: self.visibility,
+            collaborators: self.collaborators.clone(),
+            invited_collaborators: self.invited_collaborators.clone().unwrap_or_default(),
+            video_count: self.video_ids.len() as u32,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl Storable for Playlist {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    // Up to MAX_PLAYLIST_VIDEOS ids of any length, which no fixed bound covers
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_playlist(video_ids: &[&str]) -> Playlist {
+        let principal_bytes = [
+            10, 116, 101, 115, 116, 45, 112, 114, 105, 110, 99, 105, 112, 97, 108,
+        ];
+        Playlist {
+            playlist_id: "pl2".to_string(),
+            owner_principal: Principal::from_slice(&principal_bytes),
+            title: "Favourites".to_string(),
+            description: None,
+            visibility: PlaylistVisibility::Private,
+            collaborators: vec![],
+            video_ids: video_ids.iter().map(|id| id.to_string()).collect(),
+            created_at: 1234567890,
+            updated_at: 1234567890,
+            invited_collaborators: None,
+        }
+    }
+
+    #[test]
+    fn test_add_and_move_videos() {
+        let mut playlist = test_playlist(&["a", "b"]);
+
+        playlist.add_video("c".to_string(), None).unwrap();
+        playlist.add_video("d".to_string(), Some(0)).unwrap();
+        playlist.add_video("e".to_string(), Some(99)).unwrap();
+        assert_eq!(playlist.video_ids, vec!["d", "a", "b", "c", "e"]);
+        assert!(playlist.add_video("a".to_string(), None).is_err());
+
+        playlist.move_video("e", 1).unwrap();
+        playlist.move_video("d", 99).unwrap();
+        assert_eq!(playlist.video_ids, vec!["e", "a", "b", "c", "d"]);
+        assert!(playlist.move_video("x", 0).is_err());
+
+        assert!(playlist.remove_video("b"));
+        assert!(!playlist.remove_video("b"));
+        assert_eq!(playlist.summary().video_count, 4);
+    }
+
+    #[test]
+    fn test_access() {
+        let mut playlist = test_playlist(&[]);
+        let owner = playlist.owner_principal;
+        let collaborator = Principal::anonymous();
+        let stranger = Principal::management_canister();
+
+        assert!(playlist.can_view(&owner) && playlist.can_edit(&owner));
+        assert!(!playlist.can_view(&collaborator));
+
+        // An invitee can look at the playlist but not edit it yet
+        playlist.invited_collaborators = Some(vec![collaborator]);
+        assert!(playlist.can_view(&collaborator) && !playlist.can_edit(&collaborator));
+
+        playlist.invited_collaborators = None;
+        playlist.collaborators.push(collaborator);
+        assert!(playlist.can_view(&collaborator) && playlist.can_edit(&collaborator));
+
+        playlist.visibility = PlaylistVisibility::Unlisted;
+        assert!(playlist.can_view(&stranger) && !playlist.can_edit(&stranger));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let playlist = test_playlist(&["a", "b"]);
+        assert_eq!(Playlist::from_bytes(playlist.to_bytes()), playlist);
+
+        // A full playlist of long legacy ids
+        let long_ids: Vec<String> = (0..MAX_PLAYLIST_VIDEOS).map(|i| format!("{:0>100}", i)).collect();
+        let mut full = test_playlist(&[]);
+        full.video_ids = long_ids;
+        assert_eq!(Playlist::from_bytes(full.to_bytes()), full);
+    }
+}
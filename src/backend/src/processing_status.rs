@@ -0,0 +1,82 @@
+use candid::{CandidType, Deserialize};
+
+/// Where a video is in the upload and transcoding pipeline
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub enum ProcessingStatus {
+    Uploading,
+    Processing,
+    Ready,
+    Failed { reason: String },
+}
+
+impl ProcessingStatus {
+    /// Whether moving from this status to `next` is allowed.
+    ///
+    /// Ready is terminal. A failed video can be picked up again when the
+    /// pipeline retries it. Repeating the current status is always allowed,
+    /// since webhooks are retried and progress updates repeat.
+    pub fn can_transition_to(&self, next: &ProcessingStatus) -> bool {
+        use ProcessingStatus::*;
+
+        matches!(
+            (self, next),
+            (Uploading, Uploading)
+                | (Processing, Processing)
+                | (Ready, Ready)
+                | (Failed { .. }, Failed { .. })
+                | (Uploading, Processing)
+                | (Uploading, Ready)
+                | (Uploading, Failed { .. })
+                | (Processing, Ready)
+                | (Processing, Failed { .. })
+                | (Failed { .. }, Processing)
+                | (Failed { .. }, Ready)
+        )
+    }
+
+    /// Returns the new status if the transition is allowed
+    pub fn transition(&self, next: ProcessingStatus) -> Result<ProcessingStatus, String> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(format!("Cannot move from {:?} to {:?}", self, next))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> ProcessingStatus {
+        ProcessingStatus::Failed {
+            reason: "Transcode error".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_happy_path() {
+        let status = ProcessingStatus::Uploading;
+        let status = status.transition(ProcessingStatus::Processing).unwrap();
+        let status = status.transition(ProcessingStatus::Ready).unwrap();
+        assert_eq!(status, ProcessingStatus::Ready);
+    }
+
+    #[test]
+    fn test_ready_is_terminal() {
+        assert!(ProcessingStatus::Ready
+            .transition(ProcessingStatus::Processing)
+            .is_err());
+        assert!(ProcessingStatus::Ready.transition(failed()).is_err());
+        assert!(ProcessingStatus::Ready
+            .transition(ProcessingStatus::Uploading)
+            .is_err());
+    }
+
+    #[test]
+    fn test_failed_can_retry() {
+        assert!(failed().can_transition_to(&ProcessingStatus::Processing));
+        assert!(!failed().can_transition_to(&ProcessingStatus::Uploading));
+        assert!(!ProcessingStatus::Processing.can_transition_to(&ProcessingStatus::Uploading));
+    }
+}
@@ -0,0 +1,107 @@
+use candid::{CandidType, Deserialize};
+
+use crate::video_metadata::VideoMetadata;
+
+/// Most parents walked in one call, guards against walking very deep remix trees
+pub const MAX_ANCESTRY_DEPTH: usize = 50;
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RemixType {
+    Duet,     // Side by side with the parent
+    Stitch,   // Starts with a clip of the parent
+    Reaction, // Responds to the parent
+}
+
+/// Link from a remix to the video it was made from
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct RemixSource {
+    pub parent_video_id: String,
+    pub remix_type: RemixType,
+}
+
+/// Walks up from `video` to the original, nearest parent first, looking up
+/// each parent only as the walk reaches it.
+///
+/// Parents that `lookup` can't find end the chain; the caller decides which
+/// of the returned videos to show and how far to walk.
+pub fn ancestry<'a>(
+    video: &VideoMetadata,
+    lookup: impl Fn(&str) -> Option<VideoMetadata> + 'a,
+) -> impl Iterator<Item = VideoMetadata> + 'a {
+    let parent_of = move |video: &VideoMetadata| {
+        let source = video.remix_of.as_ref()?;
+        lookup(&source.parent_video_id)
+    };
+    let first = parent_of(video);
+    std::iter::successors(first, move |parent| parent_of(parent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+    use std::collections::HashMap;
+
+    fn video(video_id: &str, parent: Option<&str>) -> VideoMetadata {
+        VideoMetadata {
+            video_id: video_id.to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: vec![],
+            title: video_id.to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: parent.map(|parent| RemixSource {
+                parent_video_id: parent.to_string(),
+                remix_type: RemixType::Duet,
+            }),
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    #[test]
+    fn test_ancestry_walks_to_the_original() {
+        let videos: HashMap<String, VideoMetadata> = [
+            video("original", None),
+            video("remix", Some("original")),
+            video("remix-of-remix", Some("remix")),
+        ]
+        .into_iter()
+        .map(|video| (video.video_id.clone(), video))
+        .collect();
+        let lookup = |id: &str| videos.get(id).cloned();
+
+        let chain: Vec<VideoMetadata> = ancestry(&video("latest", Some("remix-of-remix")), lookup).collect();
+        let ids: Vec<&str> = chain.iter().map(|v| v.video_id.as_str()).collect();
+        assert_eq!(ids, vec!["remix-of-remix", "remix", "original"]);
+
+        assert_eq!(ancestry(&videos["original"], lookup).count(), 0);
+    }
+
+    #[test]
+    fn test_ancestry_stops_at_missing_parent() {
+        let videos: HashMap<String, VideoMetadata> = [video("remix", Some("purged"))]
+            .into_iter()
+            .map(|video| (video.video_id.clone(), video))
+            .collect();
+
+        let chain = ancestry(&video("latest", Some("remix")), |id| videos.get(id).cloned());
+        assert_eq!(chain.count(), 1);
+    }
+}
@@ -0,0 +1,113 @@
+// Periodic background jobs driven by the canister global timer
+//
+// Each job returns true when it stopped early because of its batch limit, in
+// which case it is scheduled again right away instead of waiting a full interval.
+// Due jobs run in separate self-calls, so one that traps doesn't stop the others.
+
+use ic_cdk::{api::call::CallResult, update};
+use std::cell::RefCell;
+
+use crate::service;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+struct Job {
+    name: &'static str,
+    interval_sec: u64,
+    run: fn() -> bool,
+}
+
+const JOBS: &[Job] = &[
+    Job {
+        name: "purge_expired_videos",
+        interval_sec: 60 * 60,
+        run: service::video::purge_expired_videos,
+    },
+    Job {
+        name: "retag_videos",
+        interval_sec: 60,
+        run: service::tags::retag_videos,
+    },
+    Job {
+        name: "refresh_trending_tags",
+        interval_sec: 5 * 60,
+        run: service::trending::refresh_trending_tags,
+    },
+];
+
+thread_local! {
+    // Next run time (nanoseconds) per entry in JOBS
+    static NEXT_RUNS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Schedules every job one interval from now and arms the global timer.
+/// Timers don't survive upgrades, so this runs from both init and post_upgrade.
+pub fn start() {
+    let now = ic_cdk::api::time();
+    NEXT_RUNS.with(|next_runs| {
+        *next_runs.borrow_mut() = JOBS
+            .iter()
+            .map(|job| now + job.interval_sec * NANOS_PER_SEC)
+            .collect();
+    });
+    arm_timer();
+}
+
+fn arm_timer() {
+    let next = NEXT_RUNS.with(|next_runs| next_runs.borrow().iter().copied().min());
+    if let Some(next) = next {
+        ic_cdk::api::set_global_timer(next);
+    }
+}
+
+#[export_name = "canister_global_timer"]
+extern "C" fn global_timer() {
+    ic_cdk::setup();
+
+    // Reschedule the due jobs and re-arm the timer before anything runs, so a
+    // job that traps can't leave the timer unset
+    let now = ic_cdk::api::time();
+    let due: Vec<&'static str> = NEXT_RUNS.with(|next_runs| {
+        let mut next_runs = next_runs.borrow_mut();
+        JOBS.iter()
+            .zip(next_runs.iter_mut())
+            .filter(|(_, next_run)| **next_run <= now)
+            .map(|(job, next_run)| {
+                *next_run = now + job.interval_sec * NANOS_PER_SEC;
+                job.name
+            })
+            .collect()
+    });
+    arm_timer();
+
+    // Each job runs in its own message, so a trap only rolls back that job
+    for name in due {
+        ic_cdk::spawn(async move {
+            let result: CallResult<()> = ic_cdk::call(ic_cdk::id(), "run_scheduled_job", (name,)).await;
+            if let Err((code, message)) = result {
+                ic_cdk::println!("Scheduled job {} failed: {:?} {}", name, code, message);
+            }
+        });
+    }
+}
+
+/// Runs one scheduled job; only the canister itself can call this
+#[update(hidden = true)]
+fn run_scheduled_job(name: String) {
+    if ic_cdk::caller() != ic_cdk::id() {
+        ic_cdk::trap("Scheduled jobs can only be run by the canister");
+    }
+    let Some(index) = JOBS.iter().position(|job| job.name == name) else {
+        return;
+    };
+
+    // Run again right away when work is left instead of waiting a full interval
+    if (JOBS[index].run)() {
+        NEXT_RUNS.with(|next_runs| {
+            if let Some(next_run) = next_runs.borrow_mut().get_mut(index) {
+                *next_run = ic_cdk::api::time();
+            }
+        });
+        arm_timer();
+    }
+}
@@ -0,0 +1,276 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::video_metadata::VideoMetadata;
+
+const MAX_VALUE_SIZE: u32 = 64_000;
+const MAX_TOKEN_LENGTH: usize = 40;
+
+// Field weights: a term in the title counts as three occurrences, in a tag as two
+const TITLE_WEIGHT: u32 = 3;
+const TAG_WEIGHT: u32 = 2;
+const DESCRIPTION_WEIGHT: u32 = 1;
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Fuzzy matches score at this fraction of an exact match
+pub const FUZZY_WEIGHT: f64 = 0.5;
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum MatchMode {
+    #[default]
+    All, // Every query term must match
+    Any, // At least one query term must match
+}
+
+/// Terms of one indexed video with their weighted frequencies, kept so the
+/// postings can be removed when the video changes
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct IndexedDocument {
+    pub terms: Vec<(String, u32)>,
+    pub length: u32,
+    pub timestamp: u64, // Upload time, orders equally relevant videos newest first
+}
+
+/// One page of search results, with a corrected query when some terms look misspelled
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SearchResults {
+    pub videos: Vec<VideoMetadata>,
+    pub next_cursor: Option<String>,
+    pub did_you_mean: Option<String>,
+}
+
+/// Corpus-wide figures BM25 needs
+pub struct CorpusStats {
+    pub document_count: u64,
+    pub total_length: u64,
+}
+
+/// Splits text into lowercase alphanumeric tokens
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase().chars().take(MAX_TOKEN_LENGTH).collect())
+        .collect()
+}
+
+/// Builds the weighted term frequencies for a video's title, tags and description
+pub fn document_for(metadata: &VideoMetadata) -> IndexedDocument {
+    let mut terms: Vec<(String, u32)> = Vec::new();
+    let mut add = |text: &str, weight: u32| {
+        for token in tokenize(text) {
+            match terms.iter_mut().find(|(term, _)| *term == token) {
+                Some((_, frequency)) => *frequency += weight,
+                None => terms.push((token, weight)),
+            }
+        }
+    };
+
+    add(&metadata.title, TITLE_WEIGHT);
+    for tag in &metadata.tags {
+        add(tag, TAG_WEIGHT);
+    }
+    if let Some(description) = &metadata.description {
+        add(description, DESCRIPTION_WEIGHT);
+    }
+
+    terms.sort();
+    let length = terms.iter().map(|(_, frequency)| frequency).sum();
+    IndexedDocument {
+        terms,
+        length,
+        timestamp: metadata.timestamp,
+    }
+}
+
+/// Unique query terms in the order they were typed
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for token in tokenize(query) {
+        if !terms.contains(&token) {
+            terms.push(token);
+        }
+    }
+    terms
+}
+
+/// Inverse document frequency of a term found in `document_frequency` documents
+pub fn idf(document_frequency: u64, stats: &CorpusStats) -> f64 {
+    let n = stats.document_count as f64;
+    let df = document_frequency as f64;
+    (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+}
+
+/// BM25 contribution of one term to one document
+pub fn term_score(term_frequency: u32, document_length: u32, idf: f64, stats: &CorpusStats) -> f64 {
+    let average_length = if stats.document_count == 0 {
+        1.0
+    } else {
+        (stats.total_length as f64 / stats.document_count as f64).max(1.0)
+    };
+    let tf = term_frequency as f64;
+    let length_norm = 1.0 - B + B * document_length as f64 / average_length;
+    idf * tf * (K1 + 1.0) / (tf + K1 * length_norm)
+}
+
+/// Character trigrams of a term, padded so short terms and word edges count
+pub fn trigrams(term: &str) -> Vec<String> {
+    let padded: Vec<char> = "^^".chars().chain(term.chars()).chain("$$".chars()).collect();
+    let mut trigrams: Vec<String> = padded.windows(3).map(|w| w.iter().collect()).collect();
+    trigrams.sort();
+    trigrams.dedup();
+    trigrams
+}
+
+/// Edits a misspelling of `term` may contain; short terms must match exactly
+pub fn max_edits(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Trigrams a term within `max_edits` of one with `trigram_count` trigrams
+/// must share with it, since each edit changes at most three
+pub fn min_shared_trigrams(trigram_count: usize, max_edits: usize) -> usize {
+    trigram_count.saturating_sub(3 * max_edits).max(1)
+}
+
+/// Levenshtein distance between two terms
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl Storable for IndexedDocument {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn video(title: &str, tags: &[&str], description: Option<&str>) -> VideoMetadata {
+        VideoMetadata {
+            video_id: "v".to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            title: title.to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp: 0,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: description.map(|d| d.to_string()),
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Rust Tutorial: async/await, Part 2!"),
+            vec!["rust", "tutorial", "async", "await", "part", "2"]
+        );
+        assert_eq!(tokenize("Crème Brûlée"), vec!["crème", "brûlée"]);
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn test_document_weights_fields() {
+        let doc = document_for(&video("Rust tutorial", &["rust"], Some("Learn Rust basics")));
+        assert_eq!(
+            doc.terms,
+            vec![
+                ("basics".to_string(), 1),
+                ("learn".to_string(), 1),
+                ("rust".to_string(), 6),
+                ("tutorial".to_string(), 3),
+            ]
+        );
+        assert_eq!(doc.length, 11);
+    }
+
+    #[test]
+    fn test_query_terms_are_unique() {
+        assert_eq!(query_terms("cat Cat dog"), vec!["cat", "dog"]);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("tutoral", "tutorial"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_trigrams_bound_fuzzy_candidates() {
+        assert_eq!(trigrams("cat"), vec!["^^c", "^ca", "at$", "cat", "t$$"]);
+
+        // A one-edit typo still shares enough trigrams to be a candidate
+        let query = trigrams("tutoral");
+        let shared = trigrams("tutorial").iter().filter(|t| query.contains(t)).count();
+        assert!(shared >= min_shared_trigrams(query.len(), max_edits("tutoral")));
+
+        assert_eq!(max_edits("cat"), 0);
+        assert_eq!(max_edits("tutoral"), 1);
+        assert_eq!(max_edits("programming"), 2);
+    }
+
+    #[test]
+    fn test_scoring() {
+        let stats = CorpusStats {
+            document_count: 100,
+            total_length: 1000,
+        };
+        // Rare terms weigh more than common ones
+        assert!(idf(1, &stats) > idf(50, &stats));
+        // More occurrences score higher, with diminishing returns
+        let once = term_score(1, 10, 1.0, &stats);
+        let twice = term_score(2, 10, 1.0, &stats);
+        let thrice = term_score(3, 10, 1.0, &stats);
+        assert!(twice > once && thrice - twice < twice - once);
+        // The same occurrences in a shorter document score higher
+        assert!(term_score(1, 5, 1.0, &stats) > term_score(1, 50, 1.0, &stats));
+    }
+}
@@ -0,0 +1,297 @@
+use candid::{CandidType, Deserialize, Principal};
+
+use crate::{
+    search_index::MatchMode,
+    video_metadata::{ContentRating, VideoMetadata},
+    video_stats::VideoStats,
+};
+
+const MAX_TAG_FILTERS: usize = 20;
+const MAX_QUERY_TEXT_LENGTH: usize = 200;
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SearchSort {
+    Relevance, // Best text match first; needs `text`
+    Newest,
+    MostViewed,
+    MostLiked,
+    MostTipped,
+}
+
+impl SearchSort {
+    /// Sorts kept as catalog-wide rankings, so a search without text can
+    /// page through them instead of sorting every video
+    pub const RANKED: [SearchSort; 4] = [
+        SearchSort::Newest,
+        SearchSort::MostViewed,
+        SearchSort::MostLiked,
+        SearchSort::MostTipped,
+    ];
+
+    fn tag(&self) -> &'static str {
+        match self {
+            SearchSort::Relevance => "r",
+            SearchSort::Newest => "n",
+            SearchSort::MostViewed => "v",
+            SearchSort::MostLiked => "l",
+            SearchSort::MostTipped => "t",
+        }
+    }
+
+    /// Prefix shared by the sort's keys
+    pub fn prefix(&self) -> String {
+        format!("{}:", self.tag())
+    }
+
+    /// Ascending sort key, best first; the video id breaks ties so every
+    /// position is unique. Relevance ranks by newest here; text results are
+    /// keyed by their match instead.
+    pub fn key(&self, timestamp: u64, stats: &VideoStats, video_id: &str) -> String {
+        let rank = match self {
+            SearchSort::Relevance | SearchSort::Newest => timestamp,
+            SearchSort::MostViewed => stats.views,
+            SearchSort::MostLiked => stats.likes,
+            SearchSort::MostTipped => stats.tips_total,
+        };
+        format!("{}{:020}:{}", self.prefix(), u64::MAX - rank, video_id)
+    }
+}
+
+/// A video search: optional text plus filters, a sort order and a page.
+/// Unset fields don't filter.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    pub text: Option<String>,
+    pub match_mode: Option<MatchMode>,
+    pub uploader: Option<Principal>,
+    pub uploaded_after: Option<u64>,  // Inclusive, seconds since the epoch
+    pub uploaded_before: Option<u64>, // Exclusive
+    pub min_duration_sec: Option<u32>,
+    pub max_duration_sec: Option<u32>,
+    pub tags_all: Option<Vec<String>>,  // Video has every one of these tags
+    pub tags_any: Option<Vec<String>>,  // Video has at least one of these tags
+    pub tags_none: Option<Vec<String>>, // Video has none of these tags
+    pub language: Option<String>,       // "en" also matches "en-US"
+    pub content_rating: Option<ContentRating>,
+    pub sort: Option<SearchSort>, // Relevance with text, Newest without
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl SearchQuery {
+    /// The query text, if it has any
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref().map(str::trim).filter(|text| !text.is_empty())
+    }
+
+    pub fn sort(&self) -> SearchSort {
+        match (self.sort, self.text()) {
+            (Some(SearchSort::Relevance), None) | (None, None) => SearchSort::Newest,
+            (Some(sort), _) => sort,
+            (None, Some(_)) => SearchSort::Relevance,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.text().is_some_and(|text| text.chars().count() > MAX_QUERY_TEXT_LENGTH) {
+            return Err(format!(
+                "Search text can be at most {} characters",
+                MAX_QUERY_TEXT_LENGTH
+            ));
+        }
+        if let (Some(after), Some(before)) = (self.uploaded_after, self.uploaded_before) {
+            if after >= before {
+                return Err("uploaded_after must be before uploaded_before".to_string());
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_duration_sec, self.max_duration_sec) {
+            if min > max {
+                return Err("min_duration_sec can't be more than max_duration_sec".to_string());
+            }
+        }
+        for tags in [&self.tags_all, &self.tags_any, &self.tags_none].into_iter().flatten() {
+            if tags.len() > MAX_TAG_FILTERS {
+                return Err(format!("A tag filter can list at most {} tags", MAX_TAG_FILTERS));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a video passes every filter; text matching is done by the index
+    pub fn matches(&self, metadata: &VideoMetadata) -> bool {
+        if self.uploader.is_some_and(|uploader| uploader != metadata.uploader_principal) {
+            return false;
+        }
+        if self.uploaded_after.is_some_and(|after| metadata.timestamp < after)
+            || self.uploaded_before.is_some_and(|before| metadata.timestamp >= before)
+        {
+            return false;
+        }
+
+        if self.min_duration_sec.is_some() || self.max_duration_sec.is_some() {
+            let Some(duration_sec) = metadata.duration_sec else {
+                return false;
+            };
+            if self.min_duration_sec.is_some_and(|min| duration_sec < min)
+                || self.max_duration_sec.is_some_and(|max| duration_sec > max)
+            {
+                return false;
+            }
+        }
+
+        let has_tag = |tag: &String| metadata.tags.iter().any(|video_tag| video_tag.eq_ignore_ascii_case(tag));
+        if self.tags_all.as_ref().is_some_and(|tags| !tags.iter().all(has_tag))
+            || self.tags_any.as_ref().is_some_and(|tags| !tags.is_empty() && !tags.iter().any(has_tag))
+            || self.tags_none.as_ref().is_some_and(|tags| tags.iter().any(has_tag))
+        {
+            return false;
+        }
+
+        if let Some(language) = &self.language {
+            let matches_language = metadata.primary_language.as_ref().is_some_and(|video_language| {
+                video_language.eq_ignore_ascii_case(language)
+                    || video_language
+                        .to_ascii_lowercase()
+                        .starts_with(&format!("{}-", language.to_ascii_lowercase()))
+            });
+            if !matches_language {
+                return false;
+            }
+        }
+
+        self.content_rating
+            .is_none_or(|rating| metadata.content_rating == Some(rating))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video() -> VideoMetadata {
+        VideoMetadata {
+            video_id: "v".to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: vec!["Rust".to_string(), "tutorial".to_string()],
+            title: "Rust tutorial".to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp: 1_000,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: Some(90),
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: Some("en-US".to_string()),
+            content_rating: Some(ContentRating::General),
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    fn tags(tags: &[&str]) -> Option<Vec<String>> {
+        Some(tags.iter().map(|t| t.to_string()).collect())
+    }
+
+    #[test]
+    fn test_filters() {
+        let video = video();
+        assert!(SearchQuery::default().matches(&video));
+
+        let in_range = SearchQuery {
+            uploaded_after: Some(1_000),
+            uploaded_before: Some(1_001),
+            min_duration_sec: Some(60),
+            max_duration_sec: Some(90),
+            ..Default::default()
+        };
+        assert!(in_range.matches(&video));
+        let too_late = SearchQuery {
+            uploaded_before: Some(1_000),
+            ..Default::default()
+        };
+        assert!(!too_late.matches(&video));
+
+        let tag_filters = SearchQuery {
+            tags_all: tags(&["rust", "TUTORIAL"]),
+            tags_any: tags(&["go", "rust"]),
+            tags_none: tags(&["beginner"]),
+            ..Default::default()
+        };
+        assert!(tag_filters.matches(&video));
+        let excluded = SearchQuery {
+            tags_none: tags(&["rust"]),
+            ..Default::default()
+        };
+        assert!(!excluded.matches(&video));
+
+        let english = SearchQuery {
+            language: Some("en".to_string()),
+            content_rating: Some(ContentRating::General),
+            ..Default::default()
+        };
+        assert!(english.matches(&video));
+        let mature = SearchQuery {
+            content_rating: Some(ContentRating::Mature),
+            ..Default::default()
+        };
+        assert!(!mature.matches(&video));
+    }
+
+    #[test]
+    fn test_duration_filter_skips_unknown_durations() {
+        let mut video = video();
+        video.duration_sec = None;
+        let query = SearchQuery {
+            max_duration_sec: Some(60),
+            ..Default::default()
+        };
+        assert!(!query.matches(&video));
+    }
+
+    #[test]
+    fn test_default_sort_and_validation() {
+        assert_eq!(SearchQuery::default().sort(), SearchSort::Newest);
+        let text = SearchQuery {
+            text: Some("rust".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(text.sort(), SearchSort::Relevance);
+
+        let bad_range = SearchQuery {
+            min_duration_sec: Some(10),
+            max_duration_sec: Some(5),
+            ..Default::default()
+        };
+        assert!(bad_range.validate().is_err());
+
+        let long_text = SearchQuery {
+            text: Some("a".repeat(MAX_QUERY_TEXT_LENGTH + 1)),
+            ..Default::default()
+        };
+        assert!(long_text.validate().is_err());
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let stats = VideoStats {
+            views: 10,
+            likes: 0,
+            tips_total: 0,
+        };
+        let quiet = VideoStats::default();
+        let viewed = SearchSort::MostViewed.key(1, &stats, "a");
+        assert!(viewed < SearchSort::MostViewed.key(2, &quiet, "b"));
+        assert!(viewed.starts_with(&SearchSort::MostViewed.prefix()));
+        assert!(SearchSort::Newest.key(2, &quiet, "b") < SearchSort::Newest.key(1, &stats, "a"));
+    }
+}
@@ -0,0 +1,284 @@
+use candid::Principal;
+use ic_cdk::query;
+use ic_stable_structures::StableBTreeMap;
+use std::{cell::RefCell, collections::BTreeSet, thread::LocalKey};
+
+use crate::{
+    autocomplete::{
+        entry_id, entry_key, normalize_for_autocomplete, parse_rank_key, rank_keys, ranked_prefix,
+        AutocompleteKind, Suggestion,
+    },
+    service::{clips::is_listed_with_source, follows::follower_count, video_stats::video_stats},
+    user_profile::UserProfile,
+    video_metadata::VideoMetadata,
+    Memory, AUTOCOMPLETE_PROFILES, AUTOCOMPLETE_PROFILE_RANKS, AUTOCOMPLETE_TAGS, AUTOCOMPLETE_TAG_RANKS,
+    AUTOCOMPLETE_TITLES, AUTOCOMPLETE_TITLE_RANKS, USER_PROFILES, VIDEOS,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 20;
+
+/// Ranked entries read per keystroke. They are read most popular first, so
+/// this only cuts short prefixes longer than the ranked ones that few
+/// entries match.
+const MAX_SCANNED_ENTRIES: usize = 500;
+
+type RankMap = StableBTreeMap<String, (), Memory>;
+
+/// Suggests tags, video titles or creators starting with `prefix`, most popular first
+#[query]
+pub fn autocomplete(prefix: String, kind: AutocompleteKind, limit: Option<u32>) -> Vec<Suggestion> {
+    let prefix = normalize_for_autocomplete(&prefix);
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    match kind {
+        AutocompleteKind::Tag => most_popular(&AUTOCOMPLETE_TAG_RANKS, &prefix, limit, |tag, popularity| {
+            Some(Suggestion {
+                text: tag.to_string(),
+                id: None,
+                popularity,
+            })
+        }),
+        AutocompleteKind::Title => most_popular(&AUTOCOMPLETE_TITLE_RANKS, &prefix, limit, |entry, popularity| {
+            let video_id = entry_id(entry).to_string();
+            VIDEOS
+                .with(|videos| videos.borrow().get(&video_id))
+                .filter(is_listed_with_source)
+                .map(|metadata| Suggestion {
+                    text: metadata.title,
+                    id: Some(video_id),
+                    popularity,
+                })
+        }),
+        AutocompleteKind::Creator => most_popular(&AUTOCOMPLETE_PROFILE_RANKS, &prefix, limit, |entry, popularity| {
+            let principal = entry_id(entry).to_string();
+            USER_PROFILES
+                .with(|profiles| profiles.borrow().get(&principal))
+                .map(|profile| Suggestion {
+                    text: profile.name,
+                    id: Some(principal),
+                    popularity,
+                })
+        }),
+    }
+}
+
+/// Reads the entries starting with `prefix` in popularity order and keeps
+/// the first `limit` that `suggest` turns into suggestions
+fn most_popular(
+    ranks: &'static LocalKey<RefCell<RankMap>>,
+    prefix: &str,
+    limit: usize,
+    suggest: impl Fn(&str, u64) -> Option<Suggestion>,
+) -> Vec<Suggestion> {
+    let start = ranked_prefix(prefix);
+    ranks.with(|ranks| {
+        ranks
+            .borrow()
+            .range(start.clone()..)
+            .take_while(|(key, _)| key.starts_with(&start))
+            .take(MAX_SCANNED_ENTRIES)
+            .filter_map(|(key, _)| {
+                let (popularity, entry) = parse_rank_key(&key)?;
+                if !entry.starts_with(prefix) {
+                    return None;
+                }
+                suggest(entry, popularity)
+            })
+            .take(limit)
+            .collect()
+    })
+}
+
+/// Moves an entry's rank keys from its popularity before to its popularity
+/// after. None removes or adds the entry.
+fn rerank(ranks: &'static LocalKey<RefCell<RankMap>>, entry: &str, before: Option<u64>, after: Option<u64>) {
+    ranks.with(|ranks| {
+        let mut ranks = ranks.borrow_mut();
+        for key in before.map(|popularity| rank_keys(entry, popularity)).unwrap_or_default() {
+            ranks.remove(&key);
+        }
+        for key in after.map(|popularity| rank_keys(entry, popularity)).unwrap_or_default() {
+            ranks.insert(key, ());
+        }
+    });
+}
+
+fn normalized_tags(metadata: Option<&VideoMetadata>) -> BTreeSet<String> {
+    metadata
+        .map(|metadata| {
+            metadata
+                .tags
+                .iter()
+                .map(|tag| normalize_for_autocomplete(tag))
+                .filter(|tag| !tag.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Moves a video's tag counts and title entry from `before` to `after`.
+/// Pass None for `before` on create and for `after` on purge.
+/// Videos in the trash count as gone.
+pub fn relink_video_suggestions(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>) {
+    let before = before.filter(|metadata| !metadata.is_deleted());
+    let after = after.filter(|metadata| !metadata.is_deleted());
+    let old_tags = normalized_tags(before);
+    let new_tags = normalized_tags(after);
+    for tag in old_tags.difference(&new_tags) {
+        let count = AUTOCOMPLETE_TAGS.with(|tags| tags.borrow().get(tag)).unwrap_or_default();
+        let remaining = count.saturating_sub(1);
+        AUTOCOMPLETE_TAGS.with(|tags| {
+            let mut tags = tags.borrow_mut();
+            if remaining == 0 {
+                tags.remove(tag);
+            } else {
+                tags.insert(tag.clone(), remaining);
+            }
+        });
+        rerank(&AUTOCOMPLETE_TAG_RANKS, tag, Some(count), (remaining > 0).then_some(remaining));
+    }
+    for tag in new_tags.difference(&old_tags) {
+        let count = AUTOCOMPLETE_TAGS.with(|tags| tags.borrow().get(tag));
+        let updated = count.unwrap_or_default() + 1;
+        AUTOCOMPLETE_TAGS.with(|tags| tags.borrow_mut().insert(tag.clone(), updated));
+        rerank(&AUTOCOMPLETE_TAG_RANKS, tag, count, Some(updated));
+    }
+
+    let old_entry = before.map(|metadata| entry_key(&metadata.title, &metadata.video_id));
+    let views = old_entry
+        .as_ref()
+        .and_then(|entry| AUTOCOMPLETE_TITLES.with(|titles| titles.borrow_mut().remove(entry)));
+    if let (Some(entry), Some(views)) = (&old_entry, views) {
+        rerank(&AUTOCOMPLETE_TITLE_RANKS, entry, Some(views), None);
+    }
+    if let Some(metadata) = after {
+        let views = views.unwrap_or_else(|| video_stats(&metadata.video_id).views);
+        let entry = entry_key(&metadata.title, &metadata.video_id);
+        AUTOCOMPLETE_TITLES.with(|titles| titles.borrow_mut().insert(entry.clone(), views));
+        rerank(&AUTOCOMPLETE_TITLE_RANKS, &entry, None, Some(views));
+    }
+}
+
+/// Counts a view towards the video's title suggestion
+pub fn record_title_view(video_id: &str) {
+    let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id.to_string()));
+    let Some(metadata) = metadata.filter(|metadata| !metadata.is_deleted()) else {
+        return;
+    };
+    let entry = entry_key(&metadata.title, video_id);
+    let views = AUTOCOMPLETE_TITLES.with(|titles| titles.borrow().get(&entry));
+    let updated = views.unwrap_or_default() + 1;
+    AUTOCOMPLETE_TITLES.with(|titles| titles.borrow_mut().insert(entry.clone(), updated));
+    rerank(&AUTOCOMPLETE_TITLE_RANKS, &entry, views, Some(updated));
+}
+
+/// Moves a creator's suggestion from their previous profile name to the new one
+pub fn relink_profile_suggestion(principal: &Principal, before: Option<&UserProfile>, after: &UserProfile) {
+    let principal_text = principal.to_string();
+    let old_entry = before.map(|profile| entry_key(&profile.name, &principal_text));
+    let followers = old_entry
+        .as_ref()
+        .and_then(|entry| AUTOCOMPLETE_PROFILES.with(|profiles| profiles.borrow_mut().remove(entry)));
+    if let (Some(entry), Some(followers)) = (&old_entry, followers) {
+        rerank(&AUTOCOMPLETE_PROFILE_RANKS, entry, Some(followers), None);
+    }
+    let followers = followers.unwrap_or_else(|| follower_count(principal));
+    let entry = entry_key(&after.name, &principal_text);
+    AUTOCOMPLETE_PROFILES.with(|profiles| profiles.borrow_mut().insert(entry.clone(), followers));
+    rerank(&AUTOCOMPLETE_PROFILE_RANKS, &entry, None, Some(followers));
+}
+
+/// Adjusts the follower count of a creator's suggestion after a follow or unfollow
+pub fn adjust_creator_followers(principal: &Principal, followed: bool) {
+    let principal_text = principal.to_string();
+    let Some(profile) = USER_PROFILES.with(|profiles| profiles.borrow().get(&principal_text)) else {
+        return;
+    };
+    let entry = entry_key(&profile.name, &principal_text);
+    let followers = AUTOCOMPLETE_PROFILES.with(|profiles| profiles.borrow().get(&entry));
+    let updated = match followed {
+        true => followers.unwrap_or_default() + 1,
+        false => followers.unwrap_or_default().saturating_sub(1),
+    };
+    AUTOCOMPLETE_PROFILES.with(|profiles| profiles.borrow_mut().insert(entry.clone(), updated));
+    rerank(&AUTOCOMPLETE_PROFILE_RANKS, &entry, followers, Some(updated));
+}
+
+/// Builds the suggestion maps for data stored before autocomplete existed
+pub fn backfill_autocomplete() {
+    let is_empty = AUTOCOMPLETE_TAGS.with(|tags| tags.borrow().is_empty())
+        && AUTOCOMPLETE_TITLES.with(|titles| titles.borrow().is_empty())
+        && AUTOCOMPLETE_PROFILES.with(|profiles| profiles.borrow().is_empty());
+    if !is_empty {
+        return;
+    }
+
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        relink_video_suggestions(None, Some(metadata));
+    }
+
+    let profiles: Vec<(String, UserProfile)> =
+        USER_PROFILES.with(|profiles| profiles.borrow().iter().collect());
+    for (principal, profile) in &profiles {
+        if let Ok(principal) = Principal::from_text(principal) {
+            relink_profile_suggestion(&principal, None, profile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_metadata::test_video;
+
+    fn tags(suggestions: Vec<Suggestion>) -> Vec<String> {
+        suggestions.into_iter().map(|suggestion| suggestion.text).collect()
+    }
+
+    #[test]
+    fn test_most_popular_past_scanned_entries() {
+        let mut catalog = test_video("video1", Principal::anonymous(), &[], 0);
+        catalog.tags = (0..MAX_SCANNED_ENTRIES + 100).map(|i| format!("a{:04}", i)).collect();
+        relink_video_suggestions(None, Some(&catalog));
+
+        // The most used tag sorts last by name
+        let last = catalog.tags.last().unwrap().clone();
+        for video_id in ["video2", "video3"] {
+            relink_video_suggestions(None, Some(&test_video(video_id, Principal::anonymous(), &[&last], 0)));
+        }
+        assert_eq!(tags(autocomplete("A".to_string(), AutocompleteKind::Tag, Some(1))), vec![last.clone()]);
+        assert_eq!(tags(autocomplete("a05".to_string(), AutocompleteKind::Tag, Some(1))), vec![last.clone()]);
+
+        // Popularity drops as videos stop using the tag
+        for video_id in ["video2", "video3"] {
+            relink_video_suggestions(Some(&test_video(video_id, Principal::anonymous(), &[&last], 0)), None);
+        }
+        assert_eq!(tags(autocomplete("a".to_string(), AutocompleteKind::Tag, Some(1))), vec!["a0000"]);
+    }
+
+    #[test]
+    fn test_titles_rank_by_views() {
+        for video_id in ["video1", "video2"] {
+            let metadata = test_video(video_id, Principal::anonymous(), &[], 0);
+            VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+            relink_video_suggestions(None, Some(&metadata));
+        }
+        record_title_view("video2");
+
+        assert!(autocomplete(String::new(), AutocompleteKind::Title, None).is_empty());
+        let suggestions = autocomplete("video".to_string(), AutocompleteKind::Title, None);
+        assert_eq!(suggestions[0].id.as_deref(), Some("video2"));
+        assert_eq!(suggestions[0].popularity, 1);
+
+        // Prefixes longer than the ranked ones are matched among them
+        let suggestions = autocomplete("Video  Video1".to_string(), AutocompleteKind::Title, None);
+        let ids: Vec<Option<String>> = suggestions.into_iter().map(|suggestion| suggestion.id).collect();
+        assert_eq!(ids, vec![Some("video1".to_string())]);
+    }
+}
@@ -0,0 +1,284 @@
+use candid::Principal;
+use ic_cdk::update;
+
+use crate::{
+    caption_track::{caption_url_path, validate_label, validate_language, validate_webvtt, CaptionTrack},
+    service::http::HttpResponse,
+    CAPTION_CONTENT, VIDEOS,
+};
+
+const MAX_VTT_SIZE: usize = 200_000;
+const MAX_TRACKS_PER_VIDEO: usize = 20;
+
+fn content_key(video_id: &str, language: &str) -> String {
+    format!("{}:{}", video_id, language)
+}
+
+/// Adds or replaces the caption track for a language (only by uploader).
+/// Marking a track as default clears the flag on the video's other tracks.
+#[update]
+pub fn upsert_caption_track(
+    video_id: String,
+    language: String,
+    label: String,
+    is_default: bool,
+    vtt: String,
+) -> Result<Vec<CaptionTrack>, String> {
+    set_caption_track(video_id, ic_cdk::caller(), language, label, is_default, vtt)
+}
+
+fn set_caption_track(
+    video_id: String,
+    caller: Principal,
+    language: String,
+    label: String,
+    is_default: bool,
+    vtt: String,
+) -> Result<Vec<CaptionTrack>, String> {
+    validate_language(&language)?;
+    validate_label(&label)?;
+    if vtt.len() > MAX_VTT_SIZE {
+        return Err(format!("Caption file exceeds {} bytes", MAX_VTT_SIZE));
+    }
+    let cue_count = validate_webvtt(&vtt)?;
+
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+        let mut metadata = videos_map
+            .get(&video_id)
+            .filter(|m| !m.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        if metadata.uploader_principal != caller {
+            return Err("Only the uploader can manage captions".to_string());
+        }
+
+        let mut tracks = metadata.captions.take().unwrap_or_default();
+        tracks.retain(|track| track.language != language);
+        if tracks.len() >= MAX_TRACKS_PER_VIDEO {
+            return Err(format!(
+                "A video can have at most {} caption tracks",
+                MAX_TRACKS_PER_VIDEO
+            ));
+        }
+        if is_default {
+            tracks.iter_mut().for_each(|track| track.is_default = false);
+        }
+        tracks.push(CaptionTrack {
+            url_path: caption_url_path(&video_id, &language),
+            language: language.clone(),
+            label,
+            is_default,
+            cue_count,
+        });
+
+        CAPTION_CONTENT.with(|content| {
+            content
+                .borrow_mut()
+                .insert(content_key(&video_id, &language), vtt)
+        });
+
+        metadata.captions = Some(tracks.clone());
+        videos_map.insert(video_id, metadata);
+        Ok(tracks)
+    })
+}
+
+/// Removes the caption track for a language (only by uploader)
+#[update]
+pub fn remove_caption_track(video_id: String, language: String) -> Result<Vec<CaptionTrack>, String> {
+    delete_caption_track(video_id, ic_cdk::caller(), language)
+}
+
+fn delete_caption_track(video_id: String, caller: Principal, language: String) -> Result<Vec<CaptionTrack>, String> {
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+        let mut metadata = videos_map
+            .get(&video_id)
+            .filter(|m| !m.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        if metadata.uploader_principal != caller {
+            return Err("Only the uploader can manage captions".to_string());
+        }
+
+        let mut tracks = metadata.captions.take().unwrap_or_default();
+        let count_before = tracks.len();
+        tracks.retain(|track| track.language != language);
+        if tracks.len() == count_before {
+            return Err("Caption track not found".to_string());
+        }
+
+        CAPTION_CONTENT.with(|content| {
+            content
+                .borrow_mut()
+                .remove(&content_key(&video_id, &language))
+        });
+
+        // Like other optional fields, no tracks left is stored as None
+        metadata.captions = (!tracks.is_empty()).then(|| tracks.clone());
+        videos_map.insert(video_id, metadata);
+        Ok(tracks)
+    })
+}
+
+/// Drops up to `limit` stored caption files of a video that is being purged.
+/// Returns how many were dropped.
+pub fn remove_captions_for_video(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    CAPTION_CONTENT.with(|content| {
+        let mut content_map = content.borrow_mut();
+        let keys: Vec<String> = content_map
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .take(limit)
+            .collect();
+        for key in &keys {
+            content_map.remove(key);
+        }
+        keys.len()
+    })
+}
+
+/// Serves `/captions/{video_id}/{language}.vtt` as text/vtt
+pub fn serve_caption(path: &str) -> HttpResponse {
+    let Some((video_id, language)) = path
+        .strip_prefix("/captions/")
+        .and_then(|rest| rest.strip_suffix(".vtt"))
+        .and_then(|rest| rest.split_once('/'))
+    else {
+        return HttpResponse::text(404, "Not found");
+    };
+
+    let video_is_visible = VIDEOS.with(|videos| {
+        videos
+            .borrow()
+            .get(&video_id.to_string())
+            .is_some_and(|metadata| !metadata.is_deleted())
+    });
+    if !video_is_visible {
+        return HttpResponse::text(404, "Not found");
+    }
+
+    match CAPTION_CONTENT.with(|content| content.borrow().get(&content_key(video_id, language))) {
+        Some(vtt) => {
+            let mut response = HttpResponse::new(200, "text/vtt; charset=utf-8", vtt.into_bytes());
+            response
+                .headers
+                .push(("Access-Control-Allow-Origin".to_string(), "*".to_string()));
+            response
+        }
+        None => HttpResponse::text(404, "Not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_metadata::test_video;
+
+    const VTT: &str = "WEBVTT
+
+00:00.000 --> 00:02.000
+Hello
+";
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    fn upload(video_id: &str, uploader: Principal) {
+        let metadata = test_video(video_id, uploader, &[], 0);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata));
+    }
+
+    fn set(
+        video_id: &str,
+        caller: Principal,
+        language: &str,
+        label: &str,
+        is_default: bool,
+    ) -> Result<Vec<CaptionTrack>, String> {
+        set_caption_track(
+            video_id.to_string(),
+            caller,
+            language.to_string(),
+            label.to_string(),
+            is_default,
+            VTT.to_string(),
+        )
+    }
+
+    fn stored_captions(video_id: &str) -> Option<Vec<CaptionTrack>> {
+        VIDEOS
+            .with(|videos| videos.borrow().get(&video_id.to_string()))
+            .and_then(|metadata| metadata.captions)
+    }
+
+    #[test]
+    fn test_tracks_replace_by_language() {
+        let owner = principal(1);
+        upload("video1", owner);
+
+        set("video1", owner, "en", "English", true).unwrap();
+        let tracks = set("video1", owner, "fr", "Français", true).unwrap();
+        assert_eq!(tracks.iter().filter(|track| track.is_default).count(), 1);
+
+        // Sending a language again replaces its track
+        let tracks = set("video1", owner, "en", "English (CC)", false).unwrap();
+        assert_eq!(tracks.len(), 2);
+        let english = tracks.iter().find(|track| track.language == "en").unwrap();
+        assert_eq!((english.label.as_str(), english.cue_count), ("English (CC)", 1));
+        assert_eq!(english.url_path, "/captions/video1/en.vtt");
+        assert_eq!(stored_captions("video1"), Some(tracks));
+    }
+
+    #[test]
+    fn test_track_limit() {
+        let owner = principal(1);
+        upload("video1", owner);
+        for i in 0..MAX_TRACKS_PER_VIDEO {
+            set("video1", owner, &format!("en-{}", i), "English", false).unwrap();
+        }
+
+        assert!(set("video1", owner, "fr", "Français", false).is_err());
+        // Replacing a track doesn't count towards the limit
+        assert!(set("video1", owner, "en-0", "English", true).is_ok());
+        assert_eq!(stored_captions("video1").map(|tracks| tracks.len()), Some(MAX_TRACKS_PER_VIDEO));
+    }
+
+    #[test]
+    fn test_only_uploader_manages_tracks() {
+        let (owner, stranger) = (principal(1), principal(2));
+        upload("video1", owner);
+        set("video1", owner, "en", "English", true).unwrap();
+
+        assert!(set("video1", stranger, "fr", "Français", false).is_err());
+        assert!(delete_caption_track("video1".to_string(), stranger, "en".to_string()).is_err());
+        assert!(set("missing", owner, "en", "English", false).is_err());
+        assert_eq!(stored_captions("video1").map(|tracks| tracks.len()), Some(1));
+    }
+
+    #[test]
+    fn test_removed_track_is_not_served() {
+        let owner = principal(1);
+        upload("video1", owner);
+        set("video1", owner, "en", "English", true).unwrap();
+        set("video1", owner, "fr", "Français", false).unwrap();
+
+        let response = serve_caption("/captions/video1/en.vtt");
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_slice(), VTT.as_bytes());
+
+        let tracks = delete_caption_track("video1".to_string(), owner, "en".to_string()).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(serve_caption("/captions/video1/en.vtt").status_code, 404);
+        assert!(delete_caption_track("video1".to_string(), owner, "en".to_string()).is_err());
+
+        // Removing the last track clears the field
+        delete_caption_track("video1".to_string(), owner, "fr".to_string()).unwrap();
+        assert_eq!(stored_captions("video1"), None);
+        assert_eq!(serve_caption("/captions/video1/fr.vtt").status_code, 404);
+    }
+}
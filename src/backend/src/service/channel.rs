@@ -0,0 +1,100 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+
+use crate::{
+    channel::{channel_page, ChannelEntry, ChannelSort},
+    page::{page_size, Page},
+    service::{clips::is_listed_with_source, video_stats::video_stats},
+    video_metadata::VideoMetadata,
+    CHANNEL_PINS, UPLOADER_VIDEOS, VIDEOS,
+};
+
+/// Pins one of the caller's videos to the top of their channel, at `position` or last
+#[update]
+pub fn pin_video(video_id: String, position: Option<u32>) -> Result<Vec<String>, String> {
+    let caller = ic_cdk::caller();
+
+    let metadata = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Video not found".to_string())?;
+    if metadata.uploader_principal != caller {
+        return Err("You can only pin your own videos".to_string());
+    }
+
+    CHANNEL_PINS.with(|pins| {
+        let mut pins_map = pins.borrow_mut();
+        let mut pinned = pins_map.get(&caller.to_string()).unwrap_or_default();
+        pinned.pin(video_id, position)?;
+        pins_map.insert(caller.to_string(), pinned.clone());
+        Ok(pinned.0)
+    })
+}
+
+#[update]
+pub fn unpin_video(video_id: String) -> Result<Vec<String>, String> {
+    let caller = ic_cdk::caller();
+
+    CHANNEL_PINS.with(|pins| {
+        let mut pins_map = pins.borrow_mut();
+        let mut pinned = pins_map.get(&caller.to_string()).unwrap_or_default();
+        if !pinned.unpin(&video_id) {
+            return Err("Video is not pinned".to_string());
+        }
+        pins_map.insert(caller.to_string(), pinned.clone());
+        Ok(pinned.0)
+    })
+}
+
+/// Lists a creator's videos with pinned videos first, then sorted by `sort`.
+/// Pass the returned cursor to fetch the next page.
+#[query]
+pub fn get_channel_videos(
+    principal: Principal,
+    sort: ChannelSort,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", principal);
+    let entries: Vec<ChannelEntry> = UPLOADER_VIDEOS.with(|uploads| {
+        uploads
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, timestamp)| {
+                let video_id = key[prefix.len()..].to_string();
+                let views = match sort {
+                    ChannelSort::MostViewed => video_stats(&video_id).views,
+                    _ => 0,
+                };
+                ChannelEntry {
+                    video_id,
+                    timestamp,
+                    views,
+                }
+            })
+            .collect()
+    });
+    let pinned = CHANNEL_PINS
+        .with(|pins| pins.borrow().get(&principal.to_string()))
+        .unwrap_or_default();
+
+    channel_page(entries, &pinned, sort, cursor.as_deref(), page_size(limit), |video_id| {
+        VIDEOS
+            .with(|videos| videos.borrow().get(&video_id.to_string()))
+            .filter(is_listed_with_source)
+    })
+}
+
+/// Unpins a purged video from its uploader's channel
+pub fn unpin_purged_video(uploader: &Principal, video_id: &str) {
+    CHANNEL_PINS.with(|pins| {
+        let mut pins_map = pins.borrow_mut();
+        if let Some(mut pinned) = pins_map.get(&uploader.to_string()) {
+            if pinned.unpin(video_id) {
+                pins_map.insert(uploader.to_string(), pinned);
+            }
+        }
+    });
+}
+
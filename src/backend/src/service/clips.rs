@@ -0,0 +1,314 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    clip::{media_fragment, validate_clip_range, with_fragment, ClipSource, ResolvedClip},
+    page::{collect_page, page_size, start_after, Page},
+    processing_status::ProcessingStatus,
+    service::{
+        autocomplete::relink_video_suggestions,
+        profile_search::relink_profile_video_count,
+        search::index_video,
+        tag_pages::relink_tag_aggregates,
+        tags::merge_hashtags_and_mentions,
+        trending::record_upload_activity,
+        video::{ensure_video_id_salt, mint_video_id, record_uploader_video},
+        video_stats::relink_video_rankings,
+    },
+    video_metadata::VideoMetadata,
+    CLIP_LINKS, VIDEOS,
+};
+
+const MAX_CLIP_TITLE_LENGTH: usize = 150;
+
+/// Clips sort newest first under their source
+pub fn link_key(source_video_id: &str, timestamp: u64, clip_id: &str) -> String {
+    format!("{}:{:020}:{}", source_video_id, u64::MAX - timestamp, clip_id)
+}
+
+/// Whether a video should appear in feeds and search. A clip can't be played
+/// without its source, so it is only listed while the source is.
+pub fn is_listed_with_source(metadata: &VideoMetadata) -> bool {
+    metadata.is_listed()
+        && metadata.clip_of.as_ref().is_none_or(|clip_source| {
+            VIDEOS
+                .with(|videos| videos.borrow().get(&clip_source.source_video_id))
+                .is_some_and(|source| source.is_listed())
+        })
+}
+
+/// Creates a clip of `start_sec..end_sec` of a source video.
+///
+/// The clip is stored as a video of its own, with its own id and title, so it
+/// appears in search and feeds. It has no media of its own; `resolve_clip`
+/// points players at the source.
+#[update]
+pub async fn create_clip(
+    source_video_id: String,
+    title: String,
+    start_sec: u32,
+    end_sec: u32,
+) -> Result<VideoMetadata, String> {
+    let clip = build_clip(&source_video_id, ic_cdk::caller(), title, start_sec, end_sec)?;
+    ensure_video_id_salt().await?;
+    Ok(store_clip(clip, &source_video_id, ic_cdk::api::time() / 1_000_000_000))
+}
+
+/// Validates a clip and builds its metadata, without an ID yet
+fn build_clip(
+    source_video_id: &str,
+    caller: Principal,
+    title: String,
+    start_sec: u32,
+    end_sec: u32,
+) -> Result<VideoMetadata, String> {
+    if title.trim().is_empty() || title.chars().count() > MAX_CLIP_TITLE_LENGTH {
+        return Err(format!(
+            "Clip title must be between 1 and {} characters",
+            MAX_CLIP_TITLE_LENGTH
+        ));
+    }
+
+    let source = VIDEOS
+        .with(|videos| videos.borrow().get(&source_video_id.to_string()))
+        .filter(|metadata| metadata.is_listed())
+        .ok_or_else(|| "Source video not found".to_string())?;
+    if source.clip_of.is_some() {
+        return Err("Clips can't be clipped, clip the source video instead".to_string());
+    }
+    let duration_sec = source
+        .duration_sec
+        .ok_or_else(|| "The source video's duration is unknown".to_string())?;
+    validate_clip_range(start_sec, end_sec, duration_sec)?;
+
+    let mut clip = VideoMetadata {
+        video_id: String::new(),
+        uploader_principal: caller,
+        tags: source.tags.clone(),
+        title,
+        storage_ref: None,
+        renditions: Vec::new(),
+        timestamp: 0,
+        deleted_at: None,
+        processing_status: Some(ProcessingStatus::Ready),
+        captions: None,
+        description: None,
+        duration_sec: Some(end_sec - start_sec),
+        chapters: None,
+        aspect_ratio: source.aspect_ratio,
+        primary_language: source.primary_language.clone(),
+        content_rating: source.content_rating,
+        allow_comments: None,
+        content_hash: None,
+        remix_of: None,
+        allow_remixes: None,
+        sound_id: None,
+        credits: None,
+        clip_of: Some(ClipSource {
+            source_video_id: source_video_id.to_string(),
+            start_sec,
+            end_sec,
+        }),
+        mentions: None,
+        chapters_from_description: None,
+    };
+    merge_hashtags_and_mentions(&mut clip)?;
+    Ok(clip)
+}
+
+/// Mints the clip's ID and stores it linked to its source
+fn store_clip(mut clip: VideoMetadata, source_video_id: &str, timestamp: u64) -> VideoMetadata {
+    let clip_id = mint_video_id();
+    clip.video_id = clip_id.clone();
+    clip.timestamp = timestamp;
+
+    VIDEOS.with(|videos| videos.borrow_mut().insert(clip_id.clone(), clip.clone()));
+    CLIP_LINKS.with(|links| {
+        links
+            .borrow_mut()
+            .insert(link_key(source_video_id, timestamp, &clip_id), timestamp)
+    });
+    record_uploader_video(&clip);
+    relink_profile_video_count(None, Some(&clip));
+    relink_video_rankings(None, Some(&clip));
+    index_video(&clip);
+    relink_video_suggestions(None, Some(&clip));
+    relink_tag_aggregates(None, Some(&clip));
+    record_upload_activity(&clip);
+    clip
+}
+
+/// Returns the source media and the segment a clip plays
+#[query]
+pub fn resolve_clip(clip_id: String) -> Result<ResolvedClip, String> {
+    let clip_source = VIDEOS
+        .with(|videos| videos.borrow().get(&clip_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .and_then(|metadata| metadata.clip_of)
+        .ok_or_else(|| "Clip not found".to_string())?;
+
+    let source = VIDEOS
+        .with(|videos| videos.borrow().get(&clip_source.source_video_id))
+        .filter(|metadata| metadata.is_listed())
+        .ok_or_else(|| "The source video is no longer available".to_string())?;
+
+    let fragment = media_fragment(clip_source.start_sec, clip_source.end_sec);
+    Ok(ResolvedClip {
+        clip_id,
+        source_video_id: clip_source.source_video_id,
+        storage_ref: source
+            .storage_ref
+            .map(|storage_ref| with_fragment(storage_ref, &fragment)),
+        renditions: source
+            .renditions
+            .into_iter()
+            .map(|mut rendition| {
+                rendition.storage_ref = with_fragment(rendition.storage_ref, &fragment);
+                rendition
+            })
+            .collect(),
+        start_sec: clip_source.start_sec,
+        end_sec: clip_source.end_sec,
+        media_fragment: fragment,
+    })
+}
+
+/// Lists clips made from a video, newest first
+#[query]
+pub fn list_clips_of_video(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    CLIP_LINKS.with(|links| {
+        let links = links.borrow();
+        let entries = links
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let (_, clip_id) = key.strip_prefix(&prefix)?.split_once(':')?;
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&clip_id.to_string()))?;
+                is_listed_with_source(&metadata).then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+/// Ids of up to `limit` clips made from a video
+pub fn clip_ids_of(video_id: &str, limit: usize) -> Vec<String> {
+    let prefix = format!("{}:", video_id);
+    CLIP_LINKS.with(|links| {
+        links
+            .borrow()
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .filter_map(|key| {
+                let (_, clip_id) = key[prefix.len()..].split_once(':')?;
+                Some(clip_id.to_string())
+            })
+            .take(limit)
+            .collect()
+    })
+}
+
+/// Drops the link between a purged clip and its source
+pub fn remove_clip_link(clip: &VideoMetadata) {
+    let Some(clip_source) = &clip.clip_of else {
+        return;
+    };
+    CLIP_LINKS.with(|links| {
+        links
+            .borrow_mut()
+            .remove(&link_key(&clip_source.source_video_id, clip.timestamp, &clip.video_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage_ref::StorageRef, video_metadata::test_video};
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    /// Stores a two minute source video played from a URL
+    fn upload_source(video_id: &str) -> VideoMetadata {
+        let mut metadata = test_video(video_id, principal(1), &["cats"], 10);
+        metadata.duration_sec = Some(120);
+        metadata.storage_ref = Some(StorageRef::Url {
+            url: "https://example.com/video.mp4".to_string(),
+        });
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        metadata
+    }
+
+    fn clip(source_video_id: &str, start_sec: u32, end_sec: u32, timestamp: u64) -> Result<VideoMetadata, String> {
+        let clip = build_clip(source_video_id, principal(2), "Best part".to_string(), start_sec, end_sec)?;
+        Ok(store_clip(clip, source_video_id, timestamp))
+    }
+
+    #[test]
+    fn test_clip_validation() {
+        upload_source("source");
+
+        assert!(clip("source", 0, 61, 20).is_err());
+        assert!(clip("missing", 0, 10, 20).is_err());
+        assert!(build_clip("source", principal(2), " ".to_string(), 0, 10).is_err());
+        let first = clip("source", 0, 60, 20).unwrap();
+        assert_eq!(first.duration_sec, Some(60));
+        assert_eq!(first.tags, vec!["cats".to_string()]);
+
+        // Clips point at the source rather than at another clip
+        let nested = clip(&first.video_id, 0, 10, 30);
+        assert!(nested.unwrap_err().contains("Clips can't be clipped"));
+    }
+
+    #[test]
+    fn test_resolve_clip_adds_fragment() {
+        upload_source("source");
+        let clip = clip("source", 5, 20, 20).unwrap();
+
+        let resolved = resolve_clip(clip.video_id).unwrap();
+        assert_eq!(resolved.media_fragment, "t=5,20");
+        assert_eq!(
+            resolved.storage_ref,
+            Some(StorageRef::Url {
+                url: "https://example.com/video.mp4#t=5,20".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_list_clips_of_video_pages_newest_first() {
+        upload_source("source");
+        let ids: Vec<String> = (0..3)
+            .map(|i| clip("source", i, i + 10, 20 + i as u64).unwrap().video_id)
+            .collect();
+
+        let first = list_clips_of_video("source".to_string(), None, Some(2)).unwrap();
+        let first_ids: Vec<&str> = first.items.iter().map(|clip| clip.video_id.as_str()).collect();
+        assert_eq!(first_ids, vec![ids[2].as_str(), ids[1].as_str()]);
+        let rest = list_clips_of_video("source".to_string(), first.next_cursor, Some(2)).unwrap();
+        assert_eq!(rest.items[0].video_id, ids[0]);
+        assert_eq!(rest.next_cursor, None);
+    }
+
+    #[test]
+    fn test_clips_of_trashed_sources_are_unlisted() {
+        let mut source = upload_source("source");
+        let clip = clip("source", 0, 10, 20).unwrap();
+        assert!(is_listed_with_source(&clip));
+
+        source.deleted_at = Some(30);
+        VIDEOS.with(|videos| videos.borrow_mut().insert("source".to_string(), source));
+        assert!(clip.is_listed());
+        assert!(!is_listed_with_source(&clip));
+        assert!(resolve_clip(clip.video_id).is_err());
+    }
+}
@@ -1,18 +1,24 @@
 use ic_cdk::{query, update};
+use std::ops::Bound;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{comment::{Comment, CommentList}, COMMENTS, VIDEOS};
+use crate::{
+    comment::Comment,
+    page::{collect_page, next_log_key, owner_key, page_size, start_after, Page},
+    COMMENTS, COMMENTS_BY_USER, LEGACY_COMMENTS, VIDEOS,
+};
 
 /// Posts a comment on a video
 #[update]
 pub fn post_comment(video_id: String, text: String) -> Result<Comment, String> {
-    // Verify the video exists
-    VIDEOS.with(|videos| {
-        if !videos.borrow().contains_key(&video_id) {
-            return Err("Video not found".to_string());
-        }
-        Ok(())
-    })?;
+    // Verify the video exists and takes comments
+    let metadata = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Video not found".to_string())?;
+    if !metadata.comments_allowed() {
+        return Err("Comments are turned off for this video".to_string());
+    }
     
     // Generate timestamp
     let timestamp = SystemTime::now()
@@ -23,81 +29,195 @@ pub fn post_comment(video_id: String, text: String) -> Result<Comment, String> {
     // Create comment
     let comment = Comment {
         commenter_principal: ic_cdk::caller(),
-        video_id: video_id.clone(),
+        video_id,
         text,
         timestamp,
     };
     
     // Store comment
-    COMMENTS.with(|comments| {
-        let mut comments_map = comments.borrow_mut();
-        let video_comments = match comments_map.get(&video_id) {
-            Some(comment_list) => {
-                let mut existing = comment_list.0.clone();
-                existing.push(comment.clone());
-                CommentList(existing)
-            }
-            None => CommentList(vec![comment.clone()]),
-        };
-        comments_map.insert(video_id, video_comments);
-        Ok(comment)
-    })
+    store_comment(&comment);
+    Ok(comment)
 }
 
-/// Gets comments for a video
-#[query]
-pub fn get_comments(video_id: String) -> Vec<Comment> {
+/// Adds a comment to its video's comments and the commenter's index
+pub fn store_comment(comment: &Comment) {
+    let key = COMMENTS.with(|comments| {
+        let mut comments = comments.borrow_mut();
+        let key = next_log_key(&comments, &comment.video_id, comment.timestamp);
+        comments.insert(key.clone(), comment.clone());
+        key
+    });
+    let owner = owner_key(&comment.commenter_principal.to_string(), comment.timestamp, &key);
+    COMMENTS_BY_USER.with(|index| index.borrow_mut().insert(owner, key));
+}
+
+/// Removes a comment stored under `key` and its index entry
+fn remove_comment(key: &str) {
+    if let Some(comment) = COMMENTS.with(|comments| comments.borrow_mut().remove(&key.to_string())) {
+        let owner = owner_key(&comment.commenter_principal.to_string(), comment.timestamp, key);
+        COMMENTS_BY_USER.with(|index| index.borrow_mut().remove(&owner));
+    }
+}
+
+/// Keys of the comments on a video whose keys start with `prefix`
+fn comment_keys(prefix: &str) -> Vec<String> {
     COMMENTS.with(|comments| {
         comments
             .borrow()
-            .get(&video_id)
-            .map(|comment_list| comment_list.0.clone())
-            .unwrap_or_default()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key)
+            .collect()
     })
 }
 
-/// Gets all comments by the calling user
-#[query]
-pub fn get_my_comments() -> Vec<Comment> {
-    let caller = ic_cdk::caller();
-    
-    COMMENTS.with(|comments| {
+/// Removes up to `limit` comments on a video, oldest first, and returns how
+/// many were removed
+pub fn remove_comments_for_video(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    let keys: Vec<String> = COMMENTS.with(|comments| {
         comments
             .borrow()
-            .iter()
-            .flat_map(|(_, comment_list)| comment_list.0.clone())
-            .filter(|comment| comment.commenter_principal == caller)
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(key, _)| key)
             .collect()
-    })
+    });
+    for key in &keys {
+        remove_comment(key);
+    }
+    keys.len()
+}
+
+/// Moves comments stored as per-video lists into the keyed comments map
+pub fn migrate_legacy_comments() {
+    let video_ids: Vec<String> = LEGACY_COMMENTS.with(|legacy| legacy.borrow().iter().map(|(id, _)| id).collect());
+    for video_id in video_ids {
+        let Some(comment_list) = LEGACY_COMMENTS.with(|legacy| legacy.borrow_mut().remove(&video_id)) else {
+            continue;
+        };
+        for comment in &comment_list.0 {
+            store_comment(comment);
+        }
+    }
+}
+
+/// Gets comments for a video, oldest first
+#[query]
+pub fn get_comments(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Comment>, String> {
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(COMMENTS.with(|comments| {
+        let comments = comments.borrow();
+        let entries = comments
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix));
+        collect_page(entries, page_size(limit))
+    }))
+}
+
+/// Gets comments by the calling user, oldest first
+#[query]
+pub fn get_my_comments(cursor: Option<String>, limit: Option<u32>) -> Result<Page<Comment>, String> {
+    let prefix = format!("{}:", ic_cdk::caller());
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(COMMENTS_BY_USER.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, comment_key)| {
+                COMMENTS.with(|comments| comments.borrow().get(&comment_key)).map(|comment| (key, comment))
+            });
+        collect_page(entries, page_size(limit))
+    }))
 }
 
 /// Deletes a comment (only by the commenter)
 #[update]
 pub fn delete_comment(video_id: String, timestamp: u64) -> Result<(), String> {
     let caller = ic_cdk::caller();
-    
-    COMMENTS.with(|comments| {
-        let mut comments_map = comments.borrow_mut();
-        
-        // Check if the video has comments
-        if let Some(comment_list) = comments_map.get(&video_id) {
-            let mut video_comments = comment_list.0.clone();
-            
-            // Find the comment index
-            let comment_idx = video_comments
-                .iter()
-                .position(|c| c.timestamp == timestamp && c.commenter_principal == caller);
-            
-            if let Some(idx) = comment_idx {
-                // Remove the comment
-                video_comments.remove(idx);
-                comments_map.insert(video_id, CommentList(video_comments));
-                Ok(())
-            } else {
-                Err("Comment not found or you don't have permission to delete it".to_string())
-            }
-        } else {
-            Err("No comments found for this video".to_string())
+
+    let second = format!("{}:{:020}:", video_id, timestamp);
+    let key = comment_keys(&second).into_iter().find(|key| {
+        COMMENTS
+            .with(|comments| comments.borrow().get(key))
+            .is_some_and(|comment| comment.commenter_principal == caller)
+    });
+    match key {
+        Some(key) => {
+            remove_comment(&key);
+            Ok(())
         }
-    })
-}
\ No newline at end of file
+        None => Err("Comment not found or you don't have permission to delete it".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comment::CommentList;
+    use candid::Principal;
+
+    fn comment(video_id: &str, commenter: u8, timestamp: u64) -> Comment {
+        Comment {
+            commenter_principal: Principal::from_slice(&[commenter]),
+            video_id: video_id.to_string(),
+            text: format!("comment at {}", timestamp),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_comments_page_in_order() {
+        // Two comments in the same second are both kept
+        for timestamp in [10, 10, 11, 12, 13] {
+            store_comment(&comment("video1", 1, timestamp));
+        }
+        store_comment(&comment("video2", 1, 5));
+
+        let first = get_comments("video1".to_string(), None, Some(3)).unwrap();
+        assert_eq!(first.items.iter().map(|c| c.timestamp).collect::<Vec<_>>(), vec![10, 10, 11]);
+        let rest = get_comments("video1".to_string(), first.next_cursor, Some(3)).unwrap();
+        assert_eq!(rest.items.iter().map(|c| c.timestamp).collect::<Vec<_>>(), vec![12, 13]);
+        assert_eq!(rest.next_cursor, None);
+
+        // A cursor from another video's comments is rejected
+        let other = get_comments("video2".to_string(), None, Some(1)).unwrap();
+        assert!(other.next_cursor.is_none());
+        let foreign = crate::page::encode_cursor("video2:x");
+        assert!(get_comments("video1".to_string(), Some(foreign), None).is_err());
+
+        remove_comments_for_video("video1", usize::MAX);
+        assert!(get_comments("video1".to_string(), None, None).unwrap().items.is_empty());
+        assert_eq!(COMMENTS_BY_USER.with(|index| index.borrow().len()), 1);
+    }
+
+    #[test]
+    fn test_migrate_legacy_comments() {
+        let legacy = vec![comment("video1", 1, 10), comment("video1", 2, 10), comment("video1", 1, 20)];
+        LEGACY_COMMENTS.with(|comments| comments.borrow_mut().insert("video1".to_string(), CommentList(legacy.clone())));
+
+        migrate_legacy_comments();
+
+        assert!(LEGACY_COMMENTS.with(|comments| comments.borrow().is_empty()));
+        assert_eq!(get_comments("video1".to_string(), None, None).unwrap().items, legacy);
+        let by_first_user: Vec<String> = COMMENTS_BY_USER.with(|index| {
+            let prefix = format!("{}:", Principal::from_slice(&[1]));
+            index
+                .borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .map(|(_, comment_key)| comment_key)
+                .collect()
+        });
+        assert_eq!(by_first_user.len(), 2);
+    }
+}
@@ -0,0 +1,258 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    credit::{merge_credits, Credit, CreditInput, CreditStatus},
+    page::{collect_page, page_size, start_after, Page},
+    video_metadata::VideoMetadata,
+    CREDITED_VIDEOS, VIDEOS,
+};
+
+fn credit_key(principal: &Principal, video_id: &str) -> String {
+    format!("{}:{}", principal, video_id)
+}
+
+/// Replaces the co-creators credited on a video (only by uploader).
+/// New or changed credits stay pending until the co-creator accepts them.
+#[update]
+pub fn set_video_credits(video_id: String, credits: Vec<CreditInput>) -> Result<Vec<Credit>, String> {
+    set_credits(video_id, ic_cdk::caller(), credits)
+}
+
+fn set_credits(video_id: String, caller: Principal, credits: Vec<CreditInput>) -> Result<Vec<Credit>, String> {
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+        let mut metadata = videos_map
+            .get(&video_id)
+            .filter(|m| !m.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        if metadata.uploader_principal != caller {
+            return Err("Only the uploader can manage credits".to_string());
+        }
+
+        let current = metadata.credits.take().unwrap_or_default();
+        let credits = merge_credits(&metadata.uploader_principal, &current, credits)?;
+        metadata.credits = Some(credits.clone()).filter(|c| !c.is_empty());
+        videos_map.insert(video_id.clone(), metadata);
+        relink_credits(&video_id, &current, &credits);
+        Ok(credits)
+    })
+}
+
+/// Accepts the caller's pending credit on a video
+#[update]
+pub fn accept_video_credit(video_id: String) -> Result<Credit, String> {
+    accept_credit(video_id, ic_cdk::caller())
+}
+
+fn accept_credit(video_id: String, caller: Principal) -> Result<Credit, String> {
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+        let mut metadata = videos_map
+            .get(&video_id)
+            .filter(|m| !m.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        let credit = metadata
+            .credits
+            .iter_mut()
+            .flatten()
+            .find(|credit| credit.principal == caller)
+            .ok_or_else(|| "You are not credited on this video".to_string())?;
+        if credit.status == CreditStatus::Accepted {
+            return Err("Credit is already accepted".to_string());
+        }
+        credit.status = CreditStatus::Accepted;
+        let credit = credit.clone();
+
+        videos_map.insert(video_id, metadata);
+        Ok(credit)
+    })
+}
+
+/// Removes the caller's credit from a video, whether pending or accepted
+#[update]
+pub fn decline_video_credit(video_id: String) -> Result<(), String> {
+    decline_credit(video_id, ic_cdk::caller())
+}
+
+fn decline_credit(video_id: String, caller: Principal) -> Result<(), String> {
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+        let mut metadata = videos_map
+            .get(&video_id)
+            .filter(|m| !m.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        let mut credits = metadata.credits.take().unwrap_or_default();
+        let count_before = credits.len();
+        credits.retain(|credit| credit.principal != caller);
+        if credits.len() == count_before {
+            return Err("You are not credited on this video".to_string());
+        }
+
+        metadata.credits = Some(credits).filter(|c| !c.is_empty());
+        videos_map.insert(video_id.clone(), metadata);
+        CREDITED_VIDEOS.with(|credited| credited.borrow_mut().remove(&credit_key(&caller, &video_id)));
+        Ok(())
+    })
+}
+
+/// Lists videos where the caller has a credit waiting for acceptance
+#[query]
+pub fn list_my_pending_credits(cursor: Option<String>, limit: Option<u32>) -> Result<Page<VideoMetadata>, String> {
+    pending_credits(ic_cdk::caller(), cursor, limit)
+}
+
+fn pending_credits(
+    caller: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", caller);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    CREDITED_VIDEOS.with(|credited| {
+        let credited = credited.borrow();
+        let entries = credited
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let video_id = key.strip_prefix(&prefix)?.to_string();
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id))?;
+                let pending = !metadata.is_deleted()
+                    && metadata.credits.iter().flatten().any(|credit| {
+                        credit.principal == caller && credit.status == CreditStatus::Pending
+                    });
+                pending.then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+/// Moves a video's entries in the credited videos index from the `before`
+/// credits to the `after` ones. Purged videos pass no `after` credits.
+pub fn relink_credits(video_id: &str, before: &[Credit], after: &[Credit]) {
+    CREDITED_VIDEOS.with(|credited| {
+        let mut credited = credited.borrow_mut();
+        for credit in before {
+            if !after.iter().any(|other| other.principal == credit.principal) {
+                credited.remove(&credit_key(&credit.principal, video_id));
+            }
+        }
+        for credit in after {
+            credited.insert(credit_key(&credit.principal, video_id), ());
+        }
+    });
+}
+
+/// Indexes credits given before credited videos were indexed
+pub fn backfill_credits_index() {
+    if CREDITED_VIDEOS.with(|credited| !credited.borrow().is_empty()) {
+        return;
+    }
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        relink_credits(&metadata.video_id, &[], metadata.credits.as_deref().unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        credit::CreditRole, service::tips::split_tip, user_profile::UserProfile, video_metadata::test_video,
+        USER_PROFILES,
+    };
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    fn upload(video_id: &str, uploader: Principal) {
+        let metadata = test_video(video_id, uploader, &[], 0);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata));
+    }
+
+    fn input(id: u8, share_bps: u32) -> CreditInput {
+        CreditInput {
+            principal: principal(id),
+            role: CreditRole::Performer,
+            share_bps,
+        }
+    }
+
+    fn pending_ids(caller: Principal) -> Vec<String> {
+        pending_credits(caller, None, None)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|metadata| metadata.video_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_only_the_uploader_sets_credits() {
+        upload("video1", principal(1));
+
+        let denied = set_credits("video1".to_string(), principal(2), vec![input(3, 1_000)]);
+        assert!(denied.is_err());
+        let credits = set_credits("video1".to_string(), principal(1), vec![input(3, 1_000)]).unwrap();
+        assert_eq!(credits[0].status, CreditStatus::Pending);
+        assert!(set_credits("missing".to_string(), principal(1), vec![]).is_err());
+    }
+
+    #[test]
+    fn test_accept_and_decline() {
+        upload("video1", principal(1));
+        upload("video2", principal(1));
+        set_credits("video1".to_string(), principal(1), vec![input(2, 1_000)]).unwrap();
+        set_credits("video2".to_string(), principal(1), vec![input(2, 1_000), input(3, 500)]).unwrap();
+        assert_eq!(pending_ids(principal(2)), vec!["video1", "video2"]);
+
+        let page = pending_credits(principal(2), None, Some(1)).unwrap();
+        assert_eq!(page.items.len(), 1);
+        let rest = pending_credits(principal(2), page.next_cursor, Some(1)).unwrap();
+        assert_eq!(rest.items[0].video_id, "video2");
+
+        assert_eq!(accept_credit("video1".to_string(), principal(2)).unwrap().status, CreditStatus::Accepted);
+        assert!(accept_credit("video1".to_string(), principal(2)).is_err());
+        assert!(accept_credit("video1".to_string(), principal(3)).is_err());
+        assert_eq!(pending_ids(principal(2)), vec!["video2"]);
+
+        decline_credit("video2".to_string(), principal(2)).unwrap();
+        assert!(pending_ids(principal(2)).is_empty());
+        assert!(decline_credit("video2".to_string(), principal(2)).is_err());
+        assert_eq!(pending_ids(principal(3)), vec!["video2"]);
+
+        // Changing an accepted share asks for acceptance again
+        set_credits("video1".to_string(), principal(1), vec![input(2, 2_000)]).unwrap();
+        assert_eq!(pending_ids(principal(2)), vec!["video1"]);
+    }
+
+    #[test]
+    fn test_tip_split_skips_unaccepted_credits() {
+        for (id, address) in [(1, "0xcreator"), (2, "0xaccepted"), (3, "0xpending"), (4, "0xdeclined")] {
+            let profile = UserProfile {
+                evm_address: address.to_string(),
+                name: String::new(),
+                avatar_url: String::new(),
+            };
+            USER_PROFILES.with(|profiles| profiles.borrow_mut().insert(principal(id).to_string(), profile));
+        }
+        upload("video1", principal(1));
+        let credits = vec![input(2, 2_000), input(3, 3_000), input(4, 1_000)];
+        set_credits("video1".to_string(), principal(1), credits).unwrap();
+        accept_credit("video1".to_string(), principal(2)).unwrap();
+        accept_credit("video1".to_string(), principal(4)).unwrap();
+        decline_credit("video1".to_string(), principal(4)).unwrap();
+
+        let metadata = VIDEOS.with(|videos| videos.borrow().get(&"video1".to_string())).unwrap();
+        let tips = split_tip(&metadata, 1_000, "0x1", "0xfan", 10).unwrap();
+        let recipients: Vec<(&str, u64)> = tips.iter().map(|tip| (tip.to_addr.as_str(), tip.amount)).collect();
+        assert_eq!(recipients, vec![("0xcreator", 800), ("0xaccepted", 200)]);
+    }
+}
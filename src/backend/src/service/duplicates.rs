@@ -0,0 +1,259 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    content_hash::{DuplicateFlag, DuplicatePolicy},
+    page::{collect_page, page_size, start_after, Page},
+    service::{moderation::is_moderator, video::video_is_live},
+    video_metadata::VideoMetadata,
+    CONTENT_HASH_INDEX, DUPLICATE_FLAGS, DUPLICATE_POLICY,
+};
+
+/// Chooses whether re-uploads are rejected or flagged (controllers only)
+#[update]
+pub fn set_duplicate_policy(policy: DuplicatePolicy) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only controllers can set the duplicate policy".to_string());
+    }
+
+    DUPLICATE_POLICY
+        .with(|p| p.borrow_mut().set(policy).map(|_| ()))
+        .map_err(|e| format!("Failed to store duplicate policy: {:?}", e))
+}
+
+#[query]
+pub fn get_duplicate_policy() -> DuplicatePolicy {
+    DUPLICATE_POLICY.with(|p| *p.borrow().get())
+}
+
+/// Lists uploads flagged as duplicates with the video each one duplicates (moderators only)
+#[query]
+pub fn list_duplicate_flags(cursor: Option<String>, limit: Option<u32>) -> Result<Page<DuplicateFlag>, String> {
+    if !is_moderator(&ic_cdk::caller()) {
+        return Err("Only moderators can view duplicate flags".to_string());
+    }
+    let start = start_after(cursor.as_deref(), "")?;
+
+    Ok(DUPLICATE_FLAGS.with(|flags| {
+        let flags = flags.borrow();
+        collect_page(flags.range((start, Bound::Unbounded)), page_size(limit))
+    }))
+}
+
+/// Returns the video an upload duplicates, if it was flagged (moderators only)
+#[query]
+pub fn get_duplicate_of(video_id: String) -> Result<Option<String>, String> {
+    if !is_moderator(&ic_cdk::caller()) {
+        return Err("Only moderators can view duplicate flags".to_string());
+    }
+
+    Ok(DUPLICATE_FLAGS.with(|flags| flags.borrow().get(&video_id).map(|flag| flag.duplicate_of)))
+}
+
+/// Looks up an earlier video with the same content hash, ignoring videos in
+/// the trash.
+///
+/// Fails under the Reject policy; under Flag returns the video the upload duplicates.
+pub fn check_duplicate(content_hash: &str) -> Result<Option<String>, String> {
+    let Some(original) = find_original(content_hash) else {
+        return Ok(None);
+    };
+
+    match get_duplicate_policy() {
+        DuplicatePolicy::Reject => Err("This file has already been uploaded".to_string()),
+        DuplicatePolicy::Flag => Ok(Some(original)),
+    }
+}
+
+/// Indexes a new upload's content hash and flags it if it duplicates `duplicate_of`
+pub fn record_upload(
+    content_hash: &str,
+    video_id: &str,
+    duplicate_of: Option<String>,
+    uploader_principal: Principal,
+    timestamp: u64,
+) {
+    CONTENT_HASH_INDEX.with(|index| {
+        index
+            .borrow_mut()
+            .insert(hash_key(content_hash, timestamp, video_id), ())
+    });
+    if let Some(duplicate_of) = duplicate_of {
+        DUPLICATE_FLAGS.with(|flags| {
+            flags.borrow_mut().insert(
+                video_id.to_string(),
+                DuplicateFlag {
+                    video_id: video_id.to_string(),
+                    duplicate_of,
+                    uploader_principal,
+                    flagged_at: timestamp,
+                },
+            );
+        });
+    }
+}
+
+/// Keeps the hash index in step with an edit that changes a video's content hash
+pub fn relink_content_hash(before: &VideoMetadata, after: &VideoMetadata) {
+    if before.content_hash == after.content_hash {
+        return;
+    }
+    CONTENT_HASH_INDEX.with(|index| {
+        let mut index = index.borrow_mut();
+        if let Some(content_hash) = &before.content_hash {
+            index.remove(&hash_key(content_hash, before.timestamp, &before.video_id));
+        }
+        if let Some(content_hash) = &after.content_hash {
+            index.insert(hash_key(content_hash, after.timestamp, &after.video_id), ());
+        }
+    });
+}
+
+/// Drops a purged video from the hash index and the duplicate flags. If it was
+/// the original of flagged copies, the earliest surviving copy takes its place.
+pub fn remove_from_duplicate_index(metadata: &VideoMetadata) {
+    let video_id = &metadata.video_id;
+    DUPLICATE_FLAGS.with(|flags| flags.borrow_mut().remove(video_id));
+    let Some(content_hash) = &metadata.content_hash else {
+        return;
+    };
+    CONTENT_HASH_INDEX.with(|index| {
+        index
+            .borrow_mut()
+            .remove(&hash_key(content_hash, metadata.timestamp, video_id))
+    });
+
+    let mut survivors = uploads_of(content_hash).into_iter();
+    let Some(new_original) = survivors.next() else {
+        return;
+    };
+    DUPLICATE_FLAGS.with(|flags| {
+        let mut flags = flags.borrow_mut();
+        if flags.get(&new_original).is_some_and(|flag| flag.duplicate_of == *video_id) {
+            flags.remove(&new_original);
+        }
+        for copy in survivors {
+            if let Some(mut flag) = flags.get(&copy).filter(|flag| flag.duplicate_of == *video_id) {
+                flag.duplicate_of = new_original.clone();
+                flags.insert(copy, flag);
+            }
+        }
+    });
+}
+
+fn hash_key(content_hash: &str, timestamp: u64, video_id: &str) -> String {
+    format!("{}:{:020}:{}", content_hash, timestamp, video_id)
+}
+
+/// Videos uploaded with a content hash, earliest first
+fn uploads_of(content_hash: &str) -> Vec<String> {
+    let prefix = format!("{}:", content_hash);
+    CONTENT_HASH_INDEX.with(|index| {
+        index
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| key.rsplit(':').next().map(str::to_string))
+            .collect()
+    })
+}
+
+/// The earliest upload of a file that is not in the trash
+fn find_original(content_hash: &str) -> Option<String> {
+    uploads_of(content_hash)
+        .into_iter()
+        .find(|video_id| video_is_live(video_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{video_metadata::test_video, VIDEOS};
+
+    const HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    /// Stores an upload of HASH the way `create_video_metadata` does, failing under Reject
+    fn try_upload(video_id: &str, timestamp: u64) -> Result<VideoMetadata, String> {
+        let duplicate_of = check_duplicate(HASH)?;
+        let mut metadata = test_video(video_id, principal(1), &[], timestamp);
+        metadata.content_hash = Some(HASH.to_string());
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        record_upload(HASH, video_id, duplicate_of, metadata.uploader_principal, timestamp);
+        Ok(metadata)
+    }
+
+    fn upload(video_id: &str, timestamp: u64) -> VideoMetadata {
+        try_upload(video_id, timestamp).unwrap()
+    }
+
+    fn set_policy(policy: DuplicatePolicy) {
+        DUPLICATE_POLICY.with(|p| p.borrow_mut().set(policy).unwrap());
+    }
+
+    fn flagged_original(video_id: &str) -> Option<String> {
+        DUPLICATE_FLAGS.with(|flags| flags.borrow().get(&video_id.to_string()).map(|flag| flag.duplicate_of))
+    }
+
+    #[test]
+    fn test_reuploads_are_flagged() {
+        // A file never uploaded before isn't flagged
+        assert_eq!(find_original(HASH), None);
+        upload("original", 1);
+        upload("copy", 2);
+        upload("another", 3);
+
+        assert_eq!(flagged_original("original"), None);
+        assert_eq!(flagged_original("copy"), Some("original".to_string()));
+        assert_eq!(flagged_original("another"), Some("original".to_string()));
+        assert_eq!(find_original(HASH), Some("original".to_string()));
+    }
+
+    #[test]
+    fn test_reject_policy() {
+        set_policy(DuplicatePolicy::Reject);
+        let mut original = upload("original", 1);
+
+        assert_eq!(try_upload("copy", 2).unwrap_err(), "This file has already been uploaded");
+
+        // A trashed original doesn't block the file
+        original.deleted_at = Some(3);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(original.video_id.clone(), original));
+        upload("copy", 4);
+        assert_eq!(flagged_original("copy"), None);
+        assert_eq!(find_original(HASH), Some("copy".to_string()));
+    }
+
+    #[test]
+    fn test_trashed_original_is_skipped() {
+        let mut original = upload("original", 1);
+        original.deleted_at = Some(2);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(original.video_id.clone(), original));
+
+        upload("copy", 3);
+        assert_eq!(flagged_original("copy"), None);
+        assert_eq!(find_original(HASH), Some("copy".to_string()));
+    }
+
+    #[test]
+    fn test_purged_original_is_replaced_by_earliest_copy() {
+        let original = upload("original", 1);
+        upload("copy", 2);
+        upload("another", 3);
+
+        VIDEOS.with(|videos| videos.borrow_mut().remove(&original.video_id));
+        remove_from_duplicate_index(&original);
+
+        assert_eq!(find_original(HASH), Some("copy".to_string()));
+        assert_eq!(flagged_original("copy"), None);
+        assert_eq!(flagged_original("another"), Some("copy".to_string()));
+
+        // Under Reject the surviving copy now blocks re-uploads
+        set_policy(DuplicatePolicy::Reject);
+        assert!(try_upload("again", 4).is_err());
+    }
+}
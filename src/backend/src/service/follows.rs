@@ -1,9 +1,15 @@
 // Follow system service for ShawtyFormVideo
 // Provides API methods for handling user follow relationships
 
-use crate::{FollowRelationship, FollowRelationshipList, FOLLOW_RELATIONSHIPS};
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    service::{autocomplete::adjust_creator_followers, profile_search::adjust_profile_followers},
+    FollowRelationship, FollowRelationshipList, Memory, FOLLOWERS, FOLLOW_RELATIONSHIPS,
+};
 use candid::Principal;
 use ic_cdk::caller;
+use ic_stable_structures::{StableBTreeMap, Storable};
+use std::ops::Bound;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Enables a user to follow another user
@@ -27,8 +33,8 @@ pub fn follow_user(principal_to_follow: Principal) -> Result<(), String> {
     
     // Create a composite key for storage
     let relationship_key = format!("{}:{}", 
-        caller_principal.to_string(), 
-        principal_to_follow.to_string()
+        caller_principal, 
+        principal_to_follow
     );
     
     // Check if already following
@@ -48,6 +54,8 @@ pub fn follow_user(principal_to_follow: Principal) -> Result<(), String> {
             .as_secs(),
     };
     
+    let timestamp = follow_relationship.timestamp;
+
     // Store the relationship in stable storage
     FOLLOW_RELATIONSHIPS.with(|relationships| {
         let mut relationships_map = relationships.borrow_mut();
@@ -56,6 +64,14 @@ pub fn follow_user(principal_to_follow: Principal) -> Result<(), String> {
             FollowRelationshipList(vec![follow_relationship]),
         );
     });
+    FOLLOWERS.with(|followers| {
+        followers.borrow_mut().insert(
+            format!("{}:{}", principal_to_follow, caller_principal),
+            timestamp,
+        )
+    });
+    adjust_creator_followers(&principal_to_follow, true);
+    adjust_profile_followers(&principal_to_follow, true);
     
     Ok(())
 }
@@ -76,8 +92,8 @@ pub fn unfollow_user(principal_to_unfollow: Principal) -> Result<(), String> {
     
     // Create the composite key for lookup
     let relationship_key = format!("{}:{}", 
-        caller_principal.to_string(), 
-        principal_to_unfollow.to_string()
+        caller_principal, 
+        principal_to_unfollow
     );
     
     // Remove the relationship from stable storage
@@ -85,6 +101,13 @@ pub fn unfollow_user(principal_to_unfollow: Principal) -> Result<(), String> {
         let mut relationships_map = relationships.borrow_mut();
         if relationships_map.contains_key(&relationship_key) {
             relationships_map.remove(&relationship_key);
+            FOLLOWERS.with(|followers| {
+                followers
+                    .borrow_mut()
+                    .remove(&format!("{}:{}", principal_to_unfollow, caller_principal))
+            });
+            adjust_creator_followers(&principal_to_unfollow, false);
+            adjust_profile_followers(&principal_to_unfollow, false);
             Ok(())
         } else {
             Err("You are not following this user".to_string())
@@ -92,68 +115,102 @@ pub fn unfollow_user(principal_to_unfollow: Principal) -> Result<(), String> {
     })
 }
 
-/// Retrieves all followers of a specified user
+/// Retrieves the followers of a specified user
 /// 
 /// # Arguments
 /// 
 /// * `user_principal` - The principal ID of the user
+/// * `cursor` - `next_cursor` of the previous page, or None for the first page
+/// * `limit` - Page size, 50 by default and at most 100
 /// 
 /// # Returns
 /// 
-/// * `Vec<Principal>` - List of principal IDs that follow the user
+/// * `Result<Page<Principal>, String>` - A page of principal IDs that follow the user
 #[ic_cdk::query]
-pub fn get_followers(user_principal: Principal) -> Vec<Principal> {
-    // Query storage for all relationships where followed_principal matches the given user
-    // We use the key pattern to efficiently find relationships
-    let user_str = user_principal.to_string();
-    
-    FOLLOW_RELATIONSHIPS.with(|relationships| {
-        relationships
-            .borrow()
-            .iter()
-            .filter(|(key, _)| {
-                let parts: Vec<&str> = key.split(':').collect();
-                parts.len() == 2 && parts[1] == user_str
-            })
-            .map(|(key, _)| {
-                let parts: Vec<&str> = key.split(':').collect();
-                // Safe to unwrap as we checked the key format in filter
-                Principal::from_text(parts[0]).unwrap_or_else(|_| Principal::anonymous())
-            })
-            .collect()
-    })
+pub fn get_followers(
+    user_principal: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Principal>, String> {
+    // The followers index is keyed "followed:follower", so one user's followers are adjacent
+    FOLLOWERS.with(|followers| page_of_principals(&followers.borrow(), user_principal, cursor, limit))
 }
 
-/// Retrieves all users that a specified user is following
+/// Retrieves the users that a specified user is following
 /// 
 /// # Arguments
 /// 
 /// * `user_principal` - The principal ID of the user
+/// * `cursor` - `next_cursor` of the previous page, or None for the first page
+/// * `limit` - Page size, 50 by default and at most 100
 /// 
 /// # Returns
 /// 
-/// * `Vec<Principal>` - List of principal IDs that the user follows
+/// * `Result<Page<Principal>, String>` - A page of principal IDs that the user follows
 #[ic_cdk::query]
-pub fn get_following(user_principal: Principal) -> Vec<Principal> {
-    // Query storage for all relationships where follower_principal matches the given user
-    // We use the key pattern to efficiently find relationships
-    let user_str = user_principal.to_string();
-    
+pub fn get_following(
+    user_principal: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Principal>, String> {
+    // Relationships are keyed "follower:followed", so one user's follows are adjacent
     FOLLOW_RELATIONSHIPS.with(|relationships| {
+        page_of_principals(&relationships.borrow(), user_principal, cursor, limit)
+    })
+}
+
+/// Counts the followers of a user
+pub fn follower_count(user_principal: &Principal) -> u64 {
+    let prefix = format!("{}:", user_principal);
+    FOLLOWERS.with(|followers| {
+        followers
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .count() as u64
+    })
+}
+
+/// Builds the followers index for relationships stored before it existed
+pub fn backfill_followers_index() {
+    if FOLLOWERS.with(|followers| !followers.borrow().is_empty()) {
+        return;
+    }
+    let relationships: Vec<FollowRelationship> = FOLLOW_RELATIONSHIPS.with(|relationships| {
         relationships
             .borrow()
             .iter()
-            .filter(|(key, _)| {
-                let parts: Vec<&str> = key.split(':').collect();
-                parts.len() == 2 && parts[0] == user_str
-            })
-            .map(|(key, _)| {
-                let parts: Vec<&str> = key.split(':').collect();
-                // Safe to unwrap as we checked the key format in filter
-                Principal::from_text(parts[1]).unwrap_or_else(|_| Principal::anonymous())
-            })
+            .flat_map(|(_, list)| list.0)
             .collect()
-    })
+    });
+    FOLLOWERS.with(|followers| {
+        let mut followers = followers.borrow_mut();
+        for relationship in relationships {
+            followers.insert(
+                format!("{}:{}", relationship.followed_principal, relationship.follower_principal),
+                relationship.timestamp,
+            );
+        }
+    });
+}
+
+/// Pages through "user:other" keys of `map`, returning the other principals
+fn page_of_principals<V: Storable>(
+    map: &StableBTreeMap<String, V, Memory>,
+    user_principal: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Principal>, String> {
+    let prefix = format!("{}:", user_principal);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+    let entries = map
+        .range((start, Bound::Unbounded))
+        .take_while(|(key, _)| key.starts_with(&prefix))
+        .map(|(key, _)| {
+            let principal = Principal::from_text(&key[prefix.len()..]).unwrap_or_else(|_| Principal::anonymous());
+            (key, principal)
+        });
+    Ok(collect_page(entries, page_size(limit)))
 }
 
 /// Checks if one user is following another
@@ -169,7 +226,7 @@ pub fn get_following(user_principal: Principal) -> Vec<Principal> {
 #[ic_cdk::query]
 pub fn is_following(follower: Principal, followed: Principal) -> bool {
     // Create the composite key and check if it exists in storage
-    let relationship_key = format!("{}:{}", follower.to_string(), followed.to_string());
+    let relationship_key = format!("{}:{}", follower, followed);
     
     FOLLOW_RELATIONSHIPS.with(|relationships| {
         relationships.borrow().contains_key(&relationship_key)
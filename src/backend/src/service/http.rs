@@ -0,0 +1,81 @@
+// HTTP gateway interface for the canister
+// Requests arrive as queries through http_request; routes that change state
+// answer with upgrade = true so the gateway replays them to http_request_update.
+
+use candid::{CandidType, Deserialize};
+use ic_cdk::{query, update};
+use serde_bytes::ByteBuf;
+
+use crate::service::{captions, webhooks};
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+}
+
+impl HttpRequest {
+    /// URL path without the query string
+    pub fn path(&self) -> &str {
+        self.url.split('?').next().unwrap_or_default()
+    }
+
+    /// Looks up a header case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+    pub upgrade: Option<bool>,
+}
+
+impl HttpResponse {
+    pub fn new(status_code: u16, content_type: &str, body: Vec<u8>) -> Self {
+        HttpResponse {
+            status_code,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: ByteBuf::from(body),
+            upgrade: None,
+        }
+    }
+
+    pub fn text(status_code: u16, message: &str) -> Self {
+        Self::new(status_code, "text/plain", message.as_bytes().to_vec())
+    }
+
+    fn upgrade() -> Self {
+        HttpResponse {
+            status_code: 200,
+            headers: Vec::new(),
+            body: ByteBuf::new(),
+            upgrade: Some(true),
+        }
+    }
+}
+
+#[query]
+pub fn http_request(request: HttpRequest) -> HttpResponse {
+    match (request.method.as_str(), request.path()) {
+        ("POST", webhooks::LIVEPEER_WEBHOOK_PATH) => HttpResponse::upgrade(),
+        ("GET", path) if path.starts_with("/captions/") => captions::serve_caption(path),
+        _ => HttpResponse::text(404, "Not found"),
+    }
+}
+
+#[update]
+pub fn http_request_update(request: HttpRequest) -> HttpResponse {
+    match (request.method.as_str(), request.path()) {
+        ("POST", webhooks::LIVEPEER_WEBHOOK_PATH) => webhooks::handle_livepeer_webhook(&request),
+        _ => HttpResponse::text(404, "Not found"),
+    }
+}
@@ -1,4 +1,4 @@
-use candid::{CandidType, Deserialize, Principal};
+use candid::{CandidType, Deserialize};
 use ic_cdk::{api::{self, management_canister::http_request::{
     HttpResponse, TransformArgs, TransformContext, HttpHeader, HttpMethod, CanisterHttpRequestArgument,
 }}};
@@ -9,7 +9,7 @@ use std::cell::RefCell;
 
 // Global variable to store the JWT
 thread_local! {
-    static PINATA_JWT: RefCell<Option<String>> = RefCell::new(None);
+    static PINATA_JWT: RefCell<Option<String>> = const { RefCell::new(None) };
 }
 
 #[derive(CandidType, Deserialize, Debug)]
@@ -92,7 +92,7 @@ pub async fn proxy_ipfs_content(cid: String) -> IPFSProxyResponse {
             // Convert status to u32 for comparison
             let status_code = response.status.0.to_u32().unwrap_or(0);
             
-            if status_code >= 200 && status_code < 300 {
+            if (200..300).contains(&status_code) {
                 // Determine content type from response headers or default to binary
                 let content_type = response.headers.iter()
                     .find(|h| h.name.to_lowercase() == "content-type")
@@ -130,6 +130,7 @@ pub async fn proxy_ipfs_content(cid: String) -> IPFSProxyResponse {
 }
 
 /// Function to transform the IPFS response
+#[query]
 fn transform_ipfs_response(args: TransformArgs) -> HttpResponse {
     // Pass through the response
     args.response
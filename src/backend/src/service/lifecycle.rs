@@ -0,0 +1,45 @@
+use ic_cdk::{init, post_upgrade};
+
+use crate::{
+    scheduler,
+    service::{
+        autocomplete::backfill_autocomplete, comments::migrate_legacy_comments,
+        credits::backfill_credits_index, follows::backfill_followers_index,
+        playlists::backfill_playlist_index,
+        profile_search::{backfill_profile_index, backfill_profile_stats},
+        search::backfill_search_index, sounds::backfill_sound_ranking,
+        tag_pages::backfill_tag_aggregates, tags::backfill_tag_alias_targets,
+        tips::{backfill_tip_splits, migrate_legacy_tip_records}, video::backfill_uploader_index,
+        video_stats::{backfill_video_rankings, backfill_video_stats},
+        watch::migrate_legacy_watch_log,
+        webhooks::backfill_playback_index,
+    },
+};
+
+#[init]
+fn init() {
+    scheduler::start();
+}
+
+#[post_upgrade]
+fn post_upgrade() {
+    migrate_legacy_comments();
+    migrate_legacy_watch_log();
+    backfill_tip_splits();
+    migrate_legacy_tip_records();
+    backfill_search_index();
+    backfill_followers_index();
+    backfill_video_stats();
+    backfill_video_rankings();
+    backfill_autocomplete();
+    backfill_uploader_index();
+    backfill_profile_stats();
+    backfill_profile_index();
+    backfill_tag_aggregates();
+    backfill_tag_alias_targets();
+    backfill_playback_index();
+    backfill_playlist_index();
+    backfill_credits_index();
+    backfill_sound_ranking();
+    scheduler::start();
+}
@@ -1,9 +1,24 @@
 use ic_cdk::query;
+use std::ops::Bound;
 
-use crate::{user_profile::UserProfile, USER_PROFILES};
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    user_profile::UserProfile,
+    USER_PROFILES,
+};
 
+/// Lists profiles with their principals, in principal order
 #[query]
-pub fn list_profiles() -> Result<Vec<(String, UserProfile)>, String> {
-    let profiles = USER_PROFILES.with(|p| p.borrow().iter().collect::<Vec<_>>());
-    Ok(profiles)
+pub fn list_profiles(
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<(String, UserProfile)>, String> {
+    let start = start_after(cursor.as_deref(), "")?;
+    USER_PROFILES.with(|profiles| {
+        let profiles = profiles.borrow();
+        let entries = profiles
+            .range((start, Bound::Unbounded))
+            .map(|(principal, profile)| (principal.clone(), (principal, profile)));
+        Ok(collect_page(entries, page_size(limit)))
+    })
 }
@@ -8,3 +8,22 @@ pub mod comments;
 pub mod follows;
 pub mod search;
 pub mod ipfs_proxy;
+pub mod lifecycle;
+pub mod moderation;
+pub mod revisions;
+pub mod http;
+pub mod webhooks;
+pub mod captions;
+pub mod duplicates;
+pub mod playlists;
+pub mod remixes;
+pub mod sounds;
+pub mod channel;
+pub mod credits;
+pub mod clips;
+pub mod autocomplete;
+pub mod video_stats;
+pub mod profile_search;
+pub mod tags;
+pub mod tag_pages;
+pub mod trending;
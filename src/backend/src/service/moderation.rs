@@ -0,0 +1,57 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    MODERATORS,
+};
+
+/// Whether the principal may moderate content. Canister controllers always can.
+pub fn is_moderator(principal: &Principal) -> bool {
+    ic_cdk::api::is_controller(principal)
+        || MODERATORS.with(|moderators| moderators.borrow().contains_key(&principal.to_string()))
+}
+
+/// Grants the moderator role (controllers only)
+#[update]
+pub fn add_moderator(principal: Principal) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only controllers can manage moderators".to_string());
+    }
+
+    let timestamp = ic_cdk::api::time() / 1_000_000_000;
+    MODERATORS.with(|moderators| {
+        moderators.borrow_mut().insert(principal.to_string(), timestamp);
+    });
+    Ok(())
+}
+
+/// Revokes the moderator role (controllers only)
+#[update]
+pub fn remove_moderator(principal: Principal) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only controllers can manage moderators".to_string());
+    }
+
+    MODERATORS.with(|moderators| {
+        moderators
+            .borrow_mut()
+            .remove(&principal.to_string())
+            .map(|_| ())
+            .ok_or_else(|| "Principal is not a moderator".to_string())
+    })
+}
+
+/// Lists principals that were granted the moderator role
+#[query]
+pub fn list_moderators(cursor: Option<String>, limit: Option<u32>) -> Result<Page<Principal>, String> {
+    let start = start_after(cursor.as_deref(), "")?;
+    Ok(MODERATORS.with(|moderators| {
+        let moderators = moderators.borrow();
+        let entries = moderators
+            .range((start, Bound::Unbounded))
+            .filter_map(|(key, _)| Principal::from_text(&key).ok().map(|principal| (key, principal)));
+        collect_page(entries, page_size(limit))
+    }))
+}
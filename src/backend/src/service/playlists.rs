@@ -0,0 +1,615 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, page_size, start_after, start_index, Page},
+    playlist::{
+        validate_description, validate_title, Playlist, PlaylistPage, PlaylistSummary,
+        PlaylistVisibility, MAX_COLLABORATORS,
+    },
+    service::{clips::is_listed_with_source, video::video_is_live},
+    video_id::encode_base58,
+    PLAYLISTS, PLAYLISTS_BY_EDITOR, PLAYLIST_ENTRIES, PLAYLIST_ID_COUNTER, VIDEOS,
+};
+
+fn entry_key(video_id: &str, playlist_id: &str) -> String {
+    format!("{}:{}", video_id, playlist_id)
+}
+
+fn editor_key(principal: &Principal, playlist_id: &str) -> String {
+    format!("{}:{}", principal, playlist_id)
+}
+
+fn mint_playlist_id() -> String {
+    PLAYLIST_ID_COUNTER.with(|counter| {
+        let mut counter_cell = counter.borrow_mut();
+        let next_id = *counter_cell.get();
+        counter_cell
+            .set(next_id + 1)
+            .expect("Failed to persist playlist id counter");
+        format!("PL{}", encode_base58(&next_id.to_be_bytes()))
+    })
+}
+
+fn now() -> u64 {
+    ic_cdk::api::time() / 1_000_000_000
+}
+
+/// Loads a playlist, applies `edit` if `allowed` accepts the caller, and saves it
+fn edit_playlist<T>(
+    playlist_id: &str,
+    caller: Principal,
+    now: u64,
+    allowed: impl Fn(&Playlist, &Principal) -> bool,
+    edit: impl FnOnce(&mut Playlist) -> Result<T, String>,
+) -> Result<T, String> {
+    PLAYLISTS.with(|playlists| {
+        let mut playlists_map = playlists.borrow_mut();
+        let mut playlist = playlists_map
+            .get(&playlist_id.to_string())
+            .filter(|playlist| playlist.can_view(&caller))
+            .ok_or_else(|| "Playlist not found".to_string())?;
+
+        if !allowed(&playlist, &caller) {
+            return Err("You don't have permission to edit this playlist".to_string());
+        }
+
+        // Set first so the summary an edit returns has it; failed edits aren't saved
+        playlist.updated_at = now;
+        let result = edit(&mut playlist)?;
+        playlists_map.insert(playlist_id.to_string(), playlist);
+        Ok(result)
+    })
+}
+
+fn is_owner(playlist: &Playlist, principal: &Principal) -> bool {
+    playlist.owner_principal == *principal
+}
+
+/// Summary counting only the videos `get_playlist` shows
+fn summary_of(playlist: &Playlist) -> PlaylistSummary {
+    let mut summary = playlist.summary();
+    summary.video_count = VIDEOS.with(|videos| {
+        let videos_map = videos.borrow();
+        playlist
+            .video_ids
+            .iter()
+            .filter(|video_id| videos_map.get(*video_id).is_some_and(|metadata| is_listed_with_source(&metadata)))
+            .count() as u32
+    });
+    summary
+}
+
+/// Owner and collaborators of a playlist
+fn editors_of(playlist: &Playlist) -> impl Iterator<Item = &Principal> {
+    std::iter::once(&playlist.owner_principal).chain(playlist.collaborators.iter())
+}
+
+#[update]
+pub fn create_playlist(
+    title: String,
+    description: Option<String>,
+    visibility: PlaylistVisibility,
+) -> Result<PlaylistSummary, String> {
+    new_playlist(ic_cdk::caller(), now(), title, description, visibility)
+}
+
+fn new_playlist(
+    caller: Principal,
+    now: u64,
+    title: String,
+    description: Option<String>,
+    visibility: PlaylistVisibility,
+) -> Result<PlaylistSummary, String> {
+    validate_title(&title)?;
+    if let Some(description) = &description {
+        validate_description(description)?;
+    }
+
+    let playlist = Playlist {
+        playlist_id: mint_playlist_id(),
+        owner_principal: caller,
+        title,
+        description,
+        visibility,
+        collaborators: Vec::new(),
+        video_ids: Vec::new(),
+        created_at: now,
+        updated_at: now,
+        invited_collaborators: None,
+    };
+
+    PLAYLISTS.with(|playlists| {
+        playlists
+            .borrow_mut()
+            .insert(playlist.playlist_id.clone(), playlist.clone())
+    });
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        editors
+            .borrow_mut()
+            .insert(editor_key(&playlist.owner_principal, &playlist.playlist_id), ())
+    });
+    Ok(summary_of(&playlist))
+}
+
+/// Renames a playlist, changes its description or visibility (only by owner)
+#[update]
+pub fn update_playlist(
+    playlist_id: String,
+    title: Option<String>,
+    description: Option<String>,
+    visibility: Option<PlaylistVisibility>,
+) -> Result<PlaylistSummary, String> {
+    if let Some(title) = &title {
+        validate_title(title)?;
+    }
+    if let Some(description) = &description {
+        validate_description(description)?;
+    }
+
+    edit_playlist(&playlist_id, ic_cdk::caller(), now(), is_owner, |playlist| {
+        if let Some(title) = title {
+            playlist.title = title;
+        }
+        if let Some(description) = description {
+            // An empty description clears it
+            playlist.description = Some(description).filter(|d| !d.is_empty());
+        }
+        if let Some(visibility) = visibility {
+            playlist.visibility = visibility;
+        }
+        Ok(summary_of(playlist))
+    })
+}
+
+/// Deletes a playlist (only by owner). The videos themselves are not affected.
+#[update]
+pub fn delete_playlist(playlist_id: String) -> Result<(), String> {
+    let caller = ic_cdk::caller();
+
+    let playlist = PLAYLISTS.with(|playlists| {
+        let mut playlists_map = playlists.borrow_mut();
+        let playlist = playlists_map
+            .get(&playlist_id)
+            .filter(|playlist| playlist.can_view(&caller))
+            .ok_or_else(|| "Playlist not found".to_string())?;
+        if !is_owner(&playlist, &caller) {
+            return Err("Only the owner can delete the playlist".to_string());
+        }
+        playlists_map.remove(&playlist_id);
+        Ok(playlist)
+    })?;
+
+    PLAYLIST_ENTRIES.with(|entries| {
+        let mut entries_map = entries.borrow_mut();
+        for video_id in &playlist.video_ids {
+            entries_map.remove(&entry_key(video_id, &playlist_id));
+        }
+    });
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        let mut editors = editors.borrow_mut();
+        for editor in editors_of(&playlist) {
+            editors.remove(&editor_key(editor, &playlist_id));
+        }
+    });
+    Ok(())
+}
+
+/// Adds a video at `position`, or at the end (owner and collaborators)
+#[update]
+pub fn add_video_to_playlist(
+    playlist_id: String,
+    video_id: String,
+    position: Option<u32>,
+) -> Result<PlaylistSummary, String> {
+    add_video(&playlist_id, ic_cdk::caller(), now(), video_id, position)
+}
+
+fn add_video(
+    playlist_id: &str,
+    caller: Principal,
+    now: u64,
+    video_id: String,
+    position: Option<u32>,
+) -> Result<PlaylistSummary, String> {
+    if !video_is_live(&video_id) {
+        return Err("Video not found".to_string());
+    }
+
+    let summary = edit_playlist(playlist_id, caller, now, Playlist::can_edit, |playlist| {
+        playlist.add_video(video_id.clone(), position)?;
+        Ok(summary_of(playlist))
+    })?;
+
+    PLAYLIST_ENTRIES.with(|entries| {
+        entries
+            .borrow_mut()
+            .insert(entry_key(&video_id, playlist_id), now)
+    });
+    Ok(summary)
+}
+
+/// Removes a video from the playlist (owner and collaborators)
+#[update]
+pub fn remove_video_from_playlist(
+    playlist_id: String,
+    video_id: String,
+) -> Result<PlaylistSummary, String> {
+    remove_video(&playlist_id, ic_cdk::caller(), now(), &video_id)
+}
+
+fn remove_video(playlist_id: &str, caller: Principal, now: u64, video_id: &str) -> Result<PlaylistSummary, String> {
+    let summary = edit_playlist(playlist_id, caller, now, Playlist::can_edit, |playlist| {
+        if !playlist.remove_video(video_id) {
+            return Err("Video is not in the playlist".to_string());
+        }
+        Ok(summary_of(playlist))
+    })?;
+
+    PLAYLIST_ENTRIES.with(|entries| {
+        entries
+            .borrow_mut()
+            .remove(&entry_key(video_id, playlist_id))
+    });
+    Ok(summary)
+}
+
+/// Moves a video to a new zero-based position (owner and collaborators)
+#[update]
+pub fn move_video_in_playlist(
+    playlist_id: String,
+    video_id: String,
+    position: u32,
+) -> Result<PlaylistSummary, String> {
+    edit_playlist(&playlist_id, ic_cdk::caller(), now(), Playlist::can_edit, |playlist| {
+        playlist.move_video(&video_id, position)?;
+        Ok(summary_of(playlist))
+    })
+}
+
+/// Invites a principal to edit the playlist's videos (only by owner). They
+/// become a collaborator once they accept with `accept_playlist_invite`.
+#[update]
+pub fn add_playlist_collaborator(
+    playlist_id: String,
+    collaborator: Principal,
+) -> Result<PlaylistSummary, String> {
+    invite_collaborator(&playlist_id, ic_cdk::caller(), now(), collaborator)
+}
+
+fn invite_collaborator(
+    playlist_id: &str,
+    caller: Principal,
+    now: u64,
+    collaborator: Principal,
+) -> Result<PlaylistSummary, String> {
+    edit_playlist(playlist_id, caller, now, is_owner, |playlist| {
+        if collaborator == playlist.owner_principal || playlist.collaborators.contains(&collaborator) {
+            return Err("Principal can already edit the playlist".to_string());
+        }
+        if playlist.is_invited(&collaborator) {
+            return Err("Principal is already invited".to_string());
+        }
+        let invited = playlist.invited_collaborators.get_or_insert_with(Vec::new);
+        if playlist.collaborators.len() + invited.len() >= MAX_COLLABORATORS {
+            return Err(format!(
+                "A playlist can have at most {} collaborators",
+                MAX_COLLABORATORS
+            ));
+        }
+        invited.push(collaborator);
+        Ok(summary_of(playlist))
+    })
+}
+
+/// Accepts the caller's invitation to collaborate on a playlist
+#[update]
+pub fn accept_playlist_invite(playlist_id: String) -> Result<PlaylistSummary, String> {
+    accept_invite(&playlist_id, ic_cdk::caller(), now())
+}
+
+fn accept_invite(playlist_id: &str, caller: Principal, now: u64) -> Result<PlaylistSummary, String> {
+    let summary = edit_playlist(playlist_id, caller, now, Playlist::is_invited, |playlist| {
+        remove_invite(playlist, &caller);
+        playlist.collaborators.push(caller);
+        Ok(summary_of(playlist))
+    })?;
+
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        editors
+            .borrow_mut()
+            .insert(editor_key(&caller, playlist_id), ())
+    });
+    Ok(summary)
+}
+
+/// Drops a pending invitation, returning whether there was one
+fn remove_invite(playlist: &mut Playlist, principal: &Principal) -> bool {
+    let Some(invited) = playlist.invited_collaborators.as_mut() else {
+        return false;
+    };
+    let count_before = invited.len();
+    invited.retain(|p| p != principal);
+    let removed = invited.len() != count_before;
+    if invited.is_empty() {
+        playlist.invited_collaborators = None;
+    }
+    removed
+}
+
+/// Removes a collaborator or withdraws an invitation (by owner, or by the
+/// collaborator leaving or declining)
+#[update]
+pub fn remove_playlist_collaborator(
+    playlist_id: String,
+    collaborator: Principal,
+) -> Result<PlaylistSummary, String> {
+    let allowed = |playlist: &Playlist, caller: &Principal| {
+        is_owner(playlist, caller) || *caller == collaborator
+    };
+    let summary = edit_playlist(&playlist_id, ic_cdk::caller(), now(), allowed, |playlist| {
+        if remove_invite(playlist, &collaborator) {
+            return Ok(summary_of(playlist));
+        }
+        let count_before = playlist.collaborators.len();
+        playlist.collaborators.retain(|p| *p != collaborator);
+        if playlist.collaborators.len() == count_before {
+            return Err("Principal is not a collaborator".to_string());
+        }
+        Ok(summary_of(playlist))
+    })?;
+
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        editors
+            .borrow_mut()
+            .remove(&editor_key(&collaborator, &playlist_id))
+    });
+    Ok(summary)
+}
+
+/// Returns a page of a playlist's videos, in playlist order. Videos in the
+/// trash or still processing are skipped.
+#[query]
+pub fn get_playlist(
+    playlist_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<PlaylistPage, String> {
+    playlist_page(&playlist_id, ic_cdk::caller(), cursor, limit)
+}
+
+fn playlist_page(
+    playlist_id: &str,
+    caller: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<PlaylistPage, String> {
+    let playlist = PLAYLISTS
+        .with(|playlists| playlists.borrow().get(&playlist_id.to_string()))
+        .filter(|playlist| playlist.can_view(&caller))
+        .ok_or_else(|| "Playlist not found".to_string())?;
+
+    // The order lives in the playlist itself, so the cursor is a position in it
+    let start = start_index(cursor.as_deref())?;
+    let videos = VIDEOS.with(|videos| {
+        let videos_map = videos.borrow();
+        let entries = playlist
+            .video_ids
+            .iter()
+            .enumerate()
+            .skip(start)
+            .filter_map(|(position, video_id)| {
+                let metadata = videos_map.get(video_id)?;
+                is_listed_with_source(&metadata).then_some((position.to_string(), metadata))
+            });
+        collect_page(entries, page_size(limit))
+    });
+
+    Ok(PlaylistPage {
+        playlist: summary_of(&playlist),
+        videos,
+    })
+}
+
+/// Lists playlists the caller owns or collaborates on
+#[query]
+pub fn list_my_playlists(cursor: Option<String>, limit: Option<u32>) -> Result<Page<PlaylistSummary>, String> {
+    let caller = ic_cdk::caller();
+    playlists_of_editor(&caller, cursor, limit, |playlist| playlist.can_edit(&caller))
+}
+
+/// Lists a user's public playlists
+#[query]
+pub fn list_playlists_by_owner(
+    owner: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<PlaylistSummary>, String> {
+    playlists_of_editor(&owner, cursor, limit, |playlist| {
+        playlist.owner_principal == owner && playlist.visibility == PlaylistVisibility::Public
+    })
+}
+
+/// Pages through the playlists a principal owns or collaborates on, keeping
+/// those `keep` accepts
+fn playlists_of_editor(
+    principal: &Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    keep: impl Fn(&Playlist) -> bool,
+) -> Result<Page<PlaylistSummary>, String> {
+    let prefix = format!("{}:", principal);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        let editors = editors.borrow();
+        let entries = editors
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let playlist_id = key.strip_prefix(&prefix)?.to_string();
+                let playlist = PLAYLISTS.with(|playlists| playlists.borrow().get(&playlist_id))?;
+                keep(&playlist).then(|| (key, summary_of(&playlist)))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+/// Indexes playlists created before they were indexed by editor
+pub fn backfill_playlist_index() {
+    if PLAYLISTS_BY_EDITOR.with(|editors| !editors.borrow().is_empty()) {
+        return;
+    }
+    let playlists: Vec<Playlist> =
+        PLAYLISTS.with(|playlists| playlists.borrow().iter().map(|(_, playlist)| playlist).collect());
+    PLAYLISTS_BY_EDITOR.with(|editors| {
+        let mut editors = editors.borrow_mut();
+        for playlist in &playlists {
+            for editor in editors_of(playlist) {
+                editors.insert(editor_key(editor, &playlist.playlist_id), ());
+            }
+        }
+    });
+}
+
+/// Removes a purged video from up to `limit` of the playlists that contain
+/// it. Returns how many playlists it was removed from.
+pub fn remove_video_from_playlists(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    let keys: Vec<String> = PLAYLIST_ENTRIES.with(|entries| {
+        entries
+            .borrow()
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .take(limit)
+            .collect()
+    });
+
+    let removed = keys.len();
+    for key in keys {
+        let playlist_id = &key[prefix.len()..];
+        PLAYLISTS.with(|playlists| {
+            let mut playlists_map = playlists.borrow_mut();
+            if let Some(mut playlist) = playlists_map.get(&playlist_id.to_string()) {
+                playlist.remove_video(video_id);
+                playlists_map.insert(playlist_id.to_string(), playlist);
+            }
+        });
+        PLAYLIST_ENTRIES.with(|entries| entries.borrow_mut().remove(&key));
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_metadata::{test_video, VideoMetadata};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    fn upload(video_id: &str) -> VideoMetadata {
+        let metadata = test_video(video_id, principal(9), &[], NOW);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        metadata
+    }
+
+    fn private_playlist(owner: Principal) -> String {
+        new_playlist(owner, NOW, "Mix".to_string(), None, PlaylistVisibility::Private)
+            .unwrap()
+            .playlist_id
+    }
+
+    fn video_ids(playlist_id: &str, caller: Principal) -> Vec<String> {
+        playlist_page(playlist_id, caller, None, None)
+            .unwrap()
+            .videos
+            .items
+            .into_iter()
+            .map(|metadata| metadata.video_id)
+            .collect()
+    }
+
+    fn editor_playlists(editor: Principal) -> Vec<String> {
+        playlists_of_editor(&editor, None, None, |_| true)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|summary| summary.playlist_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_invitee_views_but_cannot_edit() {
+        let (owner, invitee) = (principal(1), principal(2));
+        upload("video1");
+        let playlist_id = private_playlist(owner);
+        add_video(&playlist_id, owner, NOW, "video1".to_string(), None).unwrap();
+
+        assert!(playlist_page(&playlist_id, invitee, None, None).is_err());
+        invite_collaborator(&playlist_id, owner, NOW, invitee).unwrap();
+        assert_eq!(video_ids(&playlist_id, invitee), vec!["video1"]);
+        assert!(remove_video(&playlist_id, invitee, NOW, "video1").is_err());
+        assert!(editor_playlists(invitee).is_empty());
+    }
+
+    #[test]
+    fn test_accepted_editor_edits_videos() {
+        let (owner, editor) = (principal(1), principal(2));
+        upload("video1");
+        upload("video2");
+        let playlist_id = private_playlist(owner);
+        invite_collaborator(&playlist_id, owner, NOW, editor).unwrap();
+
+        let summary = accept_invite(&playlist_id, editor, NOW + 1).unwrap();
+        assert_eq!(summary.collaborators, vec![editor]);
+        assert!(summary.invited_collaborators.is_empty());
+        assert_eq!(editor_playlists(editor), vec![playlist_id.clone()]);
+        assert!(accept_invite(&playlist_id, editor, NOW + 1).is_err());
+
+        add_video(&playlist_id, editor, NOW + 2, "video1".to_string(), None).unwrap();
+        add_video(&playlist_id, editor, NOW + 2, "video2".to_string(), Some(0)).unwrap();
+        assert_eq!(video_ids(&playlist_id, owner), vec!["video2", "video1"]);
+        let summary = remove_video(&playlist_id, editor, NOW + 3, "video2").unwrap();
+        assert_eq!(summary.video_count, 1);
+        assert_eq!(summary.updated_at, NOW + 3);
+    }
+
+    #[test]
+    fn test_only_the_owner_invites() {
+        let (owner, editor, stranger) = (principal(1), principal(2), principal(3));
+        let playlist_id = private_playlist(owner);
+        invite_collaborator(&playlist_id, owner, NOW, editor).unwrap();
+        accept_invite(&playlist_id, editor, NOW).unwrap();
+
+        assert!(invite_collaborator(&playlist_id, editor, NOW, stranger).is_err());
+        assert!(invite_collaborator(&playlist_id, stranger, NOW, stranger).is_err());
+        assert!(invite_collaborator(&playlist_id, owner, NOW, editor).is_err());
+        assert!(accept_invite(&playlist_id, stranger, NOW).is_err());
+    }
+
+    #[test]
+    fn test_purged_video_leaves_every_playlist() {
+        let owner = principal(1);
+        upload("video1");
+        upload("video2");
+        let first = private_playlist(owner);
+        let second = private_playlist(owner);
+        for playlist_id in [&first, &second] {
+            add_video(playlist_id, owner, NOW, "video1".to_string(), None).unwrap();
+            add_video(playlist_id, owner, NOW, "video2".to_string(), None).unwrap();
+        }
+
+        assert_eq!(remove_video_from_playlists("video1", usize::MAX), 2);
+        for playlist_id in [&first, &second] {
+            let playlist = PLAYLISTS.with(|playlists| playlists.borrow().get(playlist_id)).unwrap();
+            assert_eq!(playlist.video_ids, vec!["video2"]);
+        }
+        assert_eq!(PLAYLIST_ENTRIES.with(|entries| entries.borrow().len()), 2);
+    }
+}
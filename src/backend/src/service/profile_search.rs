@@ -0,0 +1,364 @@
+use candid::Principal;
+use ic_cdk::query;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_scanned_page, page_size, start_after, Page},
+    search_index::query_terms,
+    user_profile::{ProfileSearchResult, ProfileStats, UserProfile},
+    video_metadata::VideoMetadata,
+    FOLLOWERS, PROFILE_RANKS, PROFILE_STATS, PROFILE_TERMS, USER_PROFILES, VIDEOS,
+};
+
+/// Profiles sharing a mentioned name that are looked at to resolve a mention
+const MAX_MENTION_CANDIDATES: usize = 1000;
+
+/// Longest term prefix profiles are ranked under. Longer query terms are
+/// looked up by this prefix and checked against the profile's terms.
+const MAX_RANKED_PREFIX_CHARS: usize = 16;
+
+/// Ranked entries one search call reads at most
+const MAX_SCANNED_RANKS: usize = 2000;
+
+/// Finds profiles whose name words or address start with every word of the
+/// query, ignoring case. Most followed profiles come first, then by name.
+///
+/// Matches are read in rank order from the profiles ranked under the longest
+/// query term, and checked against the other terms.
+#[query]
+pub fn search_profiles(
+    query: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<ProfileSearchResult>, String> {
+    let terms = query_terms(&query);
+    let Some(lead) = terms.iter().max_by_key(|term| term.chars().count()) else {
+        return Ok(Page { items: Vec::new(), next_cursor: None });
+    };
+    let lead_prefix: String = lead.chars().take(MAX_RANKED_PREFIX_CHARS).collect();
+    let check_terms = terms.len() > 1 || lead_prefix.len() < lead.len();
+    let prefix = format!("{}\0", lead_prefix);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    PROFILE_RANKS.with(|ranks| {
+        let ranks = ranks.borrow();
+        let entries = ranks
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| {
+                let result = ranked_result(&key, &terms, check_terms);
+                (key, result)
+            });
+        Ok(collect_scanned_page(entries, page_size(limit), MAX_SCANNED_RANKS))
+    })
+}
+
+/// Search result for an entry of PROFILE_RANKS, if the profile matches every
+/// query term. The terms are only checked when the entry's prefix may not
+/// cover them.
+fn ranked_result(key: &str, terms: &[String], check_terms: bool) -> Option<ProfileSearchResult> {
+    let (_, principal_text) = key.rsplit_once('\0')?;
+    let profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&principal_text.to_string()))?;
+    if check_terms {
+        let profile_terms = profile.search_terms();
+        if !terms.iter().all(|term| profile_terms.iter().any(|word| word.starts_with(term))) {
+            return None;
+        }
+    }
+    let principal = Principal::from_text(principal_text).ok()?;
+    let stats = profile_stats(&principal);
+    Some(ProfileSearchResult {
+        principal,
+        profile,
+        follower_count: stats.follower_count,
+        video_count: stats.video_count,
+    })
+}
+
+/// Prefixes of a profile's terms, up to MAX_RANKED_PREFIX_CHARS long, that
+/// it is ranked under
+fn ranked_prefixes(profile: &UserProfile) -> BTreeSet<String> {
+    profile
+        .search_terms()
+        .iter()
+        .flat_map(|term| {
+            term.char_indices()
+                .take(MAX_RANKED_PREFIX_CHARS)
+                .map(|(index, c)| term[..index + c.len_utf8()].to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Key ranking a profile under a prefix: most followers first, then by name
+fn rank_key(prefix: &str, follower_count: u64, profile: &UserProfile, principal: &Principal) -> String {
+    format!(
+        "{}\0{:020}\0{}\0{}",
+        prefix,
+        u64::MAX - follower_count,
+        profile.name.to_lowercase(),
+        principal
+    )
+}
+
+/// Moves a profile's rank entries from how it was ranked before to how it is
+/// ranked now, each given as the profile and its follower count
+fn relink_profile_ranks(
+    principal: &Principal,
+    before: Option<(&UserProfile, u64)>,
+    after: Option<(&UserProfile, u64)>,
+) {
+    PROFILE_RANKS.with(|ranks| {
+        let mut ranks = ranks.borrow_mut();
+        if let Some((profile, follower_count)) = before {
+            for prefix in ranked_prefixes(profile) {
+                ranks.remove(&rank_key(&prefix, follower_count, profile, principal));
+            }
+        }
+        if let Some((profile, follower_count)) = after {
+            for prefix in ranked_prefixes(profile) {
+                ranks.insert(rank_key(&prefix, follower_count, profile, principal), ());
+            }
+        }
+    });
+}
+
+/// Follower and video counts for a creator, zero if it has none yet
+pub fn profile_stats(principal: &Principal) -> ProfileStats {
+    PROFILE_STATS
+        .with(|stats| stats.borrow().get(&principal.to_string()))
+        .unwrap_or_default()
+}
+
+fn update_profile_stats(principal: &Principal, update: impl FnOnce(&mut ProfileStats)) {
+    let mut stats = profile_stats(principal);
+    update(&mut stats);
+    PROFILE_STATS.with(|all| all.borrow_mut().insert(principal.to_string(), stats));
+}
+
+/// Counts a follow or unfollow towards a creator's followers and re-ranks
+/// their profile
+pub fn adjust_profile_followers(principal: &Principal, followed: bool) {
+    let before = profile_stats(principal).follower_count;
+    let after = if followed { before + 1 } else { before.saturating_sub(1) };
+    update_profile_stats(principal, |stats| stats.follower_count = after);
+
+    if let Some(profile) = USER_PROFILES.with(|profiles| profiles.borrow().get(&principal.to_string())) {
+        relink_profile_ranks(principal, Some((&profile, before)), Some((&profile, after)));
+    }
+}
+
+/// Keeps a creator's video count current as a video is uploaded, trashed,
+/// restored or purged. Videos in the trash don't count.
+pub fn relink_profile_video_count(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>) {
+    let before = before.filter(|metadata| !metadata.is_deleted());
+    let after = after.filter(|metadata| !metadata.is_deleted());
+    match (before, after) {
+        (None, Some(metadata)) => update_profile_stats(&metadata.uploader_principal, |stats| stats.video_count += 1),
+        (Some(metadata), None) => update_profile_stats(&metadata.uploader_principal, |stats| {
+            stats.video_count = stats.video_count.saturating_sub(1)
+        }),
+        _ => {}
+    }
+}
+
+/// Counts followers and videos for creators from before the counts were kept
+pub fn backfill_profile_stats() {
+    if PROFILE_STATS.with(|stats| !stats.borrow().is_empty()) {
+        return;
+    }
+    let mut counts: BTreeMap<String, ProfileStats> = BTreeMap::new();
+    FOLLOWERS.with(|followers| {
+        for (key, _) in followers.borrow().iter() {
+            if let Some((followed, _)) = key.split_once(':') {
+                counts.entry(followed.to_string()).or_default().follower_count += 1;
+            }
+        }
+    });
+    VIDEOS.with(|videos| {
+        for (_, metadata) in videos.borrow().iter().filter(|(_, metadata)| !metadata.is_deleted()) {
+            counts.entry(metadata.uploader_principal.to_string()).or_default().video_count += 1;
+        }
+    });
+    PROFILE_STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        for (principal, counts) in counts {
+            stats.insert(principal, counts);
+        }
+    });
+}
+
+/// Profile a `@mention` refers to: the one with that address, or else the
+/// only profile with that one-word name
+pub fn resolve_mention(mention: &str) -> Option<Principal> {
+    let prefix = format!("{}:", mention);
+    let candidates: Vec<(Principal, UserProfile)> = PROFILE_TERMS.with(|terms| {
+        terms
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(MAX_MENTION_CANDIDATES)
+            .filter_map(|(key, _)| {
+                let principal_text = key.strip_prefix(&prefix)?.to_string();
+                let profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&principal_text))?;
+                Some((Principal::from_text(&principal_text).ok()?, profile))
+            })
+            .collect()
+    });
+
+    if let Some((principal, _)) = candidates
+        .iter()
+        .find(|(_, profile)| profile.evm_address.eq_ignore_ascii_case(mention))
+    {
+        return Some(*principal);
+    }
+    let mut named = candidates
+        .into_iter()
+        .filter(|(_, profile)| profile.name.trim().to_lowercase() == mention);
+    match (named.next(), named.next()) {
+        (Some((principal, _)), None) => Some(principal),
+        _ => None,
+    }
+}
+
+/// Replaces the terms and ranks a profile is indexed under after it was saved
+pub fn reindex_profile(principal: &Principal, before: Option<&UserProfile>, after: &UserProfile) {
+    PROFILE_TERMS.with(|terms| {
+        let mut terms = terms.borrow_mut();
+        for term in before.map(UserProfile::search_terms).unwrap_or_default() {
+            terms.remove(&format!("{}:{}", term, principal));
+        }
+        for term in after.search_terms() {
+            terms.insert(format!("{}:{}", term, principal), ());
+        }
+    });
+    let follower_count = profile_stats(principal).follower_count;
+    relink_profile_ranks(principal, before.map(|profile| (profile, follower_count)), Some((after, follower_count)));
+}
+
+/// Indexes profiles saved before profile search existed. Runs after the
+/// profile stats are counted so profiles are ranked by their followers.
+pub fn backfill_profile_index() {
+    if PROFILE_TERMS.with(|terms| !terms.borrow().is_empty()) {
+        return;
+    }
+    let profiles: Vec<(String, UserProfile)> =
+        USER_PROFILES.with(|profiles| profiles.borrow().iter().collect());
+    for (principal, profile) in &profiles {
+        if let Ok(principal) = Principal::from_text(principal) {
+            reindex_profile(&principal, None, profile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    fn save(id: u8, name: &str, evm_address: &str) {
+        let principal = principal(id);
+        let profile = UserProfile {
+            evm_address: evm_address.to_string(),
+            name: name.to_string(),
+            avatar_url: String::new(),
+        };
+        let before = USER_PROFILES.with(|profiles| profiles.borrow_mut().insert(principal.to_string(), profile.clone()));
+        reindex_profile(&principal, before.as_ref(), &profile);
+    }
+
+    fn names(query: &str) -> Vec<String> {
+        search_profiles(query.to_string(), None, None)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|result| result.profile.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        save(1, "Alice Smith", "0xABC1");
+        save(2, "Bob", "0xDEF2");
+
+        assert_eq!(names("ali"), vec!["Alice Smith"]);
+        assert_eq!(names("SMI"), vec!["Alice Smith"]);
+        assert_eq!(names("0xab"), vec!["Alice Smith"]);
+        // Terms match from the start of a word only
+        assert!(names("lice").is_empty());
+        assert!(names("").is_empty());
+    }
+
+    #[test]
+    fn test_every_term_must_match() {
+        save(1, "Alice Smith", "0x1");
+        save(2, "Alice Jones", "0x2");
+        save(3, "Sam Smith", "0x3");
+
+        assert_eq!(names("alice smi"), vec!["Alice Smith"]);
+        assert_eq!(names("smith"), vec!["Alice Smith", "Sam Smith"]);
+        assert!(names("alice sam").is_empty());
+    }
+
+    #[test]
+    fn test_ranked_by_followers_then_name() {
+        save(1, "Dana A", "0x1");
+        save(2, "Dana B", "0x2");
+        save(3, "Dana C", "0x3");
+        adjust_profile_followers(&principal(3), true);
+        adjust_profile_followers(&principal(3), true);
+        adjust_profile_followers(&principal(2), true);
+        let mut video = crate::video_metadata::test_video("video1", principal(2), &[], 0);
+        relink_profile_video_count(None, Some(&video));
+
+        assert_eq!(names("dana"), vec!["Dana C", "Dana B", "Dana A"]);
+
+        let first = search_profiles("dana".to_string(), None, Some(2)).unwrap();
+        assert_eq!(first.items[1].follower_count, 1);
+        assert_eq!(first.items[1].video_count, 1);
+        let rest = search_profiles("dana".to_string(), first.next_cursor, Some(2)).unwrap();
+        assert_eq!(rest.items.len(), 1);
+        assert_eq!(rest.items[0].profile.name, "Dana A");
+        assert_eq!(rest.next_cursor, None);
+
+        // Trashed videos don't count
+        let before = video.clone();
+        video.deleted_at = Some(1);
+        relink_profile_video_count(Some(&before), Some(&video));
+        assert_eq!(profile_stats(&principal(2)).video_count, 0);
+    }
+
+    #[test]
+    fn test_rename_reindexes() {
+        save(1, "Alice", "0x1");
+        save(1, "Beth", "0x1");
+
+        assert!(names("alice").is_empty());
+        assert_eq!(names("beth"), vec!["Beth"]);
+        assert_eq!(PROFILE_TERMS.with(|terms| terms.borrow().len()), 2);
+    }
+
+    #[test]
+    fn test_ranking_covers_every_match() {
+        for id in 1..=60 {
+            save(id, &format!("Ann {}", id), &format!("0x{}", id));
+        }
+        save(200, "Annie Zulu", "0xff");
+        adjust_profile_followers(&principal(200), true);
+
+        // The most followed match sorts last by name and principal
+        let first = search_profiles("ann".to_string(), None, Some(1)).unwrap();
+        assert_eq!(first.items[0].profile.name, "Annie Zulu");
+        assert!(first.next_cursor.is_some());
+        assert_eq!(names("zul ann"), vec!["Annie Zulu"]);
+
+        adjust_profile_followers(&principal(200), false);
+        adjust_profile_followers(&principal(7), true);
+        let first = search_profiles("ann".to_string(), None, Some(1)).unwrap();
+        assert_eq!(first.items[0].profile.name, "Ann 7");
+    }
+}
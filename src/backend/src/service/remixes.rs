@@ -0,0 +1,202 @@
+use ic_cdk::query;
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, collect_scanned_page, page_size, start_after, Page},
+    remix::{ancestry, RemixSource, MAX_ANCESTRY_DEPTH},
+    service::clips::is_listed_with_source,
+    video_metadata::VideoMetadata,
+    REMIX_LINKS, VIDEOS,
+};
+
+/// Remixes sort oldest first under their parent
+fn link_key(parent_video_id: &str, timestamp: u64, video_id: &str) -> String {
+    format!("{}:{:020}:{}", parent_video_id, timestamp, video_id)
+}
+
+/// Checks that a new video may be created as a remix of the given parent
+pub fn validate_remix_source(source: &RemixSource) -> Result<(), String> {
+    let parent = VIDEOS
+        .with(|videos| videos.borrow().get(&source.parent_video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Parent video not found".to_string())?;
+
+    if !parent.remixes_allowed() {
+        return Err("The creator of the parent video doesn't allow remixes".to_string());
+    }
+    Ok(())
+}
+
+/// Indexes a new remix under its parent
+pub fn record_remix(metadata: &VideoMetadata) {
+    let Some(source) = &metadata.remix_of else {
+        return;
+    };
+    REMIX_LINKS.with(|links| {
+        links.borrow_mut().insert(
+            link_key(&source.parent_video_id, metadata.timestamp, &metadata.video_id),
+            metadata.timestamp,
+        )
+    });
+}
+
+/// Drops a purged video's links to its parent and its remixes. The remixes
+/// keep their `remix_of`, their ancestry simply ends at the purged video.
+pub fn remove_remix_links(metadata: &VideoMetadata) {
+    let prefix = format!("{}:", metadata.video_id);
+    REMIX_LINKS.with(|links| {
+        let mut links_map = links.borrow_mut();
+        let keys: Vec<String> = links_map
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .collect();
+        for key in keys {
+            links_map.remove(&key);
+        }
+        if let Some(source) = &metadata.remix_of {
+            links_map.remove(&link_key(&source.parent_video_id, metadata.timestamp, &metadata.video_id));
+        }
+    });
+}
+
+/// Lists the direct remixes of a video, oldest first
+#[query]
+pub fn get_video_remixes(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    REMIX_LINKS.with(|links| {
+        let links = links.borrow();
+        let entries = links
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let (_, remix_id) = key.strip_prefix(&prefix)?.split_once(':')?;
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&remix_id.to_string()))?;
+                is_listed_with_source(&metadata).then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+/// Returns the chain of videos a remix derives from, nearest parent first.
+/// Trashed ancestors are left out but the walk continues past them. A page
+/// walks at most MAX_ANCESTRY_DEPTH parents and may come back short, with a
+/// cursor to walk on from.
+#[query]
+pub fn get_video_ancestry(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let video = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Video not found".to_string())?;
+
+    // Cursors are "video_id:ancestor_id"; the next page walks on from that ancestor
+    let prefix = format!("{}:", video_id);
+    let from = match start_after(cursor.as_deref(), &prefix)? {
+        Bound::Excluded(key) => VIDEOS
+            .with(|videos| videos.borrow().get(&key[prefix.len()..].to_string()))
+            .ok_or_else(|| "Invalid cursor".to_string())?,
+        _ => video,
+    };
+
+    let chain = ancestry(&from, |id| VIDEOS.with(|videos| videos.borrow().get(&id.to_string()))).map(|metadata| {
+        let key = format!("{}{}", prefix, metadata.video_id);
+        (key, (!metadata.is_deleted()).then_some(metadata))
+    });
+    Ok(collect_scanned_page(chain, page_size(limit), MAX_ANCESTRY_DEPTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{remix::RemixType, video_metadata::test_video};
+    use candid::Principal;
+
+    /// Stores a video, remixing `parent` if given, and links it like `create_video_metadata`
+    fn upload(video_id: &str, parent: Option<&str>, timestamp: u64) -> VideoMetadata {
+        let mut metadata = test_video(video_id, Principal::from_slice(&[1]), &[], timestamp);
+        metadata.remix_of = parent.map(|parent| RemixSource {
+            parent_video_id: parent.to_string(),
+            remix_type: RemixType::Stitch,
+        });
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        record_remix(&metadata);
+        metadata
+    }
+
+    fn ids(page: &Page<VideoMetadata>) -> Vec<&str> {
+        page.items.iter().map(|metadata| metadata.video_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_remixes_need_permission() {
+        let mut original = upload("original", None, 1);
+        let source = RemixSource {
+            parent_video_id: "original".to_string(),
+            remix_type: RemixType::Duet,
+        };
+        assert!(validate_remix_source(&source).is_ok());
+
+        original.allow_remixes = Some(false);
+        VIDEOS.with(|videos| videos.borrow_mut().insert("original".to_string(), original));
+        assert!(validate_remix_source(&source).is_err());
+    }
+
+    #[test]
+    fn test_ancestry_skips_trashed_ancestors() {
+        upload("original", None, 1);
+        let mut trashed = upload("remix", Some("original"), 2);
+        upload("latest", Some("remix"), 3);
+        trashed.deleted_at = Some(4);
+        VIDEOS.with(|videos| videos.borrow_mut().insert("remix".to_string(), trashed));
+
+        let chain = get_video_ancestry("latest".to_string(), None, None).unwrap();
+        assert_eq!(ids(&chain), vec!["original"]);
+        assert_eq!(chain.next_cursor, None);
+        assert!(get_video_ancestry("remix".to_string(), None, None).is_err());
+    }
+
+    #[test]
+    fn test_ancestry_pages_past_the_walk_limit() {
+        upload("v0", None, 0);
+        for i in 1..=MAX_ANCESTRY_DEPTH + 10 {
+            upload(&format!("v{}", i), Some(&format!("v{}", i - 1)), i as u64);
+        }
+        let latest = format!("v{}", MAX_ANCESTRY_DEPTH + 10);
+
+        let first = get_video_ancestry(latest.clone(), None, Some(100)).unwrap();
+        assert_eq!(first.items.len(), MAX_ANCESTRY_DEPTH);
+        let rest = get_video_ancestry(latest, first.next_cursor, Some(100)).unwrap();
+        assert_eq!(rest.items.len(), 10);
+        assert_eq!(rest.items.last().unwrap().video_id, "v0");
+        assert_eq!(rest.next_cursor, None);
+    }
+
+    #[test]
+    fn test_purge_drops_remix_links() {
+        upload("original", None, 1);
+        let remix = upload("remix", Some("original"), 2);
+        upload("remix-of-remix", Some("remix"), 3);
+        upload("other", Some("original"), 4);
+        let first = get_video_remixes("original".to_string(), None, Some(1)).unwrap();
+        assert_eq!(ids(&first), vec!["remix"]);
+        let rest = get_video_remixes("original".to_string(), first.next_cursor, Some(1)).unwrap();
+        assert_eq!(ids(&rest), vec!["other"]);
+
+        remove_remix_links(&remix);
+        assert_eq!(ids(&get_video_remixes("original".to_string(), None, None).unwrap()), vec!["other"]);
+        assert!(get_video_remixes("remix".to_string(), None, None).unwrap().items.is_empty());
+        // The remix of the purged video keeps its remix_of and its ancestry ends there
+        VIDEOS.with(|videos| videos.borrow_mut().remove(&"remix".to_string()));
+        assert!(get_video_ancestry("remix-of-remix".to_string(), None, None).unwrap().items.is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    service::{moderation::is_moderator, video::save_video_edit},
+    video_metadata::{VideoDetails, VideoMetadata},
+    video_revision::{MetadataChange, VideoRevision, VideoRevisionList},
+    VIDEOS, VIDEO_REVISIONS,
+};
+
+/// Oldest revisions are dropped once a video has this many
+const MAX_REVISIONS_PER_VIDEO: u32 = 100;
+
+/// Key of a revision in VIDEO_REVISIONS
+fn revision_key(video_id: &str, revision: u32) -> String {
+    format!("{}:{:010}", video_id, revision)
+}
+
+/// A video's retained revisions, oldest first
+fn revisions_of(video_id: &str) -> VideoRevisionList {
+    let prefix = format!("{}:", video_id);
+    VIDEO_REVISIONS.with(|revisions| {
+        VideoRevisionList(
+            revisions
+                .borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .map(|(_, revision)| revision)
+                .collect(),
+        )
+    })
+}
+
+/// Appends a revision to a video's edit history and returns it
+pub fn record_revision(
+    video_id: &str,
+    editor_principal: Principal,
+    timestamp: u64,
+    changes: Vec<MetadataChange>,
+    reverted_to: Option<u32>,
+) -> VideoRevision {
+    let prefix = format!("{}:", video_id);
+    VIDEO_REVISIONS.with(|revisions| {
+        let mut revisions_map = revisions.borrow_mut();
+        let next_revision = revisions_map
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .last()
+            .map_or(1, |(_, revision)| revision.revision + 1);
+
+        let revision = VideoRevision {
+            revision: next_revision,
+            editor_principal,
+            timestamp,
+            changes,
+            reverted_to,
+        };
+        revisions_map.insert(revision_key(video_id, next_revision), revision.clone());
+        if next_revision > MAX_REVISIONS_PER_VIDEO {
+            revisions_map.remove(&revision_key(video_id, next_revision - MAX_REVISIONS_PER_VIDEO));
+        }
+        revision
+    })
+}
+
+/// Removes up to `limit` revisions of a video's edit history. Returns how
+/// many were removed.
+pub fn remove_revisions_for_video(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    VIDEO_REVISIONS.with(|revisions| {
+        let mut revisions = revisions.borrow_mut();
+        let keys: Vec<String> = revisions
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(key, _)| key)
+            .collect();
+        for key in &keys {
+            revisions.remove(key);
+        }
+        keys.len()
+    })
+}
+
+/// Returns the edit history of a video, oldest first. While the video is in
+/// the trash only its uploader and moderators can see it.
+#[query]
+pub fn list_video_revisions(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoRevision>, String> {
+    let caller = ic_cdk::caller();
+    let metadata = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .ok_or_else(|| "Video not found".to_string())?;
+    if metadata.is_deleted() && metadata.uploader_principal != caller && !is_moderator(&caller) {
+        return Err("Video not found".to_string());
+    }
+
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+    Ok(VIDEO_REVISIONS.with(|revisions| {
+        let revisions = revisions.borrow();
+        let entries = revisions
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix));
+        collect_page(entries, page_size(limit))
+    }))
+}
+
+/// Restores a video's metadata to how it was right after `revision` (0 restores
+/// the metadata as created). Only the uploader or a moderator can revert. The
+/// restored metadata is validated like an update, and the revert is itself
+/// recorded as a new revision.
+#[update]
+pub fn revert_video_metadata(video_id: String, revision: u32) -> Result<VideoMetadata, String> {
+    let caller = ic_cdk::caller();
+    let timestamp = ic_cdk::api::time() / 1_000_000_000;
+
+    let current = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Video not found".to_string())?;
+
+    if current.uploader_principal != caller && !is_moderator(&caller) {
+        return Err("Only the uploader or a moderator can revert video metadata".to_string());
+    }
+
+    let mut reverted = revisions_of(&video_id).metadata_at(&current, revision)?;
+    reverted.apply_details(VideoDetails::from(&reverted))?;
+
+    save_video_edit(&current, reverted, caller, timestamp, Some(revision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description_change(text: &str) -> Vec<MetadataChange> {
+        vec![MetadataChange::Description {
+            old: Some(text.repeat(5000)),
+            new: Some(text.repeat(4999)),
+        }]
+    }
+
+    #[test]
+    fn test_history_drops_oldest_revisions() {
+        let editor = Principal::anonymous();
+        for timestamp in 0..MAX_REVISIONS_PER_VIDEO as u64 + 3 {
+            record_revision("video1", editor, timestamp, vec![], None);
+        }
+        record_revision("video2", editor, 0, vec![], None);
+
+        let history = revisions_of("video1").0;
+        assert_eq!(history.len(), MAX_REVISIONS_PER_VIDEO as usize);
+        assert_eq!(history.first().map(|r| r.revision), Some(4));
+        assert_eq!(history.last().map(|r| r.revision), Some(MAX_REVISIONS_PER_VIDEO + 3));
+
+        remove_revisions_for_video("video1", usize::MAX);
+        assert!(revisions_of("video1").0.is_empty());
+        assert_eq!(revisions_of("video2").0.len(), 1);
+    }
+
+    #[test]
+    fn test_history_holds_large_edits() {
+        // Full-length multibyte descriptions, old and new value in every revision
+        for timestamp in 0..30 {
+            record_revision("video1", Principal::anonymous(), timestamp, description_change("é"), None);
+        }
+        let history = revisions_of("video1");
+        assert_eq!(history.0.len(), 30);
+        assert_eq!(history.next_revision(), 31);
+    }
+}
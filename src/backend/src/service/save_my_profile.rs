@@ -3,6 +3,7 @@ use serde_bytes::ByteBuf;
 
 use crate::{
     declarations::ic_siwe_provider::{ic_siwe_provider, GetAddressResponse},
+    service::{autocomplete::relink_profile_suggestion, profile_search::reindex_profile},
     user_profile::UserProfile,
     USER_PROFILES,
 };
@@ -21,10 +22,12 @@ async fn save_my_profile(name: String, avatar_url: String) -> Result<UserProfile
         avatar_url,
     };
 
-    USER_PROFILES.with(|p| {
+    let previous = USER_PROFILES.with(|p| {
         let mut profiles = p.borrow_mut();
-        profiles.insert(ic_cdk::caller().to_string(), profile.clone());
+        profiles.insert(ic_cdk::caller().to_string(), profile.clone())
     });
+    relink_profile_suggestion(&ic_cdk::caller(), previous.as_ref(), &profile);
+    reindex_profile(&ic_cdk::caller(), previous.as_ref(), &profile);
 
     Ok(profile)
 }
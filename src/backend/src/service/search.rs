@@ -1,121 +1,713 @@
-use crate::{VideoMetadata, VIDEOS};
+use crate::{
+    page::{collect_page, collect_scanned_page, page_size, start_after, Page},
+    search_index::{
+        document_for, edit_distance, idf, max_edits, min_shared_trigrams, query_terms, term_score,
+        trigrams, CorpusStats, MatchMode, SearchResults, FUZZY_WEIGHT,
+    },
+    search_query::{SearchQuery, SearchSort},
+    service::{clips::is_listed_with_source, sounds::sound_term_prefix, tags::canonical_tag, video_stats::video_stats},
+    sound::Sound,
+    tag::{key_suffix, tag_prefix},
+    video_stats::VideoStats,
+    Memory, VideoMetadata, SEARCH_DOCUMENTS, SEARCH_POSTINGS, SEARCH_TERMS, SEARCH_TOTAL_LENGTH,
+    SEARCH_TRIGRAMS, SOUNDS, SOUNDS_BY_USE_COUNT, SOUND_TERMS, TAG_RECENT_VIDEOS, TAG_TOP_VIDEOS, UPLOADER_VIDEOS,
+    VIDEOS, VIDEO_RANKINGS,
+};
 use ic_cdk::query;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::thread::LocalKey;
 
-/// Search for videos matching the given query in title or tags
+/// Misspelling candidates considered per query term
+const MAX_FUZZY_EXPANSIONS: usize = 10;
+
+/// Postings read per query term, highest term frequency first. Videos that
+/// use a very common term only in passing aren't matched through it.
+const MAX_POSTINGS_PER_TERM: usize = 500;
+
+/// Postings read per misspelling candidate of a query term
+const MAX_POSTINGS_PER_FUZZY_TERM: usize = 50;
+
+/// Index entries one search without text reads at most. A filter few videos
+/// pass can come back with a short page and a cursor to read on from.
+const MAX_SCANNED_VIDEOS: usize = 2000;
+
+/// Sounds one sound search call reads at most
+const MAX_SCANNED_SOUNDS: usize = 2000;
+
+type VideoIndex = LocalKey<RefCell<StableBTreeMap<String, (), Memory>>>;
+
+/// Searches videos by text and filters, sorted as the query asks.
+///
+/// Text is matched against titles, tags and descriptions through the search
+/// index and ranked with BM25. By default every query term must match;
+/// `MatchMode::Any` returns videos matching any of them. Terms also match
+/// close misspellings, ranked below exact matches, and a corrected query is
+/// suggested when a term isn't in the index at all. Each term is matched
+/// among the videos using it most, up to MAX_POSTINGS_PER_TERM, and results
+/// are keyed by score so a cursor resumes after the last one. Without text, every
+/// listed video passing the filters is a result, read in order from the
+/// uploader's videos, the first required tag's lists or the catalog rankings.
 #[query]
-pub fn search_videos(
-    query: String,
-    limit: Option<u32>,
-    offset: Option<u32>
-) -> Vec<VideoMetadata> {
-    // Empty query returns most recent videos
-    if query.is_empty() {
-        return list_recent_videos(limit, offset);
-    }
-    
-    let query = query.to_lowercase(); // Case-insensitive search
-    
-    VIDEOS.with(|videos| {
-        let videos_map = videos.borrow();
-        let mut results: Vec<VideoMetadata> = videos_map
-            .iter()
-            .filter(|(_, metadata)| {
-                // Search in title
-                let title_match = metadata.title.to_lowercase().contains(&query);
-                
-                // Search in tags
-                let tag_match = metadata.tags.iter().any(|tag| 
-                    tag.to_lowercase().contains(&query)
-                );
-                
-                // Match if either title or tags contain the query
-                title_match || tag_match
-            })
-            .map(|(_, metadata)| metadata.clone())
-            .collect();
-        
-        // Sort by timestamp (newest first)
-        results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        
-        // Apply pagination
-        apply_pagination(results, limit, offset)
+pub fn search_videos(query: SearchQuery) -> Result<SearchResults, String> {
+    query.validate()?;
+    let query = with_canonical_tags(query);
+    let sort = query.sort();
+    let cursor = query.cursor.as_deref();
+    let limit = page_size(query.limit);
+    let shown = |metadata: &VideoMetadata| is_listed_with_source(metadata) && query.matches(metadata);
+
+    let (page, did_you_mean) = match query.text() {
+        Some(text) => {
+            let (matches, did_you_mean) = match_text(text, query.match_mode.unwrap_or_default());
+            let ranked: BTreeMap<String, String> = matches
+                .into_iter()
+                .map(|(video_id, relevance)| {
+                    let key = match sort {
+                        SearchSort::Relevance => relevance.key(&video_id),
+                        SearchSort::Newest => sort.key(relevance.timestamp, &VideoStats::default(), &video_id),
+                        sort => sort.key(relevance.timestamp, &video_stats(&video_id), &video_id),
+                    };
+                    (key, video_id)
+                })
+                .collect();
+            // Videos are only loaded, and filtered, for the page
+            let start = start_after(cursor, &sort.prefix())?;
+            let entries = ranked.range((start, Bound::Unbounded)).filter_map(|(key, video_id)| {
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(video_id)).filter(shown)?;
+                Some((key.clone(), metadata))
+            });
+            (collect_page(entries, limit), did_you_mean)
+        }
+        None => (browse(&query, sort, cursor, limit, shown)?, None),
+    };
+
+    Ok(SearchResults {
+        videos: page.items,
+        next_cursor: page.next_cursor,
+        did_you_mean,
     })
 }
 
-/// Search for videos matching all specified tags
-#[query]
-pub fn search_videos_by_tags(
-    tags: Vec<String>,
-    limit: Option<u32>,
-    offset: Option<u32>
-) -> Vec<VideoMetadata> {
-    // If no tags provided, return recent videos
-    if tags.is_empty() {
-        return list_recent_videos(limit, offset);
-    }
-    
-    // Convert tags to lowercase for case-insensitive matching
-    let tags_lower: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
-    
-    VIDEOS.with(|videos| {
-        let videos_map = videos.borrow();
-        let mut results: Vec<VideoMetadata> = videos_map
-            .iter()
-            .filter(|(_, metadata)| {
-                // Video matches if it contains all the specified tags
-                tags_lower.iter().all(|search_tag| {
-                    metadata.tags.iter().any(|video_tag| 
-                        video_tag.to_lowercase() == *search_tag
-                    )
+/// Pages through the videos passing `shown` in `sort` order without text.
+/// An uploader filter narrows the scan to their videos; otherwise the first
+/// required tag's recent or most viewed list is read when it has the right
+/// order, and the catalog ranking for the sort when it doesn't. Newest first
+/// lists only read the upload date window. At most MAX_SCANNED_VIDEOS
+/// entries are read per page.
+fn browse(
+    query: &SearchQuery,
+    sort: SearchSort,
+    cursor: Option<&str>,
+    limit: usize,
+    shown: impl Fn(&VideoMetadata) -> bool,
+) -> Result<Page<VideoMetadata>, String> {
+    if let Some(uploader) = query.uploader {
+        let prefix = format!("{}:", uploader);
+        let uploaded = |timestamp: &u64| {
+            query.uploaded_after.is_none_or(|after| *timestamp >= after)
+                && query.uploaded_before.is_none_or(|before| *timestamp < before)
+        };
+        let uploads: BTreeMap<String, String> = UPLOADER_VIDEOS.with(|uploads| {
+            uploads
+                .borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .filter(|(_, timestamp)| uploaded(timestamp))
+                .map(|(key, timestamp)| {
+                    let video_id = key[prefix.len()..].to_string();
+                    let stats = match sort {
+                        SearchSort::Newest => VideoStats::default(),
+                        _ => video_stats(&video_id),
+                    };
+                    (sort.key(timestamp, &stats, &video_id), video_id)
                 })
+                .collect()
+        });
+        let start = start_after(cursor, &sort.prefix())?;
+        let entries = uploads.range((start, Bound::Unbounded)).map(|(key, video_id)| {
+            let metadata = VIDEOS.with(|videos| videos.borrow().get(video_id)).filter(&shown);
+            (key.clone(), metadata)
+        });
+        return Ok(collect_scanned_page(entries, limit, MAX_SCANNED_VIDEOS));
+    }
+
+    let tag = query.tags_all.as_ref().and_then(|tags| tags.first());
+    let (index, prefix, video_id_of): (&'static VideoIndex, String, fn(&str) -> &str) = match (tag, sort) {
+        (Some(tag), SearchSort::Newest) => (&TAG_RECENT_VIDEOS, tag_prefix(tag), key_suffix),
+        (Some(tag), SearchSort::MostViewed) => (&TAG_TOP_VIDEOS, tag_prefix(tag), key_suffix),
+        _ => (&VIDEO_RANKINGS, sort.prefix(), ranked_video_id),
+    };
+    let start = start_after(cursor, &prefix)?;
+    let (start, oldest) = match sort {
+        SearchSort::Newest => upload_window(&prefix, start, query.uploaded_after, query.uploaded_before),
+        _ => (start, None),
+    };
+    index.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take_while(|(key, _)| {
+                oldest.as_deref().is_none_or(|oldest| key[prefix.len()..].get(..20) <= Some(oldest))
             })
-            .map(|(_, metadata)| metadata.clone())
-            .collect();
-            
-        // Sort by timestamp (newest first)
-        results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        
-        // Apply pagination
-        apply_pagination(results, limit, offset)
+            .map(|(key, _)| {
+                let video_id = video_id_of(&key).to_string();
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id)).filter(&shown);
+                (key, metadata)
+            });
+        Ok(collect_scanned_page(entries, limit, MAX_SCANNED_VIDEOS))
     })
 }
 
-/// Get most recent videos
-pub fn list_recent_videos(
-    limit: Option<u32>,
-    offset: Option<u32>
-) -> Vec<VideoMetadata> {
-    VIDEOS.with(|videos| {
-        let videos_map = videos.borrow();
-        let mut results: Vec<VideoMetadata> = videos_map
+/// Narrows a scan over newest first keys, `prefix` followed by the inverted
+/// timestamp, to videos uploaded in [after, before): the range starts at the
+/// newest key allowed, and the inverted timestamp of the oldest one allowed
+/// is returned to stop at.
+fn upload_window(
+    prefix: &str,
+    start: Bound<String>,
+    after: Option<u64>,
+    before: Option<u64>,
+) -> (Bound<String>, Option<String>) {
+    let start = match before {
+        Some(before) => {
+            let newest = format!("{}{:020}", prefix, (u64::MAX - before).saturating_add(1));
+            match start {
+                Bound::Excluded(key) if key >= newest => Bound::Excluded(key),
+                _ => Bound::Included(newest),
+            }
+        }
+        None => start,
+    };
+    (start, after.map(|after| format!("{:020}", u64::MAX - after)))
+}
+
+/// Video id at the end of a `SearchSort::key`
+fn ranked_video_id(key: &str) -> &str {
+    key.splitn(3, ':').nth(2).unwrap_or_default()
+}
+
+/// The query with its tag filters spelled the way videos store tags
+fn with_canonical_tags(query: SearchQuery) -> SearchQuery {
+    let canonical = |tags: Option<Vec<String>>| {
+        tags.map(|tags| tags.iter().map(|tag| canonical_tag(tag)).collect())
+    };
+    SearchQuery {
+        tags_all: canonical(query.tags_all),
+        tags_any: canonical(query.tags_any),
+        tags_none: canonical(query.tags_none),
+        ..query
+    }
+}
+
+/// How well a video matched the query text
+#[derive(Default)]
+struct Relevance {
+    score: f64,
+    exact_matches: usize, // Query terms matched exactly rather than as a misspelling
+    timestamp: u64,       // When the video was uploaded
+}
+
+impl Relevance {
+    /// Sort key for a matched video: most exact term matches first, then
+    /// best score, then newest
+    fn key(&self, video_id: &str) -> String {
+        format!(
+            "{}{:020}:{:020}:{:020}:{}",
+            SearchSort::Relevance.prefix(),
+            u64::MAX - self.exact_matches as u64,
+            u64::MAX - self.score.max(0.0).to_bits(),
+            u64::MAX - self.timestamp,
+            video_id
+        )
+    }
+}
+
+/// Scores videos matching `text` through the search index, and suggests a
+/// corrected query when a term isn't indexed but has a close match
+fn match_text(text: &str, match_mode: MatchMode) -> (HashMap<String, Relevance>, Option<String>) {
+    let terms = query_terms(text);
+    let required_matches = match match_mode {
+        MatchMode::All => terms.len(),
+        MatchMode::Any => 1,
+    };
+
+    let stats = CorpusStats {
+        document_count: SEARCH_DOCUMENTS.with(|documents| documents.borrow().len()),
+        total_length: SEARCH_TOTAL_LENGTH.with(|total| *total.borrow().get()),
+    };
+    // video_id -> (document length, upload time)
+    let mut documents: HashMap<String, (u32, u64)> = HashMap::new();
+    let mut score_postings = |term: &str, exact: bool, matches: &mut HashMap<String, (f64, bool)>| {
+        let (weight, limit) = match exact {
+            true => (1.0, MAX_POSTINGS_PER_TERM),
+            false => (FUZZY_WEIGHT, MAX_POSTINGS_PER_FUZZY_TERM),
+        };
+        let document_frequency = SEARCH_TERMS
+            .with(|vocabulary| vocabulary.borrow().get(&term.to_string()))
+            .unwrap_or_default();
+        let idf = idf(document_frequency, &stats);
+        for (video_id, term_frequency) in postings_for(term, limit) {
+            let (length, _) = *documents.entry(video_id.clone()).or_insert_with(|| {
+                SEARCH_DOCUMENTS
+                    .with(|documents| documents.borrow().get(&video_id))
+                    .map(|document| (document.length, document.timestamp))
+                    .unwrap_or_default()
+            });
+            let score = weight * term_score(term_frequency, length, idf, &stats);
+            let entry = matches.entry(video_id).or_insert((0.0, false));
+            entry.0 = entry.0.max(score);
+            entry.1 |= exact;
+        }
+    };
+
+    // video_id -> (relevance, query terms matched)
+    let mut scores: HashMap<String, (Relevance, usize)> = HashMap::new();
+    let mut corrected_terms = Vec::with_capacity(terms.len());
+    let mut corrected = false;
+    for term in &terms {
+        let mut matches: HashMap<String, (f64, bool)> = HashMap::new();
+        score_postings(term, true, &mut matches);
+
+        let fuzzy = fuzzy_terms(term);
+        for candidate in &fuzzy {
+            score_postings(candidate, false, &mut matches);
+        }
+
+        match fuzzy.first() {
+            Some(suggestion) if !is_indexed_term(term) => {
+                corrected_terms.push(suggestion.clone());
+                corrected = true;
+            }
+            _ => corrected_terms.push(term.clone()),
+        }
+
+        for (video_id, (score, exact)) in matches {
+            let (relevance, matched) = scores.entry(video_id).or_default();
+            relevance.score += score;
+            relevance.exact_matches += usize::from(exact);
+            *matched += 1;
+        }
+    }
+
+    let matches = scores
+        .into_iter()
+        .filter(|(_, (_, matched))| *matched >= required_matches)
+        .map(|(video_id, (mut relevance, _))| {
+            relevance.timestamp = documents.get(&video_id).map_or(0, |(_, timestamp)| *timestamp);
+            (video_id, relevance)
+        })
+        .collect();
+    (matches, corrected.then(|| corrected_terms.join(" ")))
+}
+
+/// Indexed terms within the allowed edit distance of `term`, closest and
+/// most common first
+fn fuzzy_terms(term: &str) -> Vec<String> {
+    let max_edits = max_edits(term);
+    if max_edits == 0 {
+        return Vec::new();
+    }
+
+    let query_trigrams = trigrams(term);
+    let mut shared: HashMap<String, usize> = HashMap::new();
+    SEARCH_TRIGRAMS.with(|index| {
+        let index = index.borrow();
+        for trigram in &query_trigrams {
+            let prefix = format!("{}:", trigram);
+            for (key, _) in index
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+            {
+                *shared.entry(key[prefix.len()..].to_string()).or_default() += 1;
+            }
+        }
+    });
+
+    let term_length = term.chars().count();
+    let min_shared = min_shared_trigrams(query_trigrams.len(), max_edits);
+    let mut candidates: Vec<(usize, u64, String)> = shared
+        .into_iter()
+        .filter(|(candidate, count)| *count >= min_shared && candidate != term)
+        .filter(|(candidate, _)| candidate.chars().count().abs_diff(term_length) <= max_edits)
+        .filter_map(|(candidate, _)| {
+            let distance = edit_distance(term, &candidate);
+            (distance <= max_edits).then(|| {
+                let frequency = SEARCH_TERMS
+                    .with(|vocabulary| vocabulary.borrow().get(&candidate))
+                    .unwrap_or_default();
+                (distance, u64::MAX - frequency, candidate)
+            })
+        })
+        .collect();
+    candidates.sort();
+
+    candidates
+        .into_iter()
+        .take(MAX_FUZZY_EXPANSIONS)
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+fn is_indexed_term(term: &str) -> bool {
+    SEARCH_TERMS.with(|vocabulary| vocabulary.borrow().contains_key(&term.to_string()))
+}
+
+/// Adds a video to the search index, replacing what was indexed for it before.
+/// Trashed videos stay out of the index so they don't count towards corpus statistics.
+pub fn index_video(metadata: &VideoMetadata) {
+    remove_from_search_index(&metadata.video_id);
+    if metadata.is_deleted() {
+        return;
+    }
+
+    let document = document_for(metadata);
+    SEARCH_POSTINGS.with(|postings| {
+        let mut postings = postings.borrow_mut();
+        for (term, frequency) in &document.terms {
+            postings.insert(posting_key(term, *frequency, &metadata.video_id), *frequency);
+        }
+    });
+    for (term, _) in &document.terms {
+        add_to_vocabulary(term);
+    }
+    adjust_total_length(document.length as i64);
+    SEARCH_DOCUMENTS.with(|documents| {
+        documents
+            .borrow_mut()
+            .insert(metadata.video_id.clone(), document)
+    });
+}
+
+/// Removes a video from the search index
+pub fn remove_from_search_index(video_id: &str) {
+    let Some(document) = SEARCH_DOCUMENTS.with(|documents| documents.borrow_mut().remove(&video_id.to_string())) else {
+        return;
+    };
+    SEARCH_POSTINGS.with(|postings| {
+        let mut postings = postings.borrow_mut();
+        for (term, frequency) in &document.terms {
+            postings.remove(&posting_key(term, *frequency, video_id));
+        }
+    });
+    for (term, _) in &document.terms {
+        remove_from_vocabulary(term);
+    }
+    adjust_total_length(-(document.length as i64));
+}
+
+/// Counts a video using `term`, adding the term's trigrams when it is new
+fn add_to_vocabulary(term: &str) {
+    let frequency = SEARCH_TERMS.with(|vocabulary| {
+        let mut vocabulary = vocabulary.borrow_mut();
+        let frequency = vocabulary.get(&term.to_string()).unwrap_or_default() + 1;
+        vocabulary.insert(term.to_string(), frequency);
+        frequency
+    });
+    if frequency == 1 {
+        SEARCH_TRIGRAMS.with(|index| {
+            let mut index = index.borrow_mut();
+            for trigram in trigrams(term) {
+                index.insert(format!("{}:{}", trigram, term), ());
+            }
+        });
+    }
+}
+
+/// Stops counting a video using `term`, dropping the term once unused
+fn remove_from_vocabulary(term: &str) {
+    let remaining = SEARCH_TERMS.with(|vocabulary| {
+        let mut vocabulary = vocabulary.borrow_mut();
+        let remaining = vocabulary.get(&term.to_string()).unwrap_or_default().saturating_sub(1);
+        if remaining == 0 {
+            vocabulary.remove(&term.to_string());
+        } else {
+            vocabulary.insert(term.to_string(), remaining);
+        }
+        remaining
+    });
+    if remaining == 0 {
+        SEARCH_TRIGRAMS.with(|index| {
+            let mut index = index.borrow_mut();
+            for trigram in trigrams(term) {
+                index.remove(&format!("{}:{}", trigram, term));
+            }
+        });
+    }
+}
+
+/// Builds the search index for videos stored before it existed, and rebuilds
+/// it when it predates the search vocabulary. A populated index is kept
+/// up to date by the video hooks, so this does nothing on later upgrades.
+pub fn backfill_search_index() {
+    let empty = SEARCH_DOCUMENTS.with(|documents| documents.borrow().is_empty());
+    let rebuild = !empty && SEARCH_TERMS.with(|vocabulary| vocabulary.borrow().is_empty());
+    if !empty && !rebuild {
+        return;
+    }
+    let videos: Vec<VideoMetadata> = VIDEOS.with(|videos| {
+        videos
+            .borrow()
             .iter()
-            .map(|(_, metadata)| metadata.clone())
-            .collect();
-            
-        // Sort by timestamp (newest first)
-        results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        
-        // Apply pagination
-        apply_pagination(results, limit, offset)
+            .map(|(_, metadata)| metadata)
+            .collect()
+    });
+    // Trashed videos are skipped, and dropped from an index being rebuilt
+    for metadata in &videos {
+        index_video(metadata);
+    }
+}
+
+/// Key of a posting, ordering a term's videos by descending frequency
+fn posting_key(term: &str, frequency: u32, video_id: &str) -> String {
+    format!("{}\0{:010}\0{}", term, u32::MAX - frequency, video_id)
+}
+
+/// Up to `limit` videos containing `term`, most frequent first, with the
+/// term's weighted frequency
+fn postings_for(term: &str, limit: usize) -> Vec<(String, u32)> {
+    let prefix = format!("{}\0", term);
+    SEARCH_POSTINGS.with(|postings| {
+        postings
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(key, frequency)| (key_suffix(&key).to_string(), frequency))
+            .collect()
     })
 }
 
-/// Helper function to apply pagination to a vector of results
-fn apply_pagination(
-    results: Vec<VideoMetadata>,
-    limit: Option<u32>,
-    offset: Option<u32>
-) -> Vec<VideoMetadata> {
-    let start = offset.unwrap_or(0) as usize;
-    let end = if let Some(limit_val) = limit {
-        start + limit_val as usize
-    } else {
-        results.len()
+fn adjust_total_length(delta: i64) {
+    SEARCH_TOTAL_LENGTH.with(|total| {
+        let mut total = total.borrow_mut();
+        let updated = (*total.get() as i64 + delta).max(0) as u64;
+        total.set(updated).expect("Failed to update search index length");
+    });
+}
+
+/// Search the sounds library by the words of their titles, most used first.
+/// Every query word must be in the title; without any, all sounds are listed.
+#[query]
+pub fn search_sounds(query: String, cursor: Option<String>, limit: Option<u32>) -> Result<Page<Sound>, String> {
+    let terms = query_terms(&query);
+    let sound = |sound_id: &str| SOUNDS.with(|sounds| sounds.borrow().get(&sound_id.to_string()));
+
+    // Read the sounds under the longest term in rank order and check the others
+    let Some(lead) = terms.iter().max_by_key(|term| term.len()) else {
+        let start = start_after(cursor.as_deref(), "")?;
+        return SOUNDS_BY_USE_COUNT.with(|ranked| {
+            let ranked = ranked.borrow();
+            let entries = ranked.range((start, Bound::Unbounded)).filter_map(|(key, _)| {
+                let (_, sound_id) = key.split_once(':')?;
+                sound(sound_id).map(|sound| (key, sound))
+            });
+            Ok(collect_page(entries, page_size(limit)))
+        });
     };
-    
-    if start < results.len() {
-        results[start..std::cmp::min(end, results.len())].to_vec()
-    } else {
-        Vec::new()
+    let prefix = sound_term_prefix(lead);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+    SOUND_TERMS.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| {
+                let matched = key_suffix(&key).to_string();
+                let matched = sound(&matched).filter(|sound| {
+                    let title_terms = query_terms(&sound.title);
+                    terms.iter().all(|term| title_terms.contains(term))
+                });
+                (key, matched)
+            });
+        Ok(collect_scanned_page(entries, page_size(limit), MAX_SCANNED_SOUNDS))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::{
+            tag_pages::{record_tag_view, relink_tag_aggregates},
+            video::record_uploader_video,
+            video_stats::{record_view_stats, relink_video_rankings},
+        },
+        video_metadata::test_video,
+    };
+    use candid::Principal;
+
+    fn upload(video_id: &str, uploader: u8, tags: &[&str], timestamp: u64) -> VideoMetadata {
+        let metadata = test_video(video_id, Principal::from_slice(&[uploader]), tags, timestamp);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        record_uploader_video(&metadata);
+        index_video(&metadata);
+        relink_tag_aggregates(None, Some(&metadata));
+        relink_video_rankings(None, Some(&metadata));
+        metadata
+    }
+
+    fn watch(video_id: &str, liked: bool) {
+        record_view_stats(video_id, liked);
+        record_tag_view(video_id);
+    }
+
+    fn ids(results: &SearchResults) -> Vec<&str> {
+        results.videos.iter().map(|metadata| metadata.video_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_browse_reads_ordered_indexes() {
+        upload("a", 1, &["rust"], 1);
+        upload("b", 1, &["rust"], 2);
+        upload("c", 2, &["go"], 3);
+        let trashed = upload("d", 2, &["rust"], 4);
+        let mut deleted = trashed.clone();
+        deleted.deleted_at = Some(5);
+        VIDEOS.with(|videos| videos.borrow_mut().insert("d".to_string(), deleted.clone()));
+        relink_tag_aggregates(Some(&trashed), Some(&deleted));
+        relink_video_rankings(Some(&trashed), Some(&deleted));
+        watch("a", false);
+        watch("a", false);
+        watch("c", true);
+
+        let first = search_videos(SearchQuery {
+            limit: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(ids(&first), vec!["c", "b"]);
+        let rest = search_videos(SearchQuery {
+            limit: Some(2),
+            cursor: first.next_cursor.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(ids(&rest), vec!["a"]);
+        assert_eq!(rest.next_cursor, None);
+
+        let tagged = |sort| SearchQuery {
+            tags_all: Some(vec!["Rust".to_string()]),
+            sort: Some(sort),
+            ..Default::default()
+        };
+        assert_eq!(ids(&search_videos(tagged(SearchSort::Newest)).unwrap()), vec!["b", "a"]);
+        assert_eq!(ids(&search_videos(tagged(SearchSort::MostViewed)).unwrap()), vec!["a", "b"]);
+
+        let liked = SearchQuery {
+            sort: Some(SearchSort::MostLiked),
+            ..Default::default()
+        };
+        assert_eq!(ids(&search_videos(liked).unwrap())[0], "c");
+
+        let by_uploader = SearchQuery {
+            uploader: Some(Principal::from_slice(&[1])),
+            sort: Some(SearchSort::MostViewed),
+            ..Default::default()
+        };
+        assert_eq!(ids(&search_videos(by_uploader).unwrap()), vec!["a", "b"]);
+
+        // A cursor from another ordering is rejected
+        let mismatched = search_videos(SearchQuery {
+            tags_all: Some(vec!["rust".to_string()]),
+            cursor: first.next_cursor,
+            ..Default::default()
+        });
+        assert!(mismatched.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_text_results_page_by_key() {
+        upload("a", 1, &["rust"], 1);
+        upload("b", 1, &["rust"], 2);
+        upload("c", 1, &["go"], 3);
+
+        let query = |cursor| SearchQuery {
+            text: Some("rust".to_string()),
+            limit: Some(1),
+            cursor,
+            ..Default::default()
+        };
+        let first = search_videos(query(None)).unwrap();
+        assert_eq!(ids(&first), vec!["b"]);
+        let second = search_videos(query(first.next_cursor)).unwrap();
+        assert_eq!(ids(&second), vec!["a"]);
+        assert_eq!(second.next_cursor, None);
+
+        let too_long = SearchQuery {
+            text: Some("rust ".repeat(100)),
+            ..Default::default()
+        };
+        assert!(search_videos(too_long).is_err());
+    }
+
+    #[test]
+    fn test_common_terms_match_their_strongest_videos() {
+        for index in 0..MAX_POSTINGS_PER_TERM + 10 {
+            upload(&format!("v{:03}", index), 1, &["rust"], index as u64 + 1);
+        }
+        // Oldest and last by ID, but uses the term most
+        let mut strongest = test_video("zz", Principal::from_slice(&[1]), &["rust"], 0);
+        strongest.description = Some("rust rust rust".to_string());
+        VIDEOS.with(|videos| videos.borrow_mut().insert("zz".to_string(), strongest.clone()));
+        index_video(&strongest);
+
+        let (matches, _) = match_text("rust", MatchMode::All);
+        assert_eq!(matches.len(), MAX_POSTINGS_PER_TERM);
+        assert!(matches.contains_key("zz"));
+
+        let results = search_videos(SearchQuery {
+            text: Some("rust".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(ids(&results), vec!["zz"]);
+    }
+
+    #[test]
+    fn test_selective_filters_stop_at_the_scan_limit() {
+        // Only ranked, the hooks `upload` also runs aren't needed here
+        for index in 0..MAX_SCANNED_VIDEOS + 10 {
+            let metadata = test_video(&format!("v{:04}", index), Principal::from_slice(&[1]), &[], index as u64 + 10);
+            VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata.clone()));
+            relink_video_rankings(None, Some(&metadata));
+        }
+        let mut french = test_video("fr", Principal::from_slice(&[2]), &[], 1);
+        french.primary_language = Some("fr".to_string());
+        VIDEOS.with(|videos| videos.borrow_mut().insert("fr".to_string(), french.clone()));
+        relink_video_rankings(None, Some(&french));
+
+        let query = |cursor| SearchQuery {
+            language: Some("fr".to_string()),
+            cursor,
+            ..Default::default()
+        };
+        let first = search_videos(query(None)).unwrap();
+        assert!(first.videos.is_empty());
+        assert!(first.next_cursor.is_some());
+        let rest = search_videos(query(first.next_cursor)).unwrap();
+        assert_eq!(ids(&rest), vec!["fr"]);
+        assert_eq!(rest.next_cursor, None);
+
+        // A date window skips straight to the uploads inside it
+        let window = search_videos(SearchQuery {
+            uploaded_after: Some(1),
+            uploaded_before: Some(11),
+            ..query(None)
+        })
+        .unwrap();
+        assert_eq!(ids(&window), vec!["fr"]);
+        assert_eq!(window.next_cursor, None);
+        let newest = search_videos(SearchQuery {
+            uploaded_after: Some(MAX_SCANNED_VIDEOS as u64 + 15),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(newest.videos.len(), 5);
+        assert_eq!(newest.next_cursor, None);
+    }
+}
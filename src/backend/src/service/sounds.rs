@@ -0,0 +1,304 @@
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, page_size, start_after, Page, MAX_PAGE_SIZE},
+    search_index::query_terms,
+    service::clips::is_listed_with_source,
+    sound::{rank_by_recent_uses, validate_duration, validate_title, Sound, TrendingSound},
+    video_id::encode_base58,
+    video_metadata::VideoMetadata,
+    SOUNDS, SOUNDS_BY_USE_COUNT, SOUND_ID_COUNTER, SOUND_TERMS, SOUND_USES, VIDEOS,
+};
+
+/// Uses within this window count towards trending
+const TRENDING_WINDOW_SEC: u64 = 7 * 24 * 60 * 60;
+const DEFAULT_TRENDING_LIMIT: u32 = 20;
+
+/// Videos sort newest upload first under their sound
+fn use_key(sound_id: &str, metadata: &VideoMetadata) -> String {
+    format!("{}:{:020}:{}", sound_id, u64::MAX - metadata.timestamp, metadata.video_id)
+}
+
+/// Key ranking a sound in SOUNDS_BY_USE_COUNT, most used first
+pub fn use_count_key(sound: &Sound) -> String {
+    format!("{:010}:{}", u32::MAX - sound.use_count, sound.sound_id)
+}
+
+/// Key ranking a sound under one of its title terms, most used first
+pub fn sound_term_key(term: &str, sound: &Sound) -> String {
+    format!("{}\0{:010}\0{}", term, u32::MAX - sound.use_count, sound.sound_id)
+}
+
+/// Prefix of the sounds ranked under `term`
+pub fn sound_term_prefix(term: &str) -> String {
+    format!("{}\0", term)
+}
+
+/// Moves a sound's use count ranking and title terms from `before` to `after`
+fn relink_sound_ranking(before: Option<&Sound>, after: Option<&Sound>) {
+    SOUNDS_BY_USE_COUNT.with(|ranked| {
+        let mut ranked = ranked.borrow_mut();
+        if let Some(sound) = before {
+            ranked.remove(&use_count_key(sound));
+        }
+        if let Some(sound) = after {
+            ranked.insert(use_count_key(sound), ());
+        }
+    });
+    SOUND_TERMS.with(|terms| {
+        let mut terms = terms.borrow_mut();
+        if let Some(sound) = before {
+            for term in query_terms(&sound.title) {
+                terms.remove(&sound_term_key(&term, sound));
+            }
+        }
+        if let Some(sound) = after {
+            for term in query_terms(&sound.title) {
+                terms.insert(sound_term_key(&term, sound), ());
+            }
+        }
+    });
+}
+
+fn mint_sound_id() -> String {
+    SOUND_ID_COUNTER.with(|counter| {
+        let mut counter_cell = counter.borrow_mut();
+        let next_id = *counter_cell.get();
+        counter_cell
+            .set(next_id + 1)
+            .expect("Failed to persist sound id counter");
+        format!("SN{}", encode_base58(&next_id.to_be_bytes()))
+    })
+}
+
+/// Registers a sound. When it is taken from a video, the caller must be that video's uploader.
+#[update]
+pub fn create_sound(
+    title: String,
+    source_video_id: Option<String>,
+    duration_sec: u32,
+) -> Result<Sound, String> {
+    let caller = ic_cdk::caller();
+    validate_title(&title)?;
+    validate_duration(duration_sec)?;
+
+    if let Some(source_video_id) = &source_video_id {
+        let source = VIDEOS
+            .with(|videos| videos.borrow().get(source_video_id))
+            .filter(|metadata| !metadata.is_deleted())
+            .ok_or_else(|| "Source video not found".to_string())?;
+        if source.uploader_principal != caller {
+            return Err("Only the uploader can create a sound from a video".to_string());
+        }
+    }
+
+    let sound = Sound {
+        sound_id: mint_sound_id(),
+        title,
+        creator_principal: caller,
+        source_video_id,
+        duration_sec,
+        use_count: 0,
+        created_at: ic_cdk::api::time() / 1_000_000_000,
+    };
+    SOUNDS.with(|sounds| {
+        sounds
+            .borrow_mut()
+            .insert(sound.sound_id.clone(), sound.clone())
+    });
+    relink_sound_ranking(None, Some(&sound));
+    Ok(sound)
+}
+
+#[query]
+pub fn get_sound(sound_id: String) -> Result<Sound, String> {
+    SOUNDS
+        .with(|sounds| sounds.borrow().get(&sound_id))
+        .ok_or_else(|| "Sound not found".to_string())
+}
+
+/// Lists videos using a sound, newest first
+#[query]
+pub fn list_videos_by_sound(
+    sound_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", sound_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    SOUND_USES.with(|uses| {
+        let uses = uses.borrow();
+        let entries = uses
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let (_, video_id) = key.strip_prefix(&prefix)?.split_once(':')?;
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id.to_string()))?;
+                is_listed_with_source(&metadata).then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+/// Sounds picked by the most videos over the last week
+#[query]
+pub fn get_trending_sounds(limit: Option<u32>) -> Vec<TrendingSound> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let limit = limit.unwrap_or(DEFAULT_TRENDING_LIMIT).min(MAX_PAGE_SIZE) as usize;
+
+    let ranked = SOUND_USES.with(|uses| {
+        rank_by_recent_uses(
+            uses.borrow().iter().filter_map(|(key, used_at)| {
+                key.split_once(':')
+                    .map(|(sound_id, _)| (sound_id.to_string(), used_at))
+            }),
+            now.saturating_sub(TRENDING_WINDOW_SEC),
+        )
+    });
+
+    ranked
+        .into_iter()
+        .filter_map(|(sound_id, recent_uses)| {
+            SOUNDS
+                .with(|sounds| sounds.borrow().get(&sound_id))
+                .map(|sound| TrendingSound { sound, recent_uses })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Checks that a video can reference the sound
+pub fn validate_sound_id(sound_id: &str) -> Result<(), String> {
+    if SOUNDS.with(|sounds| sounds.borrow().contains_key(&sound_id.to_string())) {
+        Ok(())
+    } else {
+        Err("Sound not found".to_string())
+    }
+}
+
+/// Moves a video's entry in the sound usage index and keeps use counts
+/// current. `used_at` is when the video picked its new sound.
+pub fn relink_sound(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>, used_at: u64) {
+    let old = before.and_then(|metadata| metadata.sound_id.as_ref());
+    let new = after.and_then(|metadata| metadata.sound_id.as_ref());
+    if old == new {
+        return;
+    }
+
+    if let (Some(sound_id), Some(metadata)) = (old, before) {
+        SOUND_USES.with(|uses| uses.borrow_mut().remove(&use_key(sound_id, metadata)));
+        adjust_use_count(sound_id, |count| count.saturating_sub(1));
+    }
+    if let (Some(sound_id), Some(metadata)) = (new, after) {
+        SOUND_USES.with(|uses| {
+            uses.borrow_mut()
+                .insert(use_key(sound_id, metadata), used_at)
+        });
+        adjust_use_count(sound_id, |count| count + 1);
+    }
+}
+
+fn adjust_use_count(sound_id: &String, adjust: impl Fn(u32) -> u32) {
+    SOUNDS.with(|sounds| {
+        let mut sounds_map = sounds.borrow_mut();
+        if let Some(before) = sounds_map.get(sound_id) {
+            let mut sound = before.clone();
+            sound.use_count = adjust(sound.use_count);
+            relink_sound_ranking(Some(&before), Some(&sound));
+            sounds_map.insert(sound_id.clone(), sound);
+        }
+    });
+}
+
+/// Ranks and indexes the titles of sounds created before they were ranked by use count
+pub fn backfill_sound_ranking() {
+    if SOUNDS_BY_USE_COUNT.with(|ranked| !ranked.borrow().is_empty()) {
+        return;
+    }
+    let sounds: Vec<Sound> = SOUNDS.with(|sounds| sounds.borrow().iter().map(|(_, sound)| sound).collect());
+    for sound in &sounds {
+        relink_sound_ranking(None, Some(sound));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{service::search::search_sounds, video_metadata::test_video};
+    use candid::Principal;
+
+    fn add_sound(sound_id: &str, title: &str) {
+        let sound = Sound {
+            sound_id: sound_id.to_string(),
+            title: title.to_string(),
+            creator_principal: Principal::anonymous(),
+            source_video_id: None,
+            duration_sec: 30,
+            use_count: 0,
+            created_at: 0,
+        };
+        SOUNDS.with(|sounds| sounds.borrow_mut().insert(sound_id.to_string(), sound.clone()));
+        relink_sound_ranking(None, Some(&sound));
+    }
+
+    fn use_sound(video_id: &str, sound_id: &str, timestamp: u64) -> VideoMetadata {
+        let mut metadata = test_video(video_id, Principal::anonymous(), &[], timestamp);
+        metadata.sound_id = Some(sound_id.to_string());
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        relink_sound(None, Some(&metadata), timestamp);
+        metadata
+    }
+
+    fn ids(page: &Page<VideoMetadata>) -> Vec<&str> {
+        page.items.iter().map(|metadata| metadata.video_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_videos_by_sound_page_newest_first() {
+        add_sound("SN1", "Beat");
+        use_sound("a", "SN1", 10);
+        use_sound("b", "SN1", 30);
+        let c = use_sound("c", "SN1", 20);
+
+        let first = list_videos_by_sound("SN1".to_string(), None, Some(2)).unwrap();
+        assert_eq!(ids(&first), vec!["b", "c"]);
+        let rest = list_videos_by_sound("SN1".to_string(), first.next_cursor, Some(2)).unwrap();
+        assert_eq!(ids(&rest), vec!["a"]);
+        assert_eq!(rest.next_cursor, None);
+
+        // Switching sounds moves the video out of the list
+        let mut edited = c.clone();
+        edited.sound_id = None;
+        relink_sound(Some(&c), Some(&edited), 40);
+        assert_eq!(ids(&list_videos_by_sound("SN1".to_string(), None, None).unwrap()), vec!["b", "a"]);
+        assert_eq!(SOUNDS.with(|sounds| sounds.borrow().get(&"SN1".to_string())).unwrap().use_count, 2);
+    }
+
+    #[test]
+    fn test_search_sounds_ranks_by_use_count() {
+        add_sound("SN1", "Summer beat");
+        add_sound("SN2", "Winter beat");
+        add_sound("SN3", "Rain");
+        use_sound("a", "SN2", 10);
+        use_sound("b", "SN2", 11);
+        use_sound("c", "SN1", 12);
+
+        let first = search_sounds("BEAT".to_string(), None, Some(1)).unwrap();
+        assert_eq!(first.items[0].sound_id, "SN2");
+        let rest = search_sounds("BEAT".to_string(), first.next_cursor, Some(1)).unwrap();
+        assert_eq!(rest.items[0].sound_id, "SN1");
+        assert_eq!(rest.next_cursor, None);
+
+        let ids = |query: &str| -> Vec<String> {
+            let page = search_sounds(query.to_string(), None, None).unwrap();
+            page.items.into_iter().map(|sound| sound.sound_id).collect()
+        };
+        assert_eq!(ids("beat winter"), vec!["SN2"]);
+        assert_eq!(ids("rain"), vec!["SN3"]);
+        assert!(ids("bea").is_empty());
+        // Without text every sound is listed, most used first
+        assert_eq!(ids(""), vec!["SN2", "SN1", "SN3"]);
+    }
+}
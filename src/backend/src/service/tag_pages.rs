@@ -0,0 +1,414 @@
+use ic_cdk::query;
+use std::{collections::BTreeSet, ops::Bound};
+
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    service::{clips::is_listed_with_source, tags::canonical_tag, video_stats::video_stats},
+    tag::{key_suffix, pair_key, ranked_key, tag_prefix, RelatedTag, TagPage, TagStats},
+    video_metadata::VideoMetadata,
+    TAG_PAIRS, TAG_RECENT_VIDEOS, TAG_RELATED, TAG_STATS, TAG_TOP_VIDEOS, VIDEOS,
+};
+
+const TOP_VIDEOS_LIMIT: usize = 10;
+const RELATED_TAGS_LIMIT: usize = 10;
+
+/// Tags of a video, in the order the uploader gave them, that count towards
+/// related tags. Keeps the pairs written per video edit to a few dozen.
+const MAX_PAIRED_TAGS: usize = 8;
+
+/// Browses a tag like a channel: totals, most viewed videos, a page of recent
+/// videos and the tags most often used alongside it. Any spelling or alias of
+/// the tag works.
+#[query]
+pub fn get_tag_page(tag: String, cursor: Option<String>, limit: Option<u32>) -> Result<TagPage, String> {
+    let tag = canonical_tag(&tag);
+    if tag.is_empty() {
+        return Err("Tag can't be empty".to_string());
+    }
+    let prefix = tag_prefix(&tag);
+    let stats = TAG_STATS.with(|stats| stats.borrow().get(&tag)).unwrap_or_default();
+    let recent_videos = recent_videos(&tag, cursor, limit)?;
+
+    let top_videos = TAG_TOP_VIDEOS.with(|top| {
+        top.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| listed_video(key_suffix(&key)))
+            .take(TOP_VIDEOS_LIMIT)
+            .collect()
+    });
+
+    let related_tags = TAG_RELATED.with(|related| {
+        related
+            .borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(RELATED_TAGS_LIMIT)
+            .map(|(key, shared_videos)| RelatedTag {
+                tag: key_suffix(&key).to_string(),
+                shared_videos,
+            })
+            .collect()
+    });
+
+    Ok(TagPage {
+        tag,
+        video_count: stats.video_count,
+        total_views: stats.total_views,
+        top_videos,
+        recent_videos,
+        related_tags,
+    })
+}
+
+/// Pages through a canonical tag's listed videos, newest first
+pub fn recent_videos(tag: &str, cursor: Option<String>, limit: Option<u32>) -> Result<Page<VideoMetadata>, String> {
+    let prefix = tag_prefix(tag);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+    TAG_RECENT_VIDEOS.with(|recent| {
+        let recent = recent.borrow();
+        let entries = recent
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| listed_video(key_suffix(&key)).map(|metadata| (key, metadata)));
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+fn listed_video(video_id: &str) -> Option<VideoMetadata> {
+    VIDEOS
+        .with(|videos| videos.borrow().get(&video_id.to_string()))
+        .filter(is_listed_with_source)
+}
+
+/// Tags a video counts towards; videos in the trash count towards none
+fn counted_tags(metadata: Option<&VideoMetadata>) -> BTreeSet<String> {
+    metadata
+        .filter(|metadata| !metadata.is_deleted())
+        .map(|metadata| metadata.tags.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Tags a video counts towards related tags with: its first MAX_PAIRED_TAGS
+/// counted tags
+fn paired_tags(metadata: Option<&VideoMetadata>) -> BTreeSet<String> {
+    metadata
+        .filter(|metadata| !metadata.is_deleted())
+        .map(|metadata| metadata.tags.iter().take(MAX_PAIRED_TAGS).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Moves a video's contribution to tag pages from `before` to `after`. Call
+/// whenever a video is created, retagged, trashed, restored or purged; on
+/// purge, before its stats are removed.
+pub fn relink_tag_aggregates(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>) {
+    let Some(video) = after.or(before) else {
+        return;
+    };
+    let old_tags = counted_tags(before);
+    let new_tags = counted_tags(after);
+    let old_pairs = paired_tags(before);
+    let new_pairs = paired_tags(after);
+    if old_tags == new_tags && old_pairs == new_pairs {
+        return;
+    }
+    let views = video_stats(&video.video_id).views;
+
+    for tag in old_tags.difference(&new_tags) {
+        update_stats(tag, |stats| {
+            stats.video_count = stats.video_count.saturating_sub(1);
+            stats.total_views = stats.total_views.saturating_sub(views);
+        });
+        TAG_RECENT_VIDEOS.with(|recent| {
+            recent
+                .borrow_mut()
+                .remove(&ranked_key(tag, video.timestamp, &video.video_id))
+        });
+        TAG_TOP_VIDEOS.with(|top| top.borrow_mut().remove(&ranked_key(tag, views, &video.video_id)));
+    }
+    for tag in new_tags.difference(&old_tags) {
+        update_stats(tag, |stats| {
+            stats.video_count += 1;
+            stats.total_views += views;
+        });
+        TAG_RECENT_VIDEOS.with(|recent| {
+            recent
+                .borrow_mut()
+                .insert(ranked_key(tag, video.timestamp, &video.video_id), ())
+        });
+        TAG_TOP_VIDEOS.with(|top| top.borrow_mut().insert(ranked_key(tag, views, &video.video_id), ()));
+    }
+
+    for (tag, other) in tag_pairs(&old_pairs) {
+        if !(new_pairs.contains(tag) && new_pairs.contains(other)) {
+            adjust_pair(tag, other, false);
+        }
+    }
+    for (tag, other) in tag_pairs(&new_pairs) {
+        if !(old_pairs.contains(tag) && old_pairs.contains(other)) {
+            adjust_pair(tag, other, true);
+        }
+    }
+}
+
+/// Counts a video carrying both tags, or one that stopped carrying them,
+/// and moves `other` to its new place among `tag`'s related tags
+fn adjust_pair(tag: &str, other: &str, added: bool) {
+    let key = pair_key(tag, other);
+    let count = TAG_PAIRS.with(|pairs| pairs.borrow().get(&key)).unwrap_or_default();
+    let updated = if added { count + 1 } else { count.saturating_sub(1) };
+    TAG_PAIRS.with(|pairs| match updated {
+        0 => pairs.borrow_mut().remove(&key),
+        updated => pairs.borrow_mut().insert(key, updated),
+    });
+    TAG_RELATED.with(|related| {
+        let mut related = related.borrow_mut();
+        related.remove(&ranked_key(tag, count, other));
+        if updated > 0 {
+            related.insert(ranked_key(tag, updated, other), updated);
+        }
+    });
+}
+
+/// Every ordered pair of distinct tags
+fn tag_pairs(tags: &BTreeSet<String>) -> impl Iterator<Item = (&String, &String)> {
+    tags.iter()
+        .flat_map(move |tag| tags.iter().filter(move |other| *other != tag).map(move |other| (tag, other)))
+}
+
+fn update_stats(tag: &str, update: impl FnOnce(&mut TagStats)) {
+    TAG_STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        let mut tag_stats = stats.get(&tag.to_string()).unwrap_or_default();
+        update(&mut tag_stats);
+        if tag_stats.video_count == 0 {
+            stats.remove(&tag.to_string());
+        } else {
+            stats.insert(tag.to_string(), tag_stats);
+        }
+    });
+}
+
+/// Moves a just-watched video up its tags' most viewed lists. Call after
+/// the view is added to the video's stats.
+pub fn record_tag_view(video_id: &str) {
+    let Some(metadata) = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id.to_string()))
+        .filter(|metadata| !metadata.is_deleted())
+    else {
+        return;
+    };
+    let views = video_stats(video_id).views;
+
+    for tag in counted_tags(Some(&metadata)) {
+        update_stats(&tag, |stats| stats.total_views += 1);
+        TAG_TOP_VIDEOS.with(|top| {
+            let mut top = top.borrow_mut();
+            top.remove(&ranked_key(&tag, views.saturating_sub(1), video_id));
+            top.insert(ranked_key(&tag, views, video_id), ());
+        });
+    }
+}
+
+/// Builds tag pages for videos stored before they were kept
+pub fn backfill_tag_aggregates() {
+    if TAG_STATS.with(|stats| !stats.borrow().is_empty()) {
+        return;
+    }
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        relink_tag_aggregates(None, Some(metadata));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::video_stats::{record_view_stats, remove_video_stats},
+        video_metadata::test_video,
+    };
+    use candid::Principal;
+
+    fn create(video_id: &str, tags: &[&str], timestamp: u64) -> VideoMetadata {
+        let metadata = test_video(video_id, Principal::anonymous(), tags, timestamp);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        relink_tag_aggregates(None, Some(&metadata));
+        metadata
+    }
+
+    fn update(before: &VideoMetadata, edit: impl FnOnce(&mut VideoMetadata)) -> VideoMetadata {
+        let mut metadata = before.clone();
+        edit(&mut metadata);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata.clone()));
+        relink_tag_aggregates(Some(before), Some(&metadata));
+        metadata
+    }
+
+    fn purge(metadata: &VideoMetadata) {
+        VIDEOS.with(|videos| videos.borrow_mut().remove(&metadata.video_id));
+        relink_tag_aggregates(Some(metadata), None);
+        remove_video_stats(&metadata.video_id);
+    }
+
+    fn watch(video_id: &str, times: u32) {
+        for _ in 0..times {
+            record_view_stats(video_id, false);
+            record_tag_view(video_id);
+        }
+    }
+
+    fn page(tag: &str) -> TagPage {
+        get_tag_page(tag.to_string(), None, None).unwrap()
+    }
+
+    fn ids(videos: &[VideoMetadata]) -> Vec<&str> {
+        videos.iter().map(|metadata| metadata.video_id.as_str()).collect()
+    }
+
+    fn related(page: &TagPage) -> Vec<(&str, u64)> {
+        page.related_tags
+            .iter()
+            .map(|related| (related.tag.as_str(), related.shared_videos))
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_page_counts_views_and_lists() {
+        create("a", &["cats", "funny"], 10);
+        create("b", &["cats"], 30);
+        create("c", &["cats", "funny"], 20);
+        watch("a", 3);
+        watch("b", 1);
+        watch("c", 2);
+
+        let cats = page("Cats");
+        assert_eq!(cats.tag, "cats");
+        assert_eq!(cats.video_count, 3);
+        assert_eq!(cats.total_views, 6);
+        assert_eq!(ids(&cats.top_videos), vec!["a", "c", "b"]);
+        assert_eq!(ids(&cats.recent_videos.items), vec!["b", "c", "a"]);
+        assert_eq!(related(&cats), vec![("funny", 2)]);
+
+        let first = get_tag_page("cats".to_string(), None, Some(2)).unwrap();
+        assert_eq!(ids(&first.recent_videos.items), vec!["b", "c"]);
+        let rest = get_tag_page("cats".to_string(), first.recent_videos.next_cursor, Some(2)).unwrap();
+        assert_eq!(ids(&rest.recent_videos.items), vec!["a"]);
+        assert_eq!(rest.recent_videos.next_cursor, None);
+
+        let funny = page("funny");
+        assert_eq!((funny.video_count, funny.total_views), (2, 5));
+        assert_eq!(related(&funny), vec![("cats", 2)]);
+
+        assert!(get_tag_page(" ".to_string(), None, None).is_err());
+    }
+
+    #[test]
+    fn test_tag_page_follows_retag_trash_and_purge() {
+        let a = create("a", &["cats", "funny"], 10);
+        let b = create("b", &["cats", "funny"], 20);
+        watch("a", 2);
+        watch("b", 1);
+
+        // Editing anything but the tags leaves the aggregates alone
+        let a = update(&a, |metadata| metadata.title = "Renamed".to_string());
+        assert_eq!((page("cats").video_count, page("cats").total_views), (2, 3));
+
+        // Retagging moves the video and its views to the new tag
+        let a = update(&a, |metadata| metadata.tags = vec!["cats".to_string(), "dogs".to_string()]);
+        let funny = page("funny");
+        assert_eq!((funny.video_count, funny.total_views), (1, 1));
+        assert_eq!(ids(&funny.top_videos), vec!["b"]);
+        let dogs = page("dogs");
+        assert_eq!((dogs.video_count, dogs.total_views), (1, 2));
+        assert_eq!(ids(&dogs.recent_videos.items), vec!["a"]);
+        assert_eq!(related(&page("cats")), vec![("dogs", 1), ("funny", 1)]);
+
+        // Views after the retag land on the new tags only
+        watch("a", 1);
+        assert_eq!(page("dogs").total_views, 3);
+        assert_eq!(page("funny").total_views, 1);
+        assert_eq!(ids(&page("cats").top_videos), vec!["a", "b"]);
+
+        // Trashed videos drop out and come back when restored
+        let trashed = update(&a, |metadata| metadata.deleted_at = Some(50));
+        assert_eq!(page("dogs").video_count, 0);
+        assert_eq!((page("cats").video_count, page("cats").total_views), (1, 1));
+        watch("a", 1);
+        assert_eq!(page("cats").total_views, 1);
+        let a = update(&trashed, |metadata| metadata.deleted_at = None);
+        assert_eq!((page("dogs").video_count, page("dogs").total_views), (1, 4));
+        assert_eq!(related(&page("cats")), vec![("dogs", 1), ("funny", 1)]);
+
+        // Purging removes every trace of the video
+        purge(&a);
+        let cats = page("cats");
+        assert_eq!((cats.video_count, cats.total_views), (1, 1));
+        assert_eq!(ids(&cats.top_videos), vec!["b"]);
+        assert_eq!(ids(&cats.recent_videos.items), vec!["b"]);
+        assert_eq!(related(&cats), vec![("funny", 1)]);
+        let dogs = page("dogs");
+        assert_eq!((dogs.video_count, dogs.total_views), (0, 0));
+        assert!(dogs.top_videos.is_empty() && dogs.related_tags.is_empty());
+
+        purge(&b);
+        assert_eq!(TAG_STATS.with(|stats| stats.borrow().len()), 0);
+        assert_eq!(TAG_PAIRS.with(|pairs| pairs.borrow().len()), 0);
+        assert_eq!(TAG_RELATED.with(|related| related.borrow().len()), 0);
+        assert_eq!(TAG_TOP_VIDEOS.with(|top| top.borrow().len()), 0);
+        assert_eq!(TAG_RECENT_VIDEOS.with(|recent| recent.borrow().len()), 0);
+    }
+
+    #[test]
+    fn test_backfill_tag_aggregates() {
+        let metadata = test_video("a", Principal::anonymous(), &["cats", "funny"], 10);
+        VIDEOS.with(|videos| videos.borrow_mut().insert("a".to_string(), metadata));
+        record_view_stats("a", false);
+
+        backfill_tag_aggregates();
+        let cats = page("cats");
+        assert_eq!((cats.video_count, cats.total_views), (1, 1));
+        assert_eq!(related(&cats), vec![("funny", 1)]);
+
+        // Runs once only
+        backfill_tag_aggregates();
+        assert_eq!(page("cats").video_count, 1);
+    }
+
+    #[test]
+    fn test_related_tags_rank_by_shared_videos() {
+        // Many tags shared once, and the one shared most sorts last
+        let many: Vec<String> = (0..40).map(|i| format!("t{:02}", i)).collect();
+        for chunk in many.chunks(MAX_PAIRED_TAGS - 1) {
+            let mut tags = vec!["cats"];
+            tags.extend(chunk.iter().map(String::as_str));
+            create(&chunk[0], &tags, 1);
+        }
+        create("x", &["cats", "zebras"], 2);
+        create("y", &["cats", "zebras"], 3);
+
+        let cats = page("cats");
+        assert_eq!(cats.related_tags.len(), RELATED_TAGS_LIMIT);
+        assert_eq!(related(&cats)[0], ("zebras", 2));
+        assert_eq!(related(&cats)[1], ("t00", 1));
+    }
+
+    #[test]
+    fn test_only_first_tags_are_paired() {
+        let tags: Vec<String> = (0..MAX_PAIRED_TAGS + 2).map(|i| format!("t{:02}", i)).collect();
+        let tag_refs: Vec<&str> = tags.iter().map(String::as_str).collect();
+        let video = create("a", &tag_refs, 1);
+        let pairs = MAX_PAIRED_TAGS * (MAX_PAIRED_TAGS - 1);
+        assert_eq!(TAG_PAIRS.with(|pairs| pairs.borrow().len()), pairs as u64);
+        assert!(page(&tags[MAX_PAIRED_TAGS]).related_tags.is_empty());
+
+        // Moving a later tag to the front re-pairs it
+        let video = update(&video, |metadata| metadata.tags.rotate_right(1));
+        assert_eq!(related(&page(tags.last().unwrap()))[0], ("t00", 1));
+        assert_eq!(TAG_RELATED.with(|related| related.borrow().len()), pairs as u64);
+
+        purge(&video);
+        assert_eq!(TAG_RELATED.with(|related| related.borrow().len()), 0);
+    }
+}
@@ -0,0 +1,329 @@
+use candid::Principal;
+use ic_cdk::{query, update};
+use std::ops::Bound;
+
+use crate::{
+    page::{collect_page, page_size, start_after, Page},
+    service::{
+        autocomplete::relink_video_suggestions, moderation::is_moderator,
+        profile_search::resolve_mention, revisions::record_revision, search::index_video,
+        tag_pages::relink_tag_aggregates,
+    },
+    tag::{
+        extract_hashtags, extract_mentions, normalize_stored_tags, normalize_tag, validate_tags, TagAlias,
+        MAX_TAG_LENGTH,
+    },
+    video_metadata::VideoMetadata,
+    video_revision::diff_metadata,
+    TAG_ALIASES, TAG_ALIAS_TARGETS, TAG_RETAG_CURSOR, VIDEOS,
+};
+
+/// Maximum number of videos the re-tag job looks at per run
+const RETAG_BATCH_SIZE: usize = 50;
+
+/// Mentions resolved per video; later ones are ignored
+const MAX_MENTIONS: usize = 20;
+
+/// The tag videos are stored with for `tag`, after normalizing it and
+/// resolving aliases
+pub fn canonical_tag(tag: &str) -> String {
+    let tag = normalize_tag(tag);
+    TAG_ALIASES.with(|aliases| aliases.borrow().get(&tag)).unwrap_or(tag)
+}
+
+/// Validates and normalizes the tags sent for a video and replaces aliases
+/// with their canonical tags
+pub fn canonical_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    Ok(resolve_aliases(validate_tags(tags)?))
+}
+
+/// Merges `#hashtags` in the title and description into the video's tags,
+/// then normalizes them, and resolves its `@mentions` to profiles
+pub fn merge_hashtags_and_mentions(metadata: &mut VideoMetadata) -> Result<(), String> {
+    let mut tags = metadata.tags.clone();
+    tags.extend(extract_hashtags(&text_of(metadata)));
+    metadata.tags = canonical_tags(&tags)?;
+    metadata.mentions = mentioned_profiles(metadata);
+    Ok(())
+}
+
+/// Profiles @mentioned in the title or description, None if there are none
+pub fn mentioned_profiles(metadata: &VideoMetadata) -> Option<Vec<Principal>> {
+    let mut mentions: Vec<Principal> = Vec::new();
+    for principal in extract_mentions(&text_of(metadata))
+        .iter()
+        .filter_map(|mention| resolve_mention(mention))
+    {
+        if mentions.len() < MAX_MENTIONS && !mentions.contains(&principal) {
+            mentions.push(principal);
+        }
+    }
+    Some(mentions).filter(|mentions| !mentions.is_empty())
+}
+
+fn text_of(metadata: &VideoMetadata) -> String {
+    format!("{}\n{}", metadata.title, metadata.description.as_deref().unwrap_or_default())
+}
+
+fn resolve_aliases(tags: Vec<String>) -> Vec<String> {
+    let mut canonical: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = TAG_ALIASES.with(|aliases| aliases.borrow().get(&tag)).unwrap_or(tag);
+        if !canonical.contains(&tag) {
+            canonical.push(tag);
+        }
+    }
+    canonical
+}
+
+/// Makes `alias` a spelling of `canonical` (moderators only). Videos tagged
+/// with the alias are re-tagged in the background.
+#[update]
+pub fn set_tag_alias(alias: String, canonical: String) -> Result<(), String> {
+    if !is_moderator(&ic_cdk::caller()) {
+        return Err("Only moderators can manage tag aliases".to_string());
+    }
+    store_tag_alias(&alias, &canonical)
+}
+
+fn store_tag_alias(alias: &str, canonical: &str) -> Result<(), String> {
+    let alias = normalize_tag(alias);
+    let canonical = normalize_tag(canonical);
+    for tag in [&alias, &canonical] {
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(format!("Tags must be between 1 and {} characters", MAX_TAG_LENGTH));
+        }
+    }
+    if alias == canonical {
+        return Err("A tag can't be an alias of itself".to_string());
+    }
+
+    TAG_ALIASES.with(|aliases| {
+        let mut aliases = aliases.borrow_mut();
+        // Keep mappings one level deep so a single lookup always finds the canonical tag
+        if let Some(target) = aliases.get(&canonical) {
+            return Err(format!("\"{}\" is itself an alias of \"{}\"", canonical, target));
+        }
+        if has_aliases(&alias) {
+            return Err(format!("\"{}\" is the canonical tag of other aliases", alias));
+        }
+        if let Some(previous) = aliases.insert(alias.clone(), canonical.clone()) {
+            TAG_ALIAS_TARGETS.with(|targets| targets.borrow_mut().remove(&target_key(&previous, &alias)));
+        }
+        TAG_ALIAS_TARGETS.with(|targets| targets.borrow_mut().insert(target_key(&canonical, &alias), ()));
+        Ok(())
+    })?;
+
+    start_retag();
+    Ok(())
+}
+
+/// Removes an alias (moderators only), making its spelling a tag of its own
+/// again for videos tagged from now on.
+///
+/// Merges aren't undone: videos already re-tagged keep the canonical tag and
+/// no longer list under the alias spelling, since which spelling they were
+/// uploaded with isn't kept. Their uploaders can tag them again to split
+/// them off. Videos the re-tag job hasn't reached yet keep the alias.
+#[update]
+pub fn remove_tag_alias(alias: String) -> Result<(), String> {
+    if !is_moderator(&ic_cdk::caller()) {
+        return Err("Only moderators can manage tag aliases".to_string());
+    }
+    delete_tag_alias(&alias)
+}
+
+fn delete_tag_alias(alias: &str) -> Result<(), String> {
+    let alias = normalize_tag(alias);
+    let canonical = TAG_ALIASES
+        .with(|aliases| aliases.borrow_mut().remove(&alias))
+        .ok_or_else(|| "Tag alias not found".to_string())?;
+    TAG_ALIAS_TARGETS.with(|targets| targets.borrow_mut().remove(&target_key(&canonical, &alias)));
+    Ok(())
+}
+
+/// Reverse index key for an alias of `canonical`
+fn target_key(canonical: &str, alias: &str) -> String {
+    format!("{}\0{}", canonical, alias)
+}
+
+/// Whether some alias maps onto `tag`
+fn has_aliases(tag: &str) -> bool {
+    let prefix = format!("{}\0", tag);
+    TAG_ALIAS_TARGETS.with(|targets| {
+        targets
+            .borrow()
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|(key, _)| key.starts_with(&prefix))
+    })
+}
+
+/// Builds the alias reverse index for aliases stored before it existed
+pub fn backfill_tag_alias_targets() {
+    if TAG_ALIAS_TARGETS.with(|targets| !targets.borrow().is_empty()) {
+        return;
+    }
+    let aliases: Vec<(String, String)> = TAG_ALIASES.with(|aliases| aliases.borrow().iter().collect());
+    for (alias, canonical) in aliases {
+        TAG_ALIAS_TARGETS.with(|targets| targets.borrow_mut().insert(target_key(&canonical, &alias), ()));
+    }
+}
+
+/// Lists tag aliases in alphabetical order of the alias
+#[query]
+pub fn list_tag_aliases(cursor: Option<String>, limit: Option<u32>) -> Result<Page<TagAlias>, String> {
+    let start = start_after(cursor.as_deref(), "")?;
+    Ok(TAG_ALIASES.with(|aliases| {
+        let aliases = aliases.borrow();
+        let entries = aliases
+            .range((start, Bound::Unbounded))
+            .map(|(alias, canonical)| (alias.clone(), TagAlias { alias, canonical }));
+        collect_page(entries, page_size(limit))
+    }))
+}
+
+/// Starts a re-tag pass over every video, restarting one that is underway
+fn start_retag() {
+    TAG_RETAG_CURSOR.with(|cursor| {
+        cursor
+            .borrow_mut()
+            .set(Some(String::new()))
+            .expect("Failed to save re-tag cursor")
+    });
+}
+
+/// Scheduled job: normalizes the tags of a batch of videos and replaces
+/// aliases with their canonical tags. Returns true if videos are left.
+pub fn retag_videos() -> bool {
+    retag_batch(ic_cdk::id(), ic_cdk::api::time() / 1_000_000_000)
+}
+
+/// One run of the re-tag job, recording edits as made by `editor`
+fn retag_batch(editor: Principal, timestamp: u64) -> bool {
+    let Some(last_video_id) = TAG_RETAG_CURSOR.with(|cursor| cursor.borrow().get().clone()) else {
+        return false;
+    };
+
+    let start = match last_video_id.is_empty() {
+        true => Bound::Unbounded,
+        false => Bound::Excluded(last_video_id),
+    };
+    let batch: Vec<(String, VideoMetadata)> = VIDEOS.with(|videos| {
+        videos
+            .borrow()
+            .range((start, Bound::Unbounded))
+            .take(RETAG_BATCH_SIZE + 1)
+            .collect()
+    });
+    let has_more = batch.len() > RETAG_BATCH_SIZE;
+
+    for (_, metadata) in batch.iter().take(RETAG_BATCH_SIZE) {
+        retag_video(metadata, editor, timestamp);
+    }
+
+    let next_cursor = match has_more {
+        true => batch.get(RETAG_BATCH_SIZE - 1).map(|(video_id, _)| video_id.clone()),
+        false => None,
+    };
+    TAG_RETAG_CURSOR.with(|cursor| {
+        cursor
+            .borrow_mut()
+            .set(next_cursor)
+            .expect("Failed to save re-tag cursor")
+    });
+    has_more
+}
+
+fn retag_video(before: &VideoMetadata, editor: Principal, timestamp: u64) {
+    let tags = resolve_aliases(normalize_stored_tags(&before.tags));
+    if tags == before.tags {
+        return;
+    }
+
+    let mut metadata = before.clone();
+    metadata.tags = tags;
+    VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata.clone()));
+    index_video(&metadata);
+    relink_video_suggestions(Some(before), Some(&metadata));
+    relink_tag_aggregates(Some(before), Some(&metadata));
+    record_revision(&metadata.video_id, editor, timestamp, diff_metadata(before, &metadata), None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{page::Page, service::video::list_videos_by_tag, video_metadata::test_video};
+
+    fn upload(video_id: &str, tags: &[&str], timestamp: u64) {
+        let mut metadata = test_video(video_id, Principal::anonymous(), &[], timestamp);
+        metadata.tags = canonical_tags(&tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>()).unwrap();
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        relink_tag_aggregates(None, Some(&metadata));
+    }
+
+    fn tagged(tag: &str) -> Vec<String> {
+        let page: Page<VideoMetadata> = list_videos_by_tag(tag.to_string(), None, None).unwrap();
+        page.items.into_iter().map(|metadata| metadata.video_id).collect()
+    }
+
+    #[test]
+    fn test_removed_alias_keeps_merged_videos_canonical() {
+        upload("a", &["kitten"], 1);
+        upload("b", &["cat"], 2);
+        store_tag_alias("Kitten", "cat").unwrap();
+        while retag_batch(Principal::anonymous(), 3) {}
+        assert_eq!(tagged("kitten"), vec!["b", "a"]);
+
+        delete_tag_alias("kitten").unwrap();
+        assert_eq!(delete_tag_alias("kitten"), Err("Tag alias not found".to_string()));
+
+        // The merge stands: "a" stays under the canonical tag only
+        assert_eq!(tagged("cat"), vec!["b", "a"]);
+        assert!(tagged("kitten").is_empty());
+
+        // The spelling is a tag of its own again for new videos
+        upload("c", &["kitten"], 4);
+        assert_eq!(tagged("kitten"), vec!["c"]);
+        assert_eq!(tagged("cat"), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_aliases_stay_one_level_deep() {
+        store_tag_alias("kitten", "cat").unwrap();
+        assert_eq!(
+            store_tag_alias("kitty", "kitten"),
+            Err("\"kitten\" is itself an alias of \"cat\"".to_string())
+        );
+        assert_eq!(
+            store_tag_alias("cat", "feline"),
+            Err("\"cat\" is the canonical tag of other aliases".to_string())
+        );
+
+        // Re-pointing the only alias frees its old target
+        store_tag_alias("kitten", "kitty").unwrap();
+        store_tag_alias("cat", "feline").unwrap();
+        assert_eq!(canonical_tag("Cat"), "feline");
+
+        // As does removing it
+        assert!(store_tag_alias("kitty", "feline").is_err());
+        delete_tag_alias("kitten").unwrap();
+        store_tag_alias("kitty", "feline").unwrap();
+        assert_eq!(canonical_tag("kitty"), "feline");
+    }
+
+    #[test]
+    fn test_list_tag_aliases_pages_in_alias_order() {
+        store_tag_alias("kitty", "cat").unwrap();
+        store_tag_alias("doggo", "dog").unwrap();
+        store_tag_alias("kitten", "cat").unwrap();
+
+        let first = list_tag_aliases(None, Some(2)).unwrap();
+        let aliases: Vec<&str> = first.items.iter().map(|alias| alias.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["doggo", "kitten"]);
+
+        let second = list_tag_aliases(first.next_cursor, Some(2)).unwrap();
+        assert_eq!(second.items, vec![TagAlias { alias: "kitty".to_string(), canonical: "cat".to_string() }]);
+        assert_eq!(second.next_cursor, None);
+    }
+}
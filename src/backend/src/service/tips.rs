@@ -1,15 +1,39 @@
+use candid::Principal;
 use ic_cdk::{query, update};
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+use std::ops::Bound;
+use std::thread::LocalKey;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
-    tip_record::{TipRecord, TipRecordList}, 
-    TIP_RECORDS, 
+    credit::{split_amount, TOTAL_SHARE_BPS},
+    page::{collect_page, next_log_key, owner_key, page_size, start_after, Page},
+    tip_record::TipRecord,
+    Memory,
+    LEGACY_TIP_RECORDS,
+    TIP_RECORDS,
+    TIP_SPLITS,
+    TIPS_BY_RECIPIENT,
+    TIPS_BY_SENDER,
     VIDEOS, 
     USER_PROFILES,
-    service::save_my_profile::get_address
+    video_metadata::VideoMetadata,
+    service::{save_my_profile::get_address, video_stats::record_tip_stats},
 };
 
-/// Records a tip transaction for a video
+/// Replaces tipper and creator addresses and the transaction hash on tips of
+/// purged videos; the hash would identify the addresses on chain
+pub const REDACTED: &str = "redacted";
+
+/// Records a tip transaction for a video.
+///
+/// The tip is split between the uploader and the co-creators who accepted a
+/// credit, one entry per recipient. Co-creators without a profile address
+/// can't be credited, so their share stays with the uploader.
+///
+/// Returns the uploader's entry, or the first co-creator's if co-creators
+/// take the whole tip. `get_tip_splits` lists every entry.
 #[update]
 pub async fn record_tip(
     video_id: String,
@@ -17,25 +41,14 @@ pub async fn record_tip(
     tx_hash: String
 ) -> Result<TipRecord, String> {
     // Verify the video exists
-    let to_addr = VIDEOS.with(|videos| {
-        let videos_map = videos.borrow();
-        if !videos_map.contains_key(&video_id) {
-            return Err("Video not found".to_string());
-        }
-        
-        // Get the video's uploader principal
-        let uploader_principal = videos_map.get(&video_id).unwrap().uploader_principal;
-        
-        // Look up the uploader's address from user profiles
-        USER_PROFILES.with(|profiles| {
-            profiles
-                .borrow()
-                .get(&uploader_principal.to_string())
-                .map(|profile| profile.evm_address.clone())
-                .ok_or("Video uploader has no profile with EVM address".to_string())
-        })
+    let metadata = VIDEOS.with(|videos| {
+        videos
+            .borrow()
+            .get(&video_id)
+            .filter(|metadata| !metadata.is_deleted())
+            .ok_or_else(|| "Video not found".to_string())
     })?;
-    
+
     // Get the tipper's address
     let from_addr = get_address().await?;
     
@@ -45,67 +58,278 @@ pub async fn record_tip(
         .expect("Time went backwards")
         .as_secs();
     
-    // Create tip record
-    let tip = TipRecord {
-        from_addr,
-        to_addr,
-        video_id: video_id.clone(),
-        amount,
-        tx_hash,
-        timestamp,
-    };
+    let tips = split_tip(&metadata, amount, &tx_hash, &from_addr, timestamp)?;
     
-    // Store tip record
-    TIP_RECORDS.with(|tips| {
-        let mut tips_map = tips.borrow_mut();
-        let tip_records = match tips_map.get(&video_id) {
-            Some(record_list) => {
-                let mut records = record_list.0.clone();
-                records.push(tip.clone());
-                TipRecordList(records)
-            }
-            None => TipRecordList(vec![tip.clone()]),
+    // Store tip records
+    for tip in &tips {
+        store_tip(tip);
+    }
+    record_tip_stats(&video_id, &tips);
+    tips.into_iter()
+        .next()
+        .ok_or_else(|| "Tip has no recipients".to_string())
+}
+
+/// Builds one tip entry per recipient: the uploader and the co-creators with
+/// an accepted credit, each getting their share of `amount`. Addresses come
+/// from user profiles.
+pub fn split_tip(
+    metadata: &VideoMetadata,
+    amount: u64,
+    tx_hash: &str,
+    from_addr: &str,
+    timestamp: u64,
+) -> Result<Vec<TipRecord>, String> {
+    let address_of = |principal: &Principal| {
+        USER_PROFILES.with(|profiles| {
+            profiles
+                .borrow()
+                .get(&principal.to_string())
+                .map(|profile| profile.evm_address.clone())
+        })
+    };
+    let uploader_addr = address_of(&metadata.uploader_principal)
+        .ok_or("Video uploader has no profile with EVM address".to_string())?;
+    let co_creators: Vec<(String, u32)> = metadata
+        .accepted_credits()
+        .filter_map(|credit| address_of(&credit.principal).map(|addr| (addr, credit.share_bps)))
+        .collect();
+
+    let shares: Vec<u32> = co_creators.iter().map(|(_, share_bps)| *share_bps).collect();
+    let (amounts, uploader_amount) = split_amount(amount, &shares);
+    let uploader_share_bps = TOTAL_SHARE_BPS - shares.iter().sum::<u32>();
+    let recipients = std::iter::once((uploader_addr, uploader_share_bps, uploader_amount)).chain(
+        co_creators
+            .into_iter()
+            .zip(amounts)
+            .map(|((addr, share_bps), amount)| (addr, share_bps, amount)),
+    );
+    Ok(recipients
+        .filter(|(_, share_bps, _)| *share_bps > 0)
+        .map(|(to_addr, share_bps, amount)| TipRecord {
+            from_addr: from_addr.to_string(),
+            to_addr,
+            video_id: metadata.video_id.clone(),
+            amount,
+            tx_hash: tx_hash.to_string(),
+            timestamp,
+            share_bps: Some(share_bps),
+        })
+        .collect())
+}
+
+/// Adds a tip entry to its video's tips and the sender's and recipient's indexes
+pub fn store_tip(tip: &TipRecord) {
+    let key = TIP_RECORDS.with(|tips| {
+        let mut tips = tips.borrow_mut();
+        let key = next_log_key(&tips, &tip.video_id, tip.timestamp);
+        tips.insert(key.clone(), tip.clone());
+        key
+    });
+    index_tip(&key, tip);
+}
+
+fn index_tip(key: &str, tip: &TipRecord) {
+    // Tips of purged videos have no addresses left to index
+    if tip.from_addr == REDACTED {
+        return;
+    }
+    TIPS_BY_SENDER.with(|index| {
+        index
+            .borrow_mut()
+            .insert(owner_key(&tip.from_addr, tip.timestamp, key), key.to_string())
+    });
+    TIPS_BY_RECIPIENT.with(|index| {
+        index
+            .borrow_mut()
+            .insert(owner_key(&tip.to_addr, tip.timestamp, key), key.to_string())
+    });
+    TIP_SPLITS.with(|index| index.borrow_mut().insert(split_key(tip, key), key.to_string()));
+}
+
+/// Key of a tip entry in TIP_SPLITS
+fn split_key(tip: &TipRecord, key: &str) -> String {
+    format!("{}:{}:{}", tip.video_id, tip.tx_hash, key)
+}
+
+/// Replaces the addresses and transaction hash on up to `limit` of a video's
+/// tips and drops them from the address indexes. The amounts are kept for the
+/// records. Returns how many tips were redacted.
+pub fn redact_tips_for_video(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    let tips: Vec<(String, TipRecord)> = TIP_RECORDS.with(|tips| {
+        tips.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter(|(_, tip)| tip.tx_hash != REDACTED)
+            .take(limit)
+            .collect()
+    });
+    let redacted = tips.len();
+    for (key, mut tip) in tips {
+        TIPS_BY_SENDER.with(|index| index.borrow_mut().remove(&owner_key(&tip.from_addr, tip.timestamp, &key)));
+        TIPS_BY_RECIPIENT.with(|index| index.borrow_mut().remove(&owner_key(&tip.to_addr, tip.timestamp, &key)));
+        TIP_SPLITS.with(|index| index.borrow_mut().remove(&split_key(&tip, &key)));
+        tip.from_addr = REDACTED.to_string();
+        tip.to_addr = REDACTED.to_string();
+        tip.tx_hash = REDACTED.to_string();
+        TIP_RECORDS.with(|tips| tips.borrow_mut().insert(key, tip));
+    }
+    redacted
+}
+
+/// Moves tips stored as per-video lists into the keyed tips map
+pub fn migrate_legacy_tip_records() {
+    let video_ids: Vec<String> = LEGACY_TIP_RECORDS.with(|legacy| legacy.borrow().iter().map(|(id, _)| id).collect());
+    for video_id in video_ids {
+        let Some(record_list) = LEGACY_TIP_RECORDS.with(|legacy| legacy.borrow_mut().remove(&video_id)) else {
+            continue;
         };
-        tips_map.insert(video_id, tip_records);
-        Ok(tip)
-    })
+        for tip in &record_list.0 {
+            store_tip(tip);
+        }
+    }
+}
+
+/// Indexes keyed tips stored before they were indexed by transaction
+pub fn backfill_tip_splits() {
+    if TIP_SPLITS.with(|index| !index.borrow().is_empty()) {
+        return;
+    }
+    let tips: Vec<(String, TipRecord)> = TIP_RECORDS.with(|tips| tips.borrow().iter().collect());
+    for (key, tip) in tips.iter().filter(|(_, tip)| tip.tx_hash != REDACTED) {
+        TIP_SPLITS.with(|index| index.borrow_mut().insert(split_key(tip, key), key.clone()));
+    }
 }
 
-/// Gets tips for a specific video
+/// Lists the recipients' entries for a tip transaction on a video
 #[query]
-pub fn get_tips_for_video(video_id: String) -> Vec<TipRecord> {
-    TIP_RECORDS.with(|tips| {
-        tips.borrow()
-            .get(&video_id)
-            .map(|record_list| record_list.0.clone())
-            .unwrap_or_default()
-    })
+pub fn get_tip_splits(
+    video_id: String,
+    tx_hash: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<TipRecord>, String> {
+    let prefix = format!("{}:{}:", video_id, tx_hash);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(TIP_SPLITS.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, tip_key)| TIP_RECORDS.with(|tips| tips.borrow().get(&tip_key)).map(|tip| (key, tip)));
+        collect_page(entries, page_size(limit))
+    }))
 }
 
-/// Gets all tips sent by the calling user
+/// Gets tips for a specific video, oldest first
 #[query]
-pub async fn get_my_sent_tips() -> Result<Vec<TipRecord>, String> {
-    let my_addr = get_address().await?;
-    
+pub fn get_tips_for_video(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<TipRecord>, String> {
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
     Ok(TIP_RECORDS.with(|tips| {
-        tips.borrow()
-            .iter()
-            .flat_map(|(_, record_list)| record_list.0.clone())
-            .filter(|record| record.from_addr == my_addr)
-            .collect()
+        let tips = tips.borrow();
+        let entries = tips
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix));
+        collect_page(entries, page_size(limit))
     }))
 }
 
-/// Gets all tips received by the calling user
+/// Pages through the tips an address index lists for `address`
+fn tips_page(
+    index: &'static LocalKey<RefCell<StableBTreeMap<String, String, Memory>>>,
+    address: &str,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<TipRecord>, String> {
+    let prefix = format!("{}:", address);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(index.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, tip_key)| TIP_RECORDS.with(|tips| tips.borrow().get(&tip_key)).map(|tip| (key, tip)));
+        collect_page(entries, page_size(limit))
+    }))
+}
+
+/// Gets tips sent by the calling user, oldest first
 #[query]
-pub async fn get_my_received_tips() -> Result<Vec<TipRecord>, String> {
+pub async fn get_my_sent_tips(cursor: Option<String>, limit: Option<u32>) -> Result<Page<TipRecord>, String> {
     let my_addr = get_address().await?;
-    
-    Ok(TIP_RECORDS.with(|tips| {
-        tips.borrow()
-            .iter()
-            .flat_map(|(_, record_list)| record_list.0.clone())
-            .filter(|record| record.to_addr == my_addr)
-            .collect()
-    }))
-}
\ No newline at end of file
+    tips_page(&TIPS_BY_SENDER, &my_addr, cursor, limit)
+}
+
+/// Gets tips received by the calling user, oldest first
+#[query]
+pub async fn get_my_received_tips(cursor: Option<String>, limit: Option<u32>) -> Result<Page<TipRecord>, String> {
+    let my_addr = get_address().await?;
+    tips_page(&TIPS_BY_RECIPIENT, &my_addr, cursor, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tip_record::TipRecordList;
+
+    fn tip(to_addr: &str, tx_hash: &str, timestamp: u64) -> TipRecord {
+        TipRecord {
+            from_addr: "0xfan".to_string(),
+            to_addr: to_addr.to_string(),
+            video_id: "video1".to_string(),
+            amount: 50,
+            tx_hash: tx_hash.to_string(),
+            timestamp,
+            share_bps: Some(5_000),
+        }
+    }
+
+    #[test]
+    fn test_tips_by_address() {
+        store_tip(&tip("0xcreator", "0x1", 10));
+        store_tip(&tip("0xguest", "0x1", 10));
+        store_tip(&tip("0xcreator", "0x2", 20));
+
+        let splits = get_tip_splits("video1".to_string(), "0x1".to_string(), None, Some(1)).unwrap();
+        assert_eq!(splits.items, vec![tip("0xcreator", "0x1", 10)]);
+        let rest = get_tip_splits("video1".to_string(), "0x1".to_string(), splits.next_cursor, None).unwrap();
+        assert_eq!(rest.items, vec![tip("0xguest", "0x1", 10)]);
+        assert_eq!(rest.next_cursor, None);
+        let sent = tips_page(&TIPS_BY_SENDER, "0xfan", None, Some(2)).unwrap();
+        assert_eq!(sent.items.len(), 2);
+        let rest = tips_page(&TIPS_BY_SENDER, "0xfan", sent.next_cursor, Some(2)).unwrap();
+        assert_eq!(rest.items, vec![tip("0xcreator", "0x2", 20)]);
+        let received = tips_page(&TIPS_BY_RECIPIENT, "0xcreator", None, None).unwrap();
+        assert_eq!(received.items.iter().map(|t| t.timestamp).collect::<Vec<_>>(), vec![10, 20]);
+
+        redact_tips_for_video("video1", usize::MAX);
+        assert!(tips_page(&TIPS_BY_SENDER, "0xfan", None, None).unwrap().items.is_empty());
+        assert!(tips_page(&TIPS_BY_RECIPIENT, "0xguest", None, None).unwrap().items.is_empty());
+        assert_eq!(TIP_SPLITS.with(|index| index.borrow().len()), 0);
+        assert_eq!(get_tips_for_video("video1".to_string(), None, None).unwrap().items.len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_legacy_tips() {
+        let mut redacted = tip(REDACTED, REDACTED, 5);
+        redacted.from_addr = REDACTED.to_string();
+        let legacy = vec![redacted, tip("0xcreator", "0x1", 10)];
+        LEGACY_TIP_RECORDS.with(|tips| tips.borrow_mut().insert("video1".to_string(), TipRecordList(legacy.clone())));
+
+        migrate_legacy_tip_records();
+
+        assert_eq!(get_tips_for_video("video1".to_string(), None, None).unwrap().items, legacy);
+        // Redacted tips stay out of the address indexes
+        assert_eq!(TIPS_BY_SENDER.with(|index| index.borrow().len()), 1);
+        assert!(tips_page(&TIPS_BY_SENDER, REDACTED, None, None).unwrap().items.is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use ic_cdk::query;
+use std::{cell::RefCell, ops::Bound};
+
+use crate::{
+    trending::{top_tags, TagActivity, TrendingBoards, TrendingTag, TrendingTags, TrendingWindow},
+    video_metadata::VideoMetadata,
+    TAG_ACTIVITY, TRENDING_TAGS, VIDEOS, VIDEO_ACTIVITY,
+};
+
+const UPLOAD_WEIGHT: f64 = 5.0;
+const VIEW_WEIGHT: f64 = 1.0;
+
+/// Tags kept per leaderboard
+const LEADERBOARD_SIZE: usize = 50;
+const DEFAULT_TRENDING_LIMIT: u32 = 10;
+
+/// Tags scored per refresh run
+const REFRESH_BATCH_SIZE: usize = 500;
+
+/// Tags whose week score decayed below this are forgotten
+const MIN_SCORE: f64 = 0.01;
+
+/// A refresh spread over several runs
+struct Refresh {
+    now: u64,
+    last_tag: Option<String>,
+    boards: Vec<Vec<TrendingTag>>,
+}
+
+thread_local! {
+    // Refresh in progress; lost on upgrade, after which the next run starts over
+    static REFRESH: RefCell<Option<Refresh>> = const { RefCell::new(None) };
+}
+
+/// Tags with the most recent uploads and views, as of the last refresh.
+/// Activity decays exponentially over the chosen window.
+#[query]
+pub fn get_trending_tags(window: TrendingWindow, limit: Option<u32>) -> TrendingTags {
+    let limit = limit.unwrap_or(DEFAULT_TRENDING_LIMIT).clamp(1, LEADERBOARD_SIZE as u32) as usize;
+    TRENDING_TAGS.with(|boards| boards.borrow().get().for_window(window, limit))
+}
+
+/// Counts a new upload towards its tags, as of its upload time
+pub fn record_upload_activity(metadata: &VideoMetadata) {
+    record_video_activity(metadata, UPLOAD_WEIGHT, metadata.timestamp);
+}
+
+/// Counts a view towards the video's tags, unless the video is in the trash
+pub fn record_view_activity(video_id: &str, now: u64) {
+    let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id.to_string()));
+    if let Some(metadata) = metadata.filter(|m| !m.is_deleted()) {
+        record_video_activity(&metadata, VIEW_WEIGHT, now);
+    }
+}
+
+fn record_video_activity(metadata: &VideoMetadata, weight: f64, now: u64) {
+    let mut activity = TagActivity::default();
+    activity.record(weight, now);
+    VIDEO_ACTIVITY.with(|videos| {
+        let mut videos = videos.borrow_mut();
+        let mut video_activity = videos.get(&metadata.video_id).unwrap_or_default();
+        video_activity.merge(&activity, 1.0, now);
+        videos.insert(metadata.video_id.clone(), video_activity);
+    });
+    merge_tag_activity(&metadata.tags, &activity, 1.0, now);
+}
+
+/// Moves a video's activity between tags when it is edited, trashed,
+/// restored or purged. Videos in the trash count for no tags.
+pub fn relink_tag_activity(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>, now: u64) {
+    let tags_of = |metadata: Option<&VideoMetadata>| {
+        metadata
+            .filter(|m| !m.is_deleted())
+            .map(|m| m.tags.clone())
+            .unwrap_or_default()
+    };
+    let (before_tags, after_tags) = (tags_of(before), tags_of(after));
+    let Some(video_id) = before.or(after).map(|m| m.video_id.clone()) else {
+        return;
+    };
+    let activity = if after.is_some() {
+        VIDEO_ACTIVITY.with(|videos| videos.borrow().get(&video_id))
+    } else {
+        VIDEO_ACTIVITY.with(|videos| videos.borrow_mut().remove(&video_id))
+    };
+    let Some(activity) = activity else {
+        return;
+    };
+    if before_tags != after_tags {
+        merge_tag_activity(&before_tags, &activity, -1.0, now);
+        merge_tag_activity(&after_tags, &activity, 1.0, now);
+    }
+}
+
+fn merge_tag_activity(tags: &[String], activity: &TagActivity, sign: f64, now: u64) {
+    TAG_ACTIVITY.with(|tag_activity| {
+        let mut tag_activity = tag_activity.borrow_mut();
+        for tag in tags {
+            let mut merged = tag_activity.get(tag).unwrap_or_default();
+            merged.merge(activity, sign, now);
+            tag_activity.insert(tag.clone(), merged);
+        }
+    });
+}
+
+/// Scheduled job: scores a batch of tags towards new leaderboards and
+/// publishes them once every tag is scored. Returns true if tags are left.
+pub fn refresh_trending_tags() -> bool {
+    refresh_trending_tags_at(ic_cdk::api::time() / 1_000_000_000)
+}
+
+/// Runs one refresh batch; `now` only matters when a new refresh starts
+fn refresh_trending_tags_at(now: u64) -> bool {
+    let mut refresh = REFRESH.with(|refresh| refresh.borrow_mut().take()).unwrap_or_else(|| Refresh {
+        now,
+        last_tag: None,
+        boards: vec![Vec::new(); TrendingWindow::ALL.len()],
+    });
+
+    let start = match refresh.last_tag.take() {
+        Some(tag) => Bound::Excluded(tag),
+        None => Bound::Unbounded,
+    };
+    let batch: Vec<(String, TagActivity)> = TAG_ACTIVITY.with(|activity| {
+        activity
+            .borrow()
+            .range((start, Bound::Unbounded))
+            .take(REFRESH_BATCH_SIZE + 1)
+            .collect()
+    });
+    let has_more = batch.len() > REFRESH_BATCH_SIZE;
+
+    let mut forgotten = Vec::new();
+    for (tag, activity) in batch.into_iter().take(REFRESH_BATCH_SIZE) {
+        let scores = activity.scores_at(refresh.now);
+        if scores.iter().all(|score| *score < MIN_SCORE) {
+            forgotten.push(tag.clone());
+        } else {
+            for (board, score) in refresh.boards.iter_mut().zip(scores) {
+                board.push(TrendingTag {
+                    tag: tag.clone(),
+                    score,
+                });
+            }
+        }
+        refresh.last_tag = Some(tag);
+    }
+    for board in refresh.boards.iter_mut() {
+        *board = top_tags(std::mem::take(board), LEADERBOARD_SIZE);
+    }
+    TAG_ACTIVITY.with(|activity| {
+        let mut activity = activity.borrow_mut();
+        for tag in &forgotten {
+            activity.remove(tag);
+        }
+    });
+
+    if has_more {
+        REFRESH.with(|current| *current.borrow_mut() = Some(refresh));
+        return true;
+    }
+
+    let boards = TrendingBoards {
+        boards: refresh.boards,
+        refreshed_at: refresh.now,
+    };
+    TRENDING_TAGS.with(|current| {
+        current
+            .borrow_mut()
+            .set(boards)
+            .expect("Failed to save trending tags")
+    });
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_metadata::test_video;
+    use candid::Principal;
+
+    const NOW: u64 = 10_000_000;
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn create(video_id: &str, tags: &[&str], timestamp: u64) -> VideoMetadata {
+        let metadata = test_video(video_id, Principal::anonymous(), tags, timestamp);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.to_string(), metadata.clone()));
+        record_upload_activity(&metadata);
+        metadata
+    }
+
+    fn update(before: &VideoMetadata, now: u64, edit: impl FnOnce(&mut VideoMetadata)) -> VideoMetadata {
+        let mut metadata = before.clone();
+        edit(&mut metadata);
+        VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata.clone()));
+        relink_tag_activity(Some(before), Some(&metadata), now);
+        metadata
+    }
+
+    fn trending(window: TrendingWindow) -> Vec<String> {
+        get_trending_tags(window, None).tags.into_iter().map(|tag| tag.tag).collect()
+    }
+
+    #[test]
+    fn test_trending_ranks_recent_activity_per_window() {
+        // A burst of views three days ago against a single fresh upload
+        create("old", &["retro"], NOW - 3 * DAY);
+        for _ in 0..10 {
+            record_view_activity("old", NOW - 3 * DAY);
+        }
+        create("new", &["fresh"], NOW - 60);
+        assert!(trending(TrendingWindow::Day).is_empty());
+
+        assert!(!refresh_trending_tags_at(NOW));
+        assert_eq!(get_trending_tags(TrendingWindow::Day, None).refreshed_at, NOW);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["fresh", "retro"]);
+        assert_eq!(trending(TrendingWindow::Day), vec!["fresh", "retro"]);
+        assert_eq!(trending(TrendingWindow::Week), vec!["retro", "fresh"]);
+        assert_eq!(get_trending_tags(TrendingWindow::Week, Some(1)).tags.len(), 1);
+
+        let week = get_trending_tags(TrendingWindow::Week, None).tags;
+        assert!((week[0].score - 15.0 * (-3.0f64 / 7.0).exp()).abs() < 1e-9);
+
+        // Activity long past every window is forgotten
+        assert!(!refresh_trending_tags_at(NOW + 100 * 7 * DAY));
+        assert!(trending(TrendingWindow::Week).is_empty());
+        assert!(TAG_ACTIVITY.with(|activity| activity.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_trashed_videos_leave_trending() {
+        let cats = create("a", &["cats"], NOW - 60);
+        create("b", &["dogs"], NOW - 60);
+        for _ in 0..3 {
+            record_view_activity("a", NOW - 30);
+        }
+        refresh_trending_tags_at(NOW);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["cats", "dogs"]);
+
+        // Its upload and earlier views are taken back, and new views ignored
+        let trashed = update(&cats, NOW, |m| m.deleted_at = Some(NOW));
+        record_view_activity("a", NOW);
+        refresh_trending_tags_at(NOW + 1);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["dogs"]);
+
+        // Restoring brings the activity back as of when it happened
+        let restored = update(&trashed, NOW + 60, |m| m.deleted_at = None);
+        refresh_trending_tags_at(NOW + 60);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["cats", "dogs"]);
+        let cats_score = get_trending_tags(TrendingWindow::Hour, None).tags[0].score;
+        assert!((cats_score - 8.0 * (-2.0f64 / 60.0).exp()).abs() < 0.1);
+
+        // Retagging moves it, purging drops it
+        let retagged = update(&restored, NOW + 60, |m| m.tags = vec!["kittens".to_string()]);
+        refresh_trending_tags_at(NOW + 60);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["kittens", "dogs"]);
+        relink_tag_activity(Some(&retagged), None, NOW + 60);
+        refresh_trending_tags_at(NOW + 60);
+        assert_eq!(trending(TrendingWindow::Hour), vec!["dogs"]);
+        assert!(VIDEO_ACTIVITY.with(|activity| activity.borrow().get(&"a".to_string()).is_none()));
+    }
+}
@@ -1,38 +1,278 @@
 use candid::Principal;
-use ic_cdk::{query, update};
+use ic_cdk::{api::management_canister::main::raw_rand, query, update};
+use std::ops::Bound;
 // Removed unused imports
 
-use crate::{video_metadata::VideoMetadata, VIDEOS};
+use crate::{
+    content_hash::normalize_content_hash,
+    page::{collect_page, page_size, start_after, Page},
+    processing_status::ProcessingStatus,
+    service::{
+        autocomplete::relink_video_suggestions,
+        moderation::is_moderator,
+        captions::remove_captions_for_video,
+        channel::unpin_purged_video,
+        clips::{clip_ids_of, is_listed_with_source, remove_clip_link},
+        comments::remove_comments_for_video,
+        credits::relink_credits,
+        duplicates::{check_duplicate, record_upload, relink_content_hash, remove_from_duplicate_index},
+        playlists::remove_video_from_playlists,
+        profile_search::relink_profile_video_count,
+        remixes::{record_remix, remove_remix_links, validate_remix_source},
+        sounds::{relink_sound, validate_sound_id},
+        revisions::{record_revision, remove_revisions_for_video},
+        search::{index_video, remove_from_search_index},
+        tag_pages::{recent_videos, relink_tag_aggregates},
+        trending::{record_upload_activity, relink_tag_activity},
+        tags::{canonical_tag, merge_hashtags_and_mentions},
+        tips::redact_tips_for_video,
+        video_stats::{relink_video_rankings, remove_video_stats},
+        watch::remove_watch_events_for_video,
+        webhooks::{check_playback_id, relink_playback_id},
+    },
+    storage_ref::{validate_renditions, Rendition, StorageRef, StorageRefUpdate},
+    video_metadata::{CreateVideoRequest, VideoDetails, VideoMetadata},
+    video_revision::diff_metadata,
+    UPLOADER_VIDEOS, VIDEOS, VIDEO_IDEMPOTENCY_KEYS, VIDEO_ID_STATE, VIDEO_TRASH,
+};
 
-/// Creates a new video metadata entry
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 64;
+
+/// How long a deleted video can be restored before it is purged
+const TRASH_GRACE_PERIOD_SEC: u64 = 30 * 24 * 60 * 60;
+
+/// Maximum number of videos purged per scheduler run
+const PURGE_BATCH_SIZE: usize = 10;
+
+/// Maximum number of entries attached to trashed videos (comments, watch
+/// events, tips, revisions, captions, playlist entries and clips) removed per
+/// scheduler run; a video with more is finished by the following runs
+const PURGE_ENTRY_BUDGET: usize = 1_000;
+
+/// Creates a new video metadata entry with a canister-generated video ID.
+///
+/// Sending the same `idempotency_key` again (from the same caller) returns the
+/// video created by the first call instead of creating a duplicate.
+///
+/// A file whose `content_hash` was uploaded before is rejected or flagged for
+/// moderators, depending on the duplicate policy. A `remix_of` source must
+/// exist and allow remixes.
 #[update]
-pub fn create_video_metadata(
-    video_id: String,
-    title: String,
-    tags: Vec<String>,
-    storage_ref: Option<String>,
-) -> Result<VideoMetadata, String> {
-    // Generate timestamp using IC time instead of SystemTime
-    let timestamp = ic_cdk::api::time() / 1_000_000_000; // Convert nanoseconds to seconds
+pub async fn create_video_metadata(request: CreateVideoRequest) -> Result<VideoMetadata, String> {
+    let CreateVideoRequest {
+        title,
+        tags,
+        storage_ref,
+        idempotency_key,
+        details,
+        content_hash,
+        remix_of,
+        renditions,
+    } = request;
+    let caller = ic_cdk::caller();
+    let renditions = renditions.unwrap_or_default();
+
+    if let Some(storage_ref) = &storage_ref {
+        storage_ref.validate()?;
+    }
+    validate_renditions(&renditions)?;
 
-    // Create metadata
-    let metadata = VideoMetadata {
-        video_id: video_id.clone(),
-        uploader_principal: ic_cdk::caller(),
+    // Livepeer uploads are transcoded before they can be played, the webhook marks them ready
+    let processing_status = match storage_ref {
+        Some(StorageRef::Livepeer { .. }) => ProcessingStatus::Uploading,
+        _ => ProcessingStatus::Ready,
+    };
+
+    let details = details.unwrap_or_default();
+    validate_details_sound(&details)?;
+
+    // Build the metadata up front so the details are validated before an ID is minted
+    let mut metadata = VideoMetadata {
+        video_id: String::new(),
+        uploader_principal: caller,
         tags,
         title,
         storage_ref,
-        timestamp,
+        renditions,
+        timestamp: 0,
+        deleted_at: None,
+        processing_status: Some(processing_status),
+        captions: None,
+        description: None,
+        duration_sec: None,
+        chapters: None,
+        aspect_ratio: None,
+        primary_language: None,
+        content_rating: None,
+        allow_comments: None,
+        content_hash: None,
+        remix_of: None,
+        allow_remixes: None,
+        sound_id: None,
+        credits: None,
+        clip_of: None,
+        mentions: None,
+        chapters_from_description: None,
+    };
+    metadata.apply_details(details)?;
+    merge_hashtags_and_mentions(&mut metadata)?;
+    metadata.content_hash = content_hash.as_deref().map(normalize_content_hash).transpose()?;
+    if let Some(source) = &remix_of {
+        validate_remix_source(source)?;
+    }
+    metadata.remix_of = remix_of;
+
+    let idempotency_entry = match idempotency_key {
+        Some(key) => {
+            if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+                return Err(format!(
+                    "Idempotency key must be between 1 and {} characters",
+                    MAX_IDEMPOTENCY_KEY_LENGTH
+                ));
+            }
+            let entry = format!("{}:{}", caller, key);
+            if let Some(existing) = find_idempotent_video(&entry) {
+                return Ok(existing);
+            }
+            Some(entry)
+        }
+        None => None,
+    };
+
+    ensure_video_id_salt().await?;
+
+    // Another call with the same key may have completed while we were waiting on raw_rand
+    if let Some(existing) = idempotency_entry.as_deref().and_then(find_idempotent_video) {
+        return Ok(existing);
+    }
+
+    check_playback_id(&metadata)?;
+    let duplicate_of = match &metadata.content_hash {
+        Some(content_hash) => check_duplicate(content_hash)?,
+        None => None,
     };
 
+    // Generate timestamp using IC time instead of SystemTime
+    let timestamp = ic_cdk::api::time() / 1_000_000_000; // Convert nanoseconds to seconds
+
+    let video_id = mint_video_id();
+    metadata.video_id = video_id.clone();
+    metadata.timestamp = timestamp;
+
     // Store it
     VIDEOS.with(|videos| {
-        let mut videos_map = videos.borrow_mut();
-        if videos_map.contains_key(&video_id) {
-            return Err("Video ID already exists".to_string());
+        videos.borrow_mut().insert(video_id.clone(), metadata.clone());
+    });
+    record_uploader_video(&metadata);
+    relink_profile_video_count(None, Some(&metadata));
+    relink_video_rankings(None, Some(&metadata));
+    relink_playback_id(None, Some(&metadata));
+
+    if let Some(content_hash) = &metadata.content_hash {
+        record_upload(content_hash, &video_id, duplicate_of, caller, timestamp);
+    }
+
+    record_remix(&metadata);
+    relink_sound(None, Some(&metadata), timestamp);
+    index_video(&metadata);
+    relink_video_suggestions(None, Some(&metadata));
+    relink_tag_aggregates(None, Some(&metadata));
+    record_upload_activity(&metadata);
+
+    if let Some(entry) = idempotency_entry {
+        VIDEO_IDEMPOTENCY_KEYS.with(|keys| {
+            keys.borrow_mut().insert(entry, video_id);
+        });
+    }
+
+    Ok(metadata)
+}
+
+/// Checks that a sound referenced by the details exists
+fn validate_details_sound(details: &VideoDetails) -> Result<(), String> {
+    match details.sound_id.as_deref() {
+        Some(sound_id) if !sound_id.is_empty() => validate_sound_id(sound_id),
+        _ => Ok(()),
+    }
+}
+
+/// Looks up the video previously created for an idempotency entry, if it still
+/// exists and is not in the trash
+fn find_idempotent_video(entry: &str) -> Option<VideoMetadata> {
+    let video_id = VIDEO_IDEMPOTENCY_KEYS.with(|keys| keys.borrow().get(&entry.to_string()))?;
+    VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+}
+
+/// Forgets the idempotency keys that created a video
+fn remove_idempotency_keys(uploader: &Principal, video_id: &str) {
+    let prefix = format!("{}:", uploader);
+    VIDEO_IDEMPOTENCY_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let stale: Vec<String> = keys
+            .range(prefix.clone()..)
+            .take_while(|(entry, _)| entry.starts_with(&prefix))
+            .filter(|(_, id)| id == video_id)
+            .map(|(entry, _)| entry)
+            .collect();
+        for entry in stale {
+            keys.remove(&entry);
         }
-        videos_map.insert(video_id, metadata.clone());
-        Ok(metadata)
+    });
+}
+
+/// Mints the next video ID. Call `ensure_video_id_salt` first.
+///
+/// IDs already taken, e.g. by videos stored before the counter was
+/// introduced, are skipped so an existing video is never overwritten.
+pub fn mint_video_id() -> String {
+    VIDEO_ID_STATE.with(|state| {
+        let mut state_cell = state.borrow_mut();
+        let mut id_state = state_cell.get().clone();
+        let mut video_id = id_state.mint();
+        while VIDEOS.with(|videos| videos.borrow().contains_key(&video_id)) {
+            video_id = id_state.mint();
+        }
+        state_cell
+            .set(id_state)
+            .expect("Failed to persist video id state");
+        video_id
+    })
+}
+
+/// Fetches the random salt used for video IDs on first use
+pub async fn ensure_video_id_salt() -> Result<(), String> {
+    if VIDEO_ID_STATE.with(|state| !state.borrow().get().salt.is_empty()) {
+        return Ok(());
+    }
+
+    let (random_bytes,) = raw_rand()
+        .await
+        .map_err(|(code, msg)| format!("Failed to get randomness: {:?} - {}", code, msg))?;
+
+    VIDEO_ID_STATE.with(|state| {
+        let mut state_cell = state.borrow_mut();
+        // Keep the first salt if a concurrent call already stored one
+        if state_cell.get().salt.is_empty() {
+            let mut id_state = state_cell.get().clone();
+            id_state.salt = random_bytes;
+            state_cell
+                .set(id_state)
+                .expect("Failed to persist video id state");
+        }
+    });
+
+    Ok(())
+}
+
+/// Whether a video exists and is not in the trash
+pub fn video_is_live(video_id: &str) -> bool {
+    VIDEOS.with(|videos| {
+        videos
+            .borrow()
+            .get(&video_id.to_string())
+            .is_some_and(|metadata| !metadata.is_deleted())
     })
 }
 
@@ -43,106 +283,688 @@ pub fn get_video_metadata(video_id: String) -> Result<VideoMetadata, String> {
         videos
             .borrow()
             .get(&video_id)
+            .filter(|metadata| !metadata.is_deleted())
             .ok_or_else(|| "Video not found".to_string())
     })
 }
 
 /// Lists all videos
 #[query]
-pub fn list_all_videos() -> Vec<VideoMetadata> {
-    VIDEOS.with(|videos| {
-        videos
-            .borrow()
-            .iter()
-            .map(|(_, metadata)| metadata)
-            .collect()
-    })
+pub fn list_all_videos(cursor: Option<String>, limit: Option<u32>) -> Result<Page<VideoMetadata>, String> {
+    page_of_videos(cursor, limit, is_listed_with_source)
 }
 
-/// Lists videos by tag
+/// Lists videos by tag, newest first. Any spelling or alias of the tag matches.
 #[query]
-pub fn list_videos_by_tag(tag: String) -> Vec<VideoMetadata> {
-    VIDEOS.with(|videos| {
-        videos
-            .borrow()
-            .iter()
-            .filter(|(_, metadata)| metadata.tags.contains(&tag))
-            .map(|(_, metadata)| metadata)
-            .collect()
-    })
+pub fn list_videos_by_tag(
+    tag: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    recent_videos(&canonical_tag(&tag), cursor, limit)
 }
 
 /// Lists videos by uploader
 #[query]
-pub fn list_videos_by_uploader(uploader: Principal) -> Vec<VideoMetadata> {
+pub fn list_videos_by_uploader(
+    uploader: Principal,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", uploader);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+    UPLOADER_VIDEOS.with(|uploads| {
+        let uploads = uploads.borrow();
+        let entries = uploads
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let video_id = key.strip_prefix(&prefix)?.to_string();
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id))?;
+                (!metadata.is_deleted()).then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
+    })
+}
+
+pub fn record_uploader_video(metadata: &VideoMetadata) {
+    UPLOADER_VIDEOS.with(|uploads| {
+        uploads.borrow_mut().insert(
+            format!("{}:{}", metadata.uploader_principal, metadata.video_id),
+            metadata.timestamp,
+        )
+    });
+}
+
+/// Indexes videos uploaded before videos were indexed by uploader
+pub fn backfill_uploader_index() {
+    if UPLOADER_VIDEOS.with(|uploads| !uploads.borrow().is_empty()) {
+        return;
+    }
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        record_uploader_video(metadata);
+    }
+}
+
+/// Pages through videos in id order, keeping those matching `filter`
+fn page_of_videos(
+    cursor: Option<String>,
+    limit: Option<u32>,
+    filter: impl Fn(&VideoMetadata) -> bool,
+) -> Result<Page<VideoMetadata>, String> {
+    let start = start_after(cursor.as_deref(), "")?;
     VIDEOS.with(|videos| {
-        videos
-            .borrow()
-            .iter()
-            .filter(|(_, metadata)| metadata.uploader_principal == uploader)
-            .map(|(_, metadata)| metadata)
-            .collect()
+        let videos = videos.borrow();
+        let entries = videos
+            .range((start, Bound::Unbounded))
+            .filter(|(_, metadata)| filter(metadata));
+        Ok(collect_page(entries, page_size(limit)))
     })
 }
 
-/// Updates a video's metadata
+/// Updates a video's metadata and records the edit in the video's revision history
 #[update]
 pub fn update_video_metadata(
     video_id: String,
     title: Option<String>,
     tags: Option<Vec<String>>,
-    storage_ref: Option<String>,
+    storage_ref: Option<StorageRefUpdate>,
+    renditions: Option<Vec<Rendition>>,
+    details: Option<VideoDetails>,
+) -> Result<VideoMetadata, String> {
+    let caller = ic_cdk::caller();
+    let timestamp = ic_cdk::api::time() / 1_000_000_000;
+
+    let before = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id))
+        .filter(|metadata| !metadata.is_deleted())
+        .ok_or_else(|| "Video not found".to_string())?;
+
+    // Verify ownership
+    if before.uploader_principal != caller {
+        return Err("Only the uploader can update video metadata".to_string());
+    }
+    if before.clip_of.is_some() && (storage_ref.is_some() || renditions.is_some()) {
+        return Err("Clips play their source video's media".to_string());
+    }
+    let mut metadata = before.clone();
+
+    // Update fields if provided
+    if let Some(new_title) = title {
+        metadata.title = new_title;
+    }
+
+    if let Some(new_tags) = tags {
+        metadata.tags = new_tags;
+    }
+
+    match storage_ref {
+        Some(StorageRefUpdate::Set(new_storage_ref)) => metadata.storage_ref = Some(new_storage_ref),
+        Some(StorageRefUpdate::Clear) => metadata.storage_ref = None,
+        None => {}
+    }
+
+    if let Some(new_renditions) = renditions {
+        metadata.renditions = new_renditions;
+    }
+
+    // Also checks the title when no details are sent
+    metadata.apply_details(details.unwrap_or_default())?;
+
+    save_video_edit(&before, metadata, caller, timestamp, None)
+}
+
+/// Validates an edited video, saves it if anything changed and brings every
+/// index up to date. Updates and reverts both go through here.
+pub fn save_video_edit(
+    before: &VideoMetadata,
+    mut metadata: VideoMetadata,
+    editor: Principal,
+    timestamp: u64,
+    reverted_to: Option<u32>,
 ) -> Result<VideoMetadata, String> {
+    // Only changed references are checked, so unrecognized legacy ones don't block other edits
+    if metadata.storage_ref != before.storage_ref {
+        if let Some(storage_ref) = &metadata.storage_ref {
+            storage_ref.validate()?;
+        }
+        check_playback_id(&metadata)?;
+    }
+    if metadata.renditions != before.renditions {
+        validate_renditions(&metadata.renditions)?;
+    }
+    if metadata.sound_id != before.sound_id {
+        if let Some(sound_id) = &metadata.sound_id {
+            validate_sound_id(sound_id)?;
+        }
+    }
+    merge_hashtags_and_mentions(&mut metadata)?;
+
+    // Save updated metadata and log what changed
+    let changes = diff_metadata(before, &metadata);
+    if changes.is_empty() {
+        return Ok(metadata);
+    }
+    let video_id = &metadata.video_id;
+    VIDEOS.with(|videos| videos.borrow_mut().insert(video_id.clone(), metadata.clone()));
+    relink_sound(Some(before), Some(&metadata), timestamp);
+    relink_content_hash(before, &metadata);
+    relink_playback_id(Some(before), Some(&metadata));
+    index_video(&metadata);
+    relink_video_suggestions(Some(before), Some(&metadata));
+    relink_tag_aggregates(Some(before), Some(&metadata));
+    relink_tag_activity(Some(before), Some(&metadata), timestamp);
+    record_revision(video_id, editor, timestamp, changes, reverted_to);
+    Ok(metadata)
+}
+
+/// Moves a video to the trash (by its uploader or a moderator).
+///
+/// The video is hidden immediately and can be restored with `restore_video`
+/// for 30 days, after which it is purged together with its comments and
+/// watch events, and removed from playlists.
+#[update]
+pub fn delete_video(video_id: String) -> Result<(), String> {
+    let caller = ic_cdk::caller();
+    trash_video(&video_id, caller, is_moderator(&caller), ic_cdk::api::time() / 1_000_000_000)
+}
+
+fn trash_video(video_id: &String, caller: Principal, moderator: bool, now: u64) -> Result<(), String> {
     VIDEOS.with(|videos| {
         let mut videos_map = videos.borrow_mut();
         
         // Check if video exists
-        if let Some(mut metadata) = videos_map.get(&video_id) {
+        if let Some(mut metadata) = videos_map.get(video_id).filter(|m| !m.is_deleted()) {
             // Verify ownership
-            if metadata.uploader_principal != ic_cdk::caller() {
-                return Err("Only the uploader can update video metadata".to_string());
-            }
-            
-            // Update fields if provided
-            if let Some(new_title) = title {
-                metadata.title = new_title;
-            }
-            
-            if let Some(new_tags) = tags {
-                metadata.tags = new_tags;
-            }
-            
-            if storage_ref.is_some() {
-                metadata.storage_ref = storage_ref;
+            if metadata.uploader_principal != caller && !moderator {
+                return Err("Only the uploader or a moderator can delete the video".to_string());
             }
             
-            // Save updated metadata
-            videos_map.insert(video_id, metadata.clone());
-            Ok(metadata)
+            // Move video to the trash
+            let before = metadata.clone();
+            metadata.deleted_at = Some(now);
+            videos_map.insert(video_id.clone(), metadata.clone());
+            relink_tag_aggregates(Some(&before), Some(&metadata));
+            relink_profile_video_count(Some(&before), Some(&metadata));
+            relink_video_rankings(Some(&before), Some(&metadata));
+            relink_video_suggestions(Some(&before), Some(&metadata));
+            relink_tag_activity(Some(&before), Some(&metadata), now);
+            remove_from_search_index(video_id);
+            VIDEO_TRASH.with(|trash| trash.borrow_mut().insert(trash_key(now, video_id), ()));
+            Ok(())
         } else {
             Err("Video not found".to_string())
         }
     })
 }
 
-/// Deletes a video (only by uploader)
+/// Restores a video from the trash (by its uploader or a moderator, within the grace period)
 #[update]
-pub fn delete_video(video_id: String) -> Result<(), String> {
+pub fn restore_video(video_id: String) -> Result<VideoMetadata, String> {
+    let caller = ic_cdk::caller();
+    untrash_video(&video_id, caller, is_moderator(&caller), ic_cdk::api::time() / 1_000_000_000)
+}
+
+fn untrash_video(video_id: &String, caller: Principal, moderator: bool, now: u64) -> Result<VideoMetadata, String> {
     VIDEOS.with(|videos| {
         let mut videos_map = videos.borrow_mut();
-        
-        // Check if video exists
-        if let Some(metadata) = videos_map.get(&video_id) {
-            // Verify ownership
-            if metadata.uploader_principal != ic_cdk::caller() {
-                return Err("Only the uploader can delete the video".to_string());
-            }
-            
-            // Delete video
-            videos_map.remove(&video_id);
-            Ok(())
-        } else {
-            Err("Video not found".to_string())
+
+        let mut metadata = videos_map
+            .get(video_id)
+            .ok_or_else(|| "Video not found".to_string())?;
+
+        if metadata.uploader_principal != caller && !moderator {
+            return Err("Only the uploader or a moderator can restore the video".to_string());
         }
+
+        let deleted_at = metadata
+            .deleted_at
+            .ok_or_else(|| "Video is not in the trash".to_string())?;
+        if now >= deleted_at + TRASH_GRACE_PERIOD_SEC {
+            return Err("Video can no longer be restored".to_string());
+        }
+
+        let before = metadata.clone();
+        metadata.deleted_at = None;
+        videos_map.insert(video_id.clone(), metadata.clone());
+        relink_tag_aggregates(Some(&before), Some(&metadata));
+        relink_profile_video_count(Some(&before), Some(&metadata));
+        relink_video_rankings(Some(&before), Some(&metadata));
+        relink_video_suggestions(Some(&before), Some(&metadata));
+        relink_tag_activity(Some(&before), Some(&metadata), now);
+        index_video(&metadata);
+        VIDEO_TRASH.with(|trash| trash.borrow_mut().remove(&trash_key(deleted_at, video_id)));
+        Ok(metadata)
+    })
+}
+
+/// Trash key ordering videos by when their grace period ends
+fn trash_key(deleted_at: u64, video_id: &str) -> String {
+    format!("{:020}:{}", deleted_at + TRASH_GRACE_PERIOD_SEC, video_id)
+}
+
+/// Lists the calling user's videos that are in the trash
+#[query]
+pub fn list_my_trash(cursor: Option<String>, limit: Option<u32>) -> Result<Page<VideoMetadata>, String> {
+    let prefix = format!("{}:", ic_cdk::caller());
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    UPLOADER_VIDEOS.with(|uploads| {
+        let uploads = uploads.borrow();
+        let entries = uploads
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                let video_id = key.strip_prefix(&prefix)?.to_string();
+                let metadata = VIDEOS.with(|videos| videos.borrow().get(&video_id))?;
+                metadata.is_deleted().then_some((key, metadata))
+            });
+        Ok(collect_page(entries, page_size(limit)))
     })
 }
+
+/// Permanently removes videos whose grace period has passed, along with their
+/// comments and watch events. Tip records are kept as a ledger with the
+/// addresses and transaction hashes redacted. Returns true if expired videos
+/// remain after this batch.
+pub fn purge_expired_videos() -> bool {
+    purge_videos_expired_at(ic_cdk::api::time() / 1_000_000_000)
+}
+
+fn purge_videos_expired_at(now: u64) -> bool {
+    // Keys sort by expiry, so the expired videos are the leading ones
+    let expired: Vec<String> = VIDEO_TRASH.with(|trash| {
+        trash
+            .borrow()
+            .iter()
+            .take_while(|(key, _)| key[..20].parse::<u64>().is_ok_and(|expires_at| now >= expires_at))
+            .take(PURGE_BATCH_SIZE + 1)
+            .map(|(key, _)| key)
+            .collect()
+    });
+    let has_more = expired.len() > PURGE_BATCH_SIZE;
+
+    let mut budget = PURGE_ENTRY_BUDGET;
+    for key in expired.into_iter().take(PURGE_BATCH_SIZE) {
+        let video_id = key[21..].to_string();
+
+        // Clips go with their source, each purged as one entry of the budget
+        for clip_id in clip_ids_of(&video_id, budget) {
+            budget -= remove_video_entries(&clip_id, budget);
+            if budget == 0 {
+                return true;
+            }
+            purge_video(&clip_id);
+            budget -= 1;
+        }
+        budget -= remove_video_entries(&video_id, budget);
+        // The video stays in the trash until a later run clears the rest
+        if budget == 0 {
+            return true;
+        }
+
+        VIDEO_TRASH.with(|trash| trash.borrow_mut().remove(&key));
+        purge_video(&video_id);
+    }
+
+    has_more
+}
+
+/// Removes up to `limit` of a video's comments, watch events, revisions,
+/// captions and playlist entries and redacts its tips. Returns how many
+/// entries were touched.
+fn remove_video_entries(video_id: &str, limit: usize) -> usize {
+    let mut removed = remove_comments_for_video(video_id, limit);
+    removed += remove_watch_events_for_video(video_id, limit - removed);
+    removed += redact_tips_for_video(video_id, limit - removed);
+    removed += remove_revisions_for_video(video_id, limit - removed);
+    removed += remove_captions_for_video(video_id, limit - removed);
+    removed += remove_video_from_playlists(video_id, limit - removed);
+    removed
+}
+
+/// Removes a video and everything attached to it. Its entries and clips are
+/// cleared beforehand within the purge budget.
+fn purge_video(video_id: &String) {
+    if let Some(metadata) = VIDEOS.with(|videos| videos.borrow_mut().remove(video_id)) {
+        remove_from_duplicate_index(&metadata);
+        relink_playback_id(Some(&metadata), None);
+        relink_credits(video_id, metadata.credits.as_deref().unwrap_or_default(), &[]);
+        remove_remix_links(&metadata);
+        relink_sound(Some(&metadata), None, 0);
+        unpin_purged_video(&metadata.uploader_principal, video_id);
+        remove_from_search_index(video_id);
+        relink_tag_aggregates(Some(&metadata), None);
+        relink_profile_video_count(Some(&metadata), None);
+        relink_video_rankings(Some(&metadata), None);
+        remove_video_stats(video_id);
+        relink_video_suggestions(Some(&metadata), None);
+        relink_tag_activity(Some(&metadata), None, 0);
+        remove_idempotency_keys(&metadata.uploader_principal, video_id);
+        UPLOADER_VIDEOS.with(|uploads| {
+            uploads
+                .borrow_mut()
+                .remove(&format!("{}:{}", metadata.uploader_principal, video_id))
+        });
+        remove_clip_link(&metadata);
+        if let Some(deleted_at) = metadata.deleted_at {
+            VIDEO_TRASH.with(|trash| trash.borrow_mut().remove(&trash_key(deleted_at, video_id)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clip::ClipSource,
+        comment::Comment,
+        service::{clips::{link_key, list_clips_of_video}, comments::store_comment, tips::{store_tip, REDACTED}, watch::store_watch_event},
+        tip_record::TipRecord,
+        video_metadata::test_video,
+        watch_event::WatchEvent,
+        AUTOCOMPLETE_TAGS, CAPTION_CONTENT, CLIP_LINKS, COMMENTS, COMMENTS_BY_USER, SEARCH_DOCUMENTS, SEARCH_TOTAL_LENGTH, TAG_STATS, TIPS_BY_RECIPIENT,
+        TIPS_BY_SENDER, TIP_RECORDS, WATCH_EVENTS_BY_USER, WATCH_LOG,
+    };
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    /// Stores a video and runs the hooks `create_video_metadata` runs
+    fn upload(metadata: &VideoMetadata) {
+        VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata.clone()));
+        record_uploader_video(metadata);
+        index_video(metadata);
+        relink_video_suggestions(None, Some(metadata));
+        relink_tag_aggregates(None, Some(metadata));
+        relink_video_rankings(None, Some(metadata));
+    }
+
+    fn stored(video_id: &str) -> Option<VideoMetadata> {
+        VIDEOS.with(|videos| videos.borrow().get(&video_id.to_string()))
+    }
+
+    fn tagged_count(tag: &str) -> u64 {
+        TAG_STATS
+            .with(|stats| stats.borrow().get(&tag.to_string()))
+            .map(|stats| stats.video_count)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_trash_permissions() {
+        let (owner, moderator, stranger) = (principal(1), principal(2), principal(3));
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &["cats"], NOW));
+
+        assert!(trash_video(&video_id, stranger, false, NOW).is_err());
+        assert!(stored(&video_id).is_some_and(|metadata| !metadata.is_deleted()));
+
+        trash_video(&video_id, owner, false, NOW).unwrap();
+        assert_eq!(tagged_count("cats"), 0);
+        assert!(untrash_video(&video_id, stranger, false, NOW + 1).is_err());
+        untrash_video(&video_id, moderator, true, NOW + 1).unwrap();
+        assert_eq!(tagged_count("cats"), 1);
+
+        trash_video(&video_id, moderator, true, NOW + 2).unwrap();
+        untrash_video(&video_id, owner, false, NOW + 3).unwrap();
+        assert!(VIDEO_TRASH.with(|trash| trash.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_list_videos_by_tag() {
+        let owner = principal(1);
+        upload(&test_video("a", owner, &["cats"], NOW));
+        upload(&test_video("b", owner, &["cats", "dogs"], NOW + 2));
+        upload(&test_video("c", owner, &["cats"], NOW + 1));
+        upload(&test_video("d", owner, &["dogs"], NOW + 3));
+        trash_video(&"c".to_string(), owner, false, NOW + 4).unwrap();
+
+        let ids = |page: &Page<VideoMetadata>| page.items.iter().map(|m| m.video_id.clone()).collect::<Vec<_>>();
+        let first = list_videos_by_tag("#Cats".to_string(), None, Some(1)).unwrap();
+        assert_eq!(ids(&first), vec!["b"]);
+        let rest = list_videos_by_tag("cats".to_string(), first.next_cursor, None).unwrap();
+        assert_eq!(ids(&rest), vec!["a"]);
+        assert_eq!(rest.next_cursor, None);
+    }
+
+    #[test]
+    fn test_restore_window() {
+        let owner = principal(1);
+        for video_id in ["early", "late"] {
+            upload(&test_video(video_id, owner, &[], NOW));
+            trash_video(&video_id.to_string(), owner, false, NOW).unwrap();
+        }
+        let expires_at = NOW + TRASH_GRACE_PERIOD_SEC;
+
+        // Nothing is purged before the grace period ends
+        assert!(!purge_videos_expired_at(expires_at - 1));
+        assert!(stored("early").is_some());
+        untrash_video(&"early".to_string(), owner, false, expires_at - 1).unwrap();
+
+        assert_eq!(
+            untrash_video(&"late".to_string(), owner, false, expires_at),
+            Err("Video can no longer be restored".to_string())
+        );
+        assert!(!purge_videos_expired_at(expires_at));
+        assert!(stored("late").is_none());
+        assert!(stored("early").is_some_and(|metadata| !metadata.is_deleted()));
+        assert!(VIDEO_TRASH.with(|trash| trash.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_trash_leaves_search_index() {
+        let owner = principal(1);
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &["cats"], NOW));
+        let indexed = || SEARCH_DOCUMENTS.with(|documents| documents.borrow().contains_key(&video_id));
+        assert!(indexed());
+
+        trash_video(&video_id, owner, false, NOW).unwrap();
+        assert!(!indexed());
+        assert_eq!(SEARCH_TOTAL_LENGTH.with(|total| *total.borrow().get()), 0);
+
+        untrash_video(&video_id, owner, false, NOW + 1).unwrap();
+        assert!(indexed());
+    }
+
+    #[test]
+    fn test_purge_batches() {
+        let owner = principal(1);
+        for i in 0..=PURGE_BATCH_SIZE {
+            let video_id = format!("video{}", i);
+            upload(&test_video(&video_id, owner, &[], NOW));
+            trash_video(&video_id, owner, false, NOW + i as u64).unwrap();
+        }
+
+        let later = NOW + PURGE_BATCH_SIZE as u64 + TRASH_GRACE_PERIOD_SEC;
+        assert!(purge_videos_expired_at(later));
+        // The oldest trash goes first
+        assert!(stored("video0").is_none());
+        assert!(stored(&format!("video{}", PURGE_BATCH_SIZE)).is_some());
+        assert!(!purge_videos_expired_at(later));
+        assert!(VIDEOS.with(|videos| videos.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_purge_budget() {
+        let (owner, viewer) = (principal(1), principal(2));
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &[], NOW));
+        for i in 0..PURGE_ENTRY_BUDGET + 5 {
+            store_comment(&Comment {
+                commenter_principal: viewer,
+                video_id: video_id.clone(),
+                text: "Nice".to_string(),
+                timestamp: NOW + i as u64,
+            });
+        }
+        trash_video(&video_id, owner, false, NOW).unwrap();
+
+        // A run stops once its budget is spent and the next one finishes
+        let expires_at = NOW + TRASH_GRACE_PERIOD_SEC;
+        assert!(purge_videos_expired_at(expires_at));
+        assert!(stored(&video_id).is_some());
+        assert_eq!(COMMENTS.with(|comments| comments.borrow().len()), 5);
+        assert!(!purge_videos_expired_at(expires_at));
+        assert!(stored(&video_id).is_none());
+        assert!(COMMENTS_BY_USER.with(|index| index.borrow().is_empty()));
+        assert!(VIDEO_TRASH.with(|trash| trash.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_trash_drops_suggestions() {
+        let owner = principal(1);
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &["cats"], NOW));
+        let suggested = || AUTOCOMPLETE_TAGS.with(|tags| tags.borrow().get(&"cats".to_string()));
+        assert_eq!(suggested(), Some(1));
+
+        trash_video(&video_id, owner, false, NOW).unwrap();
+        assert_eq!(suggested(), None);
+        untrash_video(&video_id, owner, false, NOW).unwrap();
+        assert_eq!(suggested(), Some(1));
+
+        trash_video(&video_id, owner, false, NOW).unwrap();
+        assert!(!purge_videos_expired_at(NOW + TRASH_GRACE_PERIOD_SEC));
+        assert_eq!(suggested(), None);
+    }
+
+    #[test]
+    fn test_purge_budget_covers_captions_and_clips() {
+        let owner = principal(1);
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &[], NOW));
+        for i in 0..PURGE_ENTRY_BUDGET - 1 {
+            CAPTION_CONTENT.with(|content| content.borrow_mut().insert(format!("{}:l{}", video_id, i), String::new()));
+        }
+        for i in 0..3 {
+            let clip_id = format!("clip{}", i);
+            upload(&test_video(&clip_id, owner, &[], NOW));
+            CLIP_LINKS.with(|links| links.borrow_mut().insert(link_key(&video_id, NOW, &clip_id), NOW));
+        }
+        trash_video(&video_id, owner, false, NOW).unwrap();
+
+        // The clips spend the budget first and the captions are left for the next run
+        let expires_at = NOW + TRASH_GRACE_PERIOD_SEC;
+        assert!(purge_videos_expired_at(expires_at));
+        assert!(stored("clip0").is_none());
+        assert!(stored(&video_id).is_some());
+        assert_eq!(CAPTION_CONTENT.with(|content| content.borrow().len()), 2);
+        assert!(!purge_videos_expired_at(expires_at));
+        assert!(stored(&video_id).is_none());
+        assert!(CAPTION_CONTENT.with(|content| content.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_purge_cascades() {
+        let (owner, viewer) = (principal(1), principal(2));
+        let video_id = "video1".to_string();
+        upload(&test_video(&video_id, owner, &["cats", "funny"], NOW));
+
+        let mut clip = test_video("clip1", viewer, &["cats"], NOW);
+        clip.clip_of = Some(ClipSource {
+            source_video_id: video_id.clone(),
+            start_sec: 0,
+            end_sec: 10,
+        });
+        upload(&clip);
+        CLIP_LINKS.with(|links| links.borrow_mut().insert(link_key(&video_id, NOW, "clip1"), NOW));
+        assert_eq!(list_clips_of_video(video_id.clone(), None, None).unwrap().items, vec![clip.clone()]);
+
+        store_comment(&Comment {
+            commenter_principal: viewer,
+            video_id: video_id.clone(),
+            text: "Nice".to_string(),
+            timestamp: NOW,
+        });
+        store_watch_event(&WatchEvent {
+            user_principal: viewer,
+            video_id: video_id.clone(),
+            watch_duration_sec: 30,
+            liked: true,
+            completed: false,
+            timestamp: NOW,
+        });
+        store_tip(&TipRecord {
+            from_addr: "0xfrom".to_string(),
+            to_addr: "0xto".to_string(),
+            video_id: video_id.clone(),
+            amount: 100,
+            tx_hash: "0xhash".to_string(),
+            timestamp: NOW,
+            share_bps: None,
+        });
+        VIDEO_IDEMPOTENCY_KEYS.with(|keys| keys.borrow_mut().insert(format!("{}:upload-1", owner), video_id.clone()));
+
+        trash_video(&video_id, owner, false, NOW).unwrap();
+        assert!(!purge_videos_expired_at(NOW + TRASH_GRACE_PERIOD_SEC));
+
+        assert!(stored(&video_id).is_none());
+        assert!(stored("clip1").is_none());
+        assert!(CLIP_LINKS.with(|links| links.borrow().is_empty()));
+        assert!(list_clips_of_video(video_id.clone(), None, None).unwrap().items.is_empty());
+        assert!(COMMENTS.with(|comments| comments.borrow().is_empty()));
+        assert!(COMMENTS_BY_USER.with(|index| index.borrow().is_empty()));
+        assert!(WATCH_LOG.with(|log| log.borrow().is_empty()));
+        assert!(WATCH_EVENTS_BY_USER.with(|index| index.borrow().is_empty()));
+        assert!(VIDEO_IDEMPOTENCY_KEYS.with(|keys| keys.borrow().is_empty()));
+        assert!(UPLOADER_VIDEOS.with(|uploads| uploads.borrow().is_empty()));
+        assert_eq!(tagged_count("cats"), 0);
+        assert_eq!(tagged_count("funny"), 0);
+
+        // Tips stay as a ledger without anything identifying the parties
+        let tip = TIP_RECORDS.with(|tips| tips.borrow().iter().next()).unwrap().1;
+        assert_eq!(tip.amount, 100);
+        assert!(TIPS_BY_SENDER.with(|index| index.borrow().is_empty()));
+        assert!(TIPS_BY_RECIPIENT.with(|index| index.borrow().is_empty()));
+        assert_eq!(
+            [tip.from_addr, tip.to_addr, tip.tx_hash],
+            [REDACTED.to_string(), REDACTED.to_string(), REDACTED.to_string()]
+        );
+    }
+
+    #[test]
+    fn test_save_video_edit() {
+        let owner = principal(1);
+        let mut before = test_video("video1", owner, &["cats"], NOW);
+        before.storage_ref = Some(StorageRef::Unrecognized {
+            value: "My Video.mp4".to_string(),
+        });
+        upload(&before);
+
+        // Tags are validated and the tag pages follow the edit
+        let mut edited = before.clone();
+        edited.tags = vec!["".to_string()];
+        assert!(save_video_edit(&before, edited.clone(), owner, NOW, None).is_err());
+        edited.tags = vec!["Dogs".to_string()];
+        edited.title = "Dogs #Funny".to_string();
+        let saved = save_video_edit(&before, edited, owner, NOW, None).unwrap();
+        assert_eq!(saved.tags, vec!["dogs".to_string(), "funny".to_string()]);
+        assert_eq!((tagged_count("cats"), tagged_count("dogs")), (0, 1));
+
+        // A legacy reference only blocks edits that touch it
+        assert_eq!(saved.storage_ref, before.storage_ref);
+        let mut invalid = saved.clone();
+        invalid.storage_ref = Some(StorageRef::Url {
+            url: "ftp://example.com".to_string(),
+        });
+        assert!(save_video_edit(&saved, invalid, owner, NOW, None).is_err());
+    }
+
+    #[test]
+    fn test_mint_skips_taken_ids() {
+        let taken = VIDEO_ID_STATE.with(|state| state.borrow().get().clone().mint());
+        upload(&test_video(&taken, principal(1), &[], NOW));
+
+        let minted = mint_video_id();
+        assert_ne!(minted, taken);
+        assert!(stored(&minted).is_none());
+        assert_eq!(stored(&taken).unwrap().uploader_principal, principal(1));
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    search_query::SearchSort,
+    tip_record::TipRecord,
+    video_metadata::VideoMetadata,
+    video_stats::VideoStats,
+    watch_event::WatchEvent,
+    TIP_RECORDS, VIDEOS, VIDEO_RANKINGS, VIDEO_STATS, WATCH_LOG,
+};
+
+/// Running totals for a video, zero if it has none yet
+pub fn video_stats(video_id: &str) -> VideoStats {
+    VIDEO_STATS
+        .with(|stats| stats.borrow().get(&video_id.to_string()))
+        .unwrap_or_default()
+}
+
+pub fn record_view_stats(video_id: &str, liked: bool) {
+    let before = video_stats(video_id);
+    let mut stats = before;
+    stats.record_view(liked);
+    save_video_stats(video_id, &before, stats);
+}
+
+pub fn record_tip_stats(video_id: &str, tips: &[TipRecord]) {
+    let before = video_stats(video_id);
+    let mut stats = before;
+    stats.record_tips(tips);
+    save_video_stats(video_id, &before, stats);
+}
+
+/// Stores a video's new totals and moves it along the rankings they change
+fn save_video_stats(video_id: &str, before: &VideoStats, stats: VideoStats) {
+    VIDEO_STATS.with(|all| all.borrow_mut().insert(video_id.to_string(), stats));
+
+    let Some(metadata) = VIDEOS
+        .with(|videos| videos.borrow().get(&video_id.to_string()))
+        .filter(|metadata| !metadata.is_deleted())
+    else {
+        return;
+    };
+    VIDEO_RANKINGS.with(|rankings| {
+        let mut rankings = rankings.borrow_mut();
+        for sort in SearchSort::RANKED {
+            let old_key = sort.key(metadata.timestamp, before, video_id);
+            let new_key = sort.key(metadata.timestamp, &stats, video_id);
+            if old_key != new_key {
+                rankings.remove(&old_key);
+                rankings.insert(new_key, ());
+            }
+        }
+    });
+}
+
+/// Adds a video to or drops it from the catalog rankings as it is created,
+/// trashed, restored or purged; videos in the trash aren't ranked. On purge,
+/// call before its stats are removed.
+pub fn relink_video_rankings(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>) {
+    let before = before.filter(|metadata| !metadata.is_deleted());
+    let after = after.filter(|metadata| !metadata.is_deleted());
+    match (before, after) {
+        (None, Some(metadata)) => rank_video(metadata, true),
+        (Some(metadata), None) => rank_video(metadata, false),
+        _ => {}
+    }
+}
+
+fn rank_video(metadata: &VideoMetadata, ranked: bool) {
+    let stats = video_stats(&metadata.video_id);
+    VIDEO_RANKINGS.with(|rankings| {
+        let mut rankings = rankings.borrow_mut();
+        for sort in SearchSort::RANKED {
+            let key = sort.key(metadata.timestamp, &stats, &metadata.video_id);
+            if ranked {
+                rankings.insert(key, ());
+            } else {
+                rankings.remove(&key);
+            }
+        }
+    });
+}
+
+pub fn remove_video_stats(video_id: &str) {
+    VIDEO_STATS.with(|stats| stats.borrow_mut().remove(&video_id.to_string()));
+}
+
+/// Computes totals for watch events and tips stored before they were kept
+pub fn backfill_video_stats() {
+    if VIDEO_STATS.with(|stats| !stats.borrow().is_empty()) {
+        return;
+    }
+
+    let mut history: BTreeMap<String, (Vec<WatchEvent>, Vec<TipRecord>)> = BTreeMap::new();
+    WATCH_LOG.with(|log| {
+        for (_, event) in log.borrow().iter() {
+            history.entry(event.video_id.clone()).or_default().0.push(event);
+        }
+    });
+    TIP_RECORDS.with(|tips| {
+        for (_, tip) in tips.borrow().iter() {
+            history.entry(tip.video_id.clone()).or_default().1.push(tip);
+        }
+    });
+
+    for (video_id, (events, tips)) in history {
+        let stats = VideoStats::from_history(&events, &tips);
+        VIDEO_STATS.with(|all| all.borrow_mut().insert(video_id, stats));
+    }
+}
+
+/// Ranks videos stored before the catalog rankings were kept. Run after
+/// `backfill_video_stats`.
+pub fn backfill_video_rankings() {
+    if VIDEO_RANKINGS.with(|rankings| !rankings.borrow().is_empty()) {
+        return;
+    }
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        relink_video_rankings(None, Some(metadata));
+    }
+}
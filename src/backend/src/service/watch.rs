@@ -1,7 +1,15 @@
 use ic_cdk::{query, update};
-// Removed unused imports
+use std::ops::Bound;
 
-use crate::{watch_event::{WatchEvent, WatchEventList}, WATCH_LOG, VIDEOS};
+use crate::{
+    page::{collect_page, next_log_key, owner_key, page_size, start_after, Page},
+    service::{
+        autocomplete::record_title_view, tag_pages::record_tag_view, trending::record_view_activity,
+        video::video_is_live, video_stats::record_view_stats,
+    },
+    watch_event::WatchEvent,
+    LEGACY_WATCH_LOG, WATCH_EVENTS_BY_USER, WATCH_LOG,
+};
 
 /// Logs a watch event for a video
 #[update]
@@ -12,12 +20,9 @@ pub fn log_watch_event(
     completed: bool
 ) -> Result<(), String> {
     // Verify the video exists
-    VIDEOS.with(|videos| {
-        if !videos.borrow().contains_key(&video_id) {
-            return Err("Video not found".to_string());
-        }
-        Ok(())
-    })?;
+    if !video_is_live(&video_id) {
+        return Err("Video not found".to_string());
+    }
 
     // Generate timestamp using IC time instead of SystemTime
     let timestamp = ic_cdk::api::time() / 1_000_000_000; // Convert nanoseconds to seconds
@@ -33,63 +38,116 @@ pub fn log_watch_event(
     };
 
     // Store event
-    WATCH_LOG.with(|log| {
-        let mut log_map = log.borrow_mut();
-        let events = match log_map.get(&video_id) {
-            Some(event_list) => {
-                let mut events = event_list.0.clone();
-                events.push(event);
-                WatchEventList(events)
-            }
-            None => WatchEventList(vec![event]),
-        };
-        log_map.insert(video_id, events);
-        Ok(())
-    })
+    store_watch_event(&event);
+    record_title_view(&video_id);
+    record_view_stats(&video_id, liked);
+    record_tag_view(&video_id);
+    record_view_activity(&video_id, timestamp);
+    Ok(())
 }
 
-/// Returns all watch events for a specific video
-#[query]
-pub fn get_watch_events(video_id: String) -> Vec<WatchEvent> {
+/// Adds a watch event to its video's log and the viewer's index
+pub fn store_watch_event(event: &WatchEvent) {
+    let key = WATCH_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        let key = next_log_key(&log, &event.video_id, event.timestamp);
+        log.insert(key.clone(), event.clone());
+        key
+    });
+    let owner = owner_key(&event.user_principal.to_string(), event.timestamp, &key);
+    WATCH_EVENTS_BY_USER.with(|index| index.borrow_mut().insert(owner, key));
+}
+
+/// Watch events for a video, oldest first
+fn watch_events_of(video_id: &str) -> Vec<(String, WatchEvent)> {
+    let prefix = format!("{}:", video_id);
     WATCH_LOG.with(|log| {
         log.borrow()
-            .get(&video_id)
-            .map(|event_list| event_list.0.clone())
-            .unwrap_or_default()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .collect()
     })
 }
 
-/// Returns watch events for the calling user
-#[query]
-pub fn get_my_watch_events() -> Vec<WatchEvent> {
-    let caller = ic_cdk::caller();
-    
-    WATCH_LOG.with(|log| {
+/// Removes up to `limit` watch events for a video, oldest first, and returns
+/// how many were removed
+pub fn remove_watch_events_for_video(video_id: &str, limit: usize) -> usize {
+    let prefix = format!("{}:", video_id);
+    let events: Vec<(String, WatchEvent)> = WATCH_LOG.with(|log| {
         log.borrow()
-            .iter()
-            .flat_map(|(_, event_list)| event_list.0.clone())
-            .filter(|event| event.user_principal == caller)
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
             .collect()
-    })
+    });
+    for (key, event) in &events {
+        WATCH_LOG.with(|log| log.borrow_mut().remove(key));
+        let owner = owner_key(&event.user_principal.to_string(), event.timestamp, key);
+        WATCH_EVENTS_BY_USER.with(|index| index.borrow_mut().remove(&owner));
+    }
+    events.len()
+}
+
+/// Moves watch events stored as per-video lists into the keyed watch log
+pub fn migrate_legacy_watch_log() {
+    let video_ids: Vec<String> = LEGACY_WATCH_LOG.with(|legacy| legacy.borrow().iter().map(|(id, _)| id).collect());
+    for video_id in video_ids {
+        let Some(event_list) = LEGACY_WATCH_LOG.with(|legacy| legacy.borrow_mut().remove(&video_id)) else {
+            continue;
+        };
+        for event in &event_list.0 {
+            store_watch_event(event);
+        }
+    }
+}
+
+/// Returns watch events for a specific video, oldest first
+#[query]
+pub fn get_watch_events(
+    video_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<WatchEvent>, String> {
+    let prefix = format!("{}:", video_id);
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(WATCH_LOG.with(|log| {
+        let log = log.borrow();
+        let entries = log
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix));
+        collect_page(entries, page_size(limit))
+    }))
+}
+
+/// Returns watch events for the calling user, oldest first
+#[query]
+pub fn get_my_watch_events(cursor: Option<String>, limit: Option<u32>) -> Result<Page<WatchEvent>, String> {
+    let prefix = format!("{}:", ic_cdk::caller());
+    let start = start_after(cursor.as_deref(), &prefix)?;
+
+    Ok(WATCH_EVENTS_BY_USER.with(|index| {
+        let index = index.borrow();
+        let entries = index
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, event_key)| WATCH_LOG.with(|log| log.borrow().get(&event_key)).map(|event| (key, event)));
+        collect_page(entries, page_size(limit))
+    }))
 }
 
 /// Returns analytics for a specific video
 #[query]
 pub fn get_video_analytics(video_id: String) -> Result<VideoAnalytics, String> {
     // Verify the video exists
-    VIDEOS.with(|videos| {
-        if !videos.borrow().contains_key(&video_id) {
-            return Err("Video not found".to_string());
-        }
-        Ok(())
-    })?;
+    if !video_is_live(&video_id) {
+        return Err("Video not found".to_string());
+    }
 
-    let events = WATCH_LOG.with(|log| {
-        log.borrow()
-            .get(&video_id)
-            .map(|event_list| event_list.0.clone())
-            .unwrap_or_default()
-    });
+    let events: Vec<WatchEvent> = watch_events_of(&video_id)
+        .into_iter()
+        .map(|(_, event)| event)
+        .collect();
 
     // Calculate analytics
     let total_views = events.len() as u64;
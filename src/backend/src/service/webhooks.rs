@@ -0,0 +1,460 @@
+// Livepeer processing webhooks
+// Livepeer calls POST /webhooks/livepeer as an asset moves through transcoding.
+// Each call carries a `Livepeer-Signature: t=<unix ms>,v1=<hex hmac>` header, where
+// the HMAC-SHA256 is computed over "<t>.<raw body>" with the shared webhook secret.
+
+use ic_cdk::{query, update};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    processing_status::ProcessingStatus,
+    service::http::{HttpRequest, HttpResponse},
+    storage_ref::StorageRef,
+    video_metadata::VideoMetadata,
+    PLAYBACK_IDS, VIDEOS, WEBHOOK_SECRET,
+};
+
+pub const LIVEPEER_WEBHOOK_PATH: &str = "/webhooks/livepeer";
+
+const SIGNATURE_HEADER: &str = "Livepeer-Signature";
+
+/// Signed requests older (or newer) than this are rejected to prevent replays
+const MAX_SIGNATURE_AGE_MS: u64 = 5 * 60 * 1000;
+
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// A status update for the video with the given Livepeer playback id
+#[derive(Debug, Clone, PartialEq)]
+struct WebhookEvent {
+    playback_id: String,
+    status: ProcessingStatus,
+}
+
+/// Sets the secret shared with Livepeer for signing webhooks (controllers only)
+#[update]
+pub fn set_webhook_secret(secret: String) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only controllers can set the webhook secret".to_string());
+    }
+    if secret.len() < MIN_SECRET_LENGTH {
+        return Err(format!(
+            "Webhook secret must be at least {} characters",
+            MIN_SECRET_LENGTH
+        ));
+    }
+
+    WEBHOOK_SECRET
+        .with(|s| s.borrow_mut().set(secret).map(|_| ()))
+        .map_err(|e| format!("Failed to store webhook secret: {:?}", e))
+}
+
+/// Whether a webhook secret has been configured
+#[query]
+pub fn has_webhook_secret_configured() -> bool {
+    WEBHOOK_SECRET.with(|s| !s.borrow().get().is_empty())
+}
+
+pub fn handle_livepeer_webhook(request: &HttpRequest) -> HttpResponse {
+    let secret = WEBHOOK_SECRET.with(|s| s.borrow().get().clone());
+    if secret.is_empty() {
+        return HttpResponse::text(503, "Webhook secret not configured");
+    }
+
+    let now_ms = ic_cdk::api::time() / 1_000_000;
+    process_webhook(request, &secret, now_ms)
+}
+
+/// Verifies and applies a webhook request. Kept free of system API calls so
+/// tests can drive it with a fake sender.
+fn process_webhook(request: &HttpRequest, secret: &str, now_ms: u64) -> HttpResponse {
+    let Some(signature) = request.header(SIGNATURE_HEADER) else {
+        return HttpResponse::text(401, "Missing signature");
+    };
+    if let Err(e) = verify_signature(signature, &request.body, secret, now_ms) {
+        return HttpResponse::text(401, &e);
+    }
+
+    let event = match parse_event(&request.body) {
+        Ok(Some(event)) => event,
+        Ok(None) => return HttpResponse::text(200, "Ignored"),
+        Err(e) => return HttpResponse::text(400, &e),
+    };
+
+    match apply_event(event) {
+        Ok(status) => HttpResponse::text(200, &format!("{:?}", status)),
+        Err((status_code, message)) => HttpResponse::text(status_code, &message),
+    }
+}
+
+fn verify_signature(header: &str, body: &[u8], secret: &str, now_ms: u64) -> Result<(), String> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<u64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or_else(|| "Malformed signature header".to_string())?;
+    if now_ms.abs_diff(timestamp) > MAX_SIGNATURE_AGE_MS {
+        return Err("Signature timestamp outside the allowed window".to_string());
+    }
+
+    let mut signed_payload = format!("{}.", timestamp).into_bytes();
+    signed_payload.extend_from_slice(body);
+    let expected = to_hex(&hmac_sha256(secret.as_bytes(), &signed_payload));
+
+    if signatures
+        .iter()
+        .any(|signature| constant_time_eq(signature.as_bytes(), expected.as_bytes()))
+    {
+        Ok(())
+    } else {
+        Err("Invalid signature".to_string())
+    }
+}
+
+/// Maps a Livepeer asset event to a processing status. Events that don't
+/// concern asset processing are ignored.
+fn parse_event(body: &[u8]) -> Result<Option<WebhookEvent>, String> {
+    let json: Value = serde_json::from_slice(body).map_err(|_| "Invalid JSON body".to_string())?;
+
+    let event = json["event"]
+        .as_str()
+        .ok_or_else(|| "Missing event type".to_string())?;
+    let asset = &json["payload"]["asset"];
+    let failure = || ProcessingStatus::Failed {
+        reason: asset["status"]["errorMessage"]
+            .as_str()
+            .unwrap_or("Processing failed")
+            .to_string(),
+    };
+
+    let status = match event {
+        "asset.created" => ProcessingStatus::Uploading,
+        "asset.ready" => ProcessingStatus::Ready,
+        "asset.failed" => failure(),
+        "asset.updated" => match asset["status"]["phase"].as_str() {
+            Some("waiting") | Some("uploading") => ProcessingStatus::Uploading,
+            Some("processing") => ProcessingStatus::Processing,
+            Some("ready") => ProcessingStatus::Ready,
+            Some("failed") => failure(),
+            _ => return Err("Unknown asset phase".to_string()),
+        },
+        _ => return Ok(None),
+    };
+
+    let playback_id = asset["playbackId"]
+        .as_str()
+        .ok_or_else(|| "Missing playback ID".to_string())?
+        .to_string();
+
+    Ok(Some(WebhookEvent {
+        playback_id,
+        status,
+    }))
+}
+
+fn apply_event(event: WebhookEvent) -> Result<ProcessingStatus, (u16, String)> {
+    let video_id = PLAYBACK_IDS
+        .with(|ids| ids.borrow().get(&event.playback_id))
+        .ok_or_else(|| (404, "No video with this playback ID".to_string()))?;
+
+    VIDEOS.with(|videos| {
+        let mut videos_map = videos.borrow_mut();
+
+        let mut metadata = videos_map
+            .get(&video_id)
+            .ok_or_else(|| (404, "No video with this playback ID".to_string()))?;
+
+        let status = metadata
+            .status()
+            .transition(event.status)
+            .map_err(|e| (409, e))?;
+
+        metadata.processing_status = Some(status.clone());
+        videos_map.insert(video_id, metadata);
+        Ok(status)
+    })
+}
+
+fn playback_id_of(metadata: &VideoMetadata) -> Option<&String> {
+    match &metadata.storage_ref {
+        Some(StorageRef::Livepeer { playback_id }) => Some(playback_id),
+        _ => None,
+    }
+}
+
+/// Fails if another video's storage reference already uses the video's playback id
+pub fn check_playback_id(metadata: &VideoMetadata) -> Result<(), String> {
+    let Some(playback_id) = playback_id_of(metadata) else {
+        return Ok(());
+    };
+    match PLAYBACK_IDS.with(|ids| ids.borrow().get(playback_id)) {
+        Some(owner) if owner != metadata.video_id => Err("Another video already uses this playback ID".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Moves a video's playback id from `before` to `after`. Call whenever a video
+/// is created, its storage reference is edited, or it is purged.
+pub fn relink_playback_id(before: Option<&VideoMetadata>, after: Option<&VideoMetadata>) {
+    let old = before.and_then(playback_id_of);
+    let new = after.and_then(playback_id_of);
+    if old == new {
+        return;
+    }
+    PLAYBACK_IDS.with(|ids| {
+        let mut ids = ids.borrow_mut();
+        if let (Some(old), Some(video)) = (old, before) {
+            if ids.get(old).as_ref() == Some(&video.video_id) {
+                ids.remove(old);
+            }
+        }
+        if let (Some(new), Some(video)) = (new, after) {
+            ids.insert(new.clone(), video.video_id.clone());
+        }
+    });
+}
+
+/// Indexes playback ids of videos stored before they were indexed. Where
+/// several videos share one, the first keeps it.
+pub fn backfill_playback_index() {
+    if PLAYBACK_IDS.with(|ids| !ids.borrow().is_empty()) {
+        return;
+    }
+    let videos: Vec<VideoMetadata> =
+        VIDEOS.with(|videos| videos.borrow().iter().map(|(_, metadata)| metadata).collect());
+    for metadata in &videos {
+        if let Some(playback_id) = playback_id_of(metadata) {
+            if PLAYBACK_IDS.with(|ids| !ids.borrow().contains_key(playback_id)) {
+                relink_playback_id(None, Some(metadata));
+            }
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|b| b ^ 0x36));
+    inner.update(message);
+
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_metadata::VideoMetadata;
+    use candid::Principal;
+    use serde_bytes::ByteBuf;
+
+    const SECRET: &str = "test-webhook-secret";
+    const PLAYBACK_ID: &str = "f5eese9wwl88k4g8";
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    /// Signs requests the way Livepeer does
+    struct FakeLivepeerSender {
+        secret: String,
+    }
+
+    impl FakeLivepeerSender {
+        fn send(&self, body: &str, timestamp_ms: u64) -> HttpRequest {
+            let mut signed_payload = format!("{}.", timestamp_ms).into_bytes();
+            signed_payload.extend_from_slice(body.as_bytes());
+            let signature = to_hex(&hmac_sha256(self.secret.as_bytes(), &signed_payload));
+
+            HttpRequest {
+                method: "POST".to_string(),
+                url: LIVEPEER_WEBHOOK_PATH.to_string(),
+                headers: vec![(
+                    SIGNATURE_HEADER.to_string(),
+                    format!("t={},v1={}", timestamp_ms, signature),
+                )],
+                body: ByteBuf::from(body.as_bytes().to_vec()),
+            }
+        }
+    }
+
+    fn asset_event(event: &str, phase: &str) -> String {
+        format!(
+            r#"{{"event":"{}","payload":{{"asset":{{"id":"asset-1","playbackId":"{}","status":{{"phase":"{}","errorMessage":"Unsupported codec"}}}}}}}}"#,
+            event, PLAYBACK_ID, phase
+        )
+    }
+
+    fn insert_uploading_video() {
+        let metadata = VideoMetadata {
+            video_id: "video123".to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: vec![],
+            title: "Test Video".to_string(),
+            storage_ref: Some(StorageRef::Livepeer {
+                playback_id: PLAYBACK_ID.to_string(),
+            }),
+            renditions: vec![],
+            timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: Some(ProcessingStatus::Uploading),
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        };
+        relink_playback_id(None, Some(&metadata));
+        VIDEOS.with(|videos| videos.borrow_mut().insert(metadata.video_id.clone(), metadata));
+    }
+
+    fn stored_status() -> ProcessingStatus {
+        VIDEOS.with(|videos| videos.borrow().get(&"video123".to_string()).unwrap().status())
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            to_hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signed_callbacks_advance_status() {
+        insert_uploading_video();
+        let sender = FakeLivepeerSender {
+            secret: SECRET.to_string(),
+        };
+
+        let request = sender.send(&asset_event("asset.updated", "processing"), NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 200);
+        assert_eq!(stored_status(), ProcessingStatus::Processing);
+
+        let request = sender.send(&asset_event("asset.ready", "ready"), NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 200);
+        assert_eq!(stored_status(), ProcessingStatus::Ready);
+
+        // Ready is terminal
+        let request = sender.send(&asset_event("asset.updated", "processing"), NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 409);
+        assert_eq!(stored_status(), ProcessingStatus::Ready);
+    }
+
+    #[test]
+    fn test_failed_callback_records_reason() {
+        insert_uploading_video();
+        let sender = FakeLivepeerSender {
+            secret: SECRET.to_string(),
+        };
+
+        let request = sender.send(&asset_event("asset.failed", "failed"), NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 200);
+        assert_eq!(
+            stored_status(),
+            ProcessingStatus::Failed {
+                reason: "Unsupported codec".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_signatures() {
+        insert_uploading_video();
+        let body = asset_event("asset.ready", "ready");
+
+        let forged = FakeLivepeerSender {
+            secret: "some-other-secret".to_string(),
+        }
+        .send(&body, NOW_MS);
+        assert_eq!(process_webhook(&forged, SECRET, NOW_MS).status_code, 401);
+
+        let sender = FakeLivepeerSender {
+            secret: SECRET.to_string(),
+        };
+        let stale = sender.send(&body, NOW_MS - MAX_SIGNATURE_AGE_MS - 1);
+        assert_eq!(process_webhook(&stale, SECRET, NOW_MS).status_code, 401);
+
+        let mut tampered = sender.send(&body, NOW_MS);
+        tampered.body = ByteBuf::from(asset_event("asset.failed", "failed").into_bytes());
+        assert_eq!(process_webhook(&tampered, SECRET, NOW_MS).status_code, 401);
+
+        let mut unsigned = sender.send(&body, NOW_MS);
+        unsigned.headers.clear();
+        assert_eq!(process_webhook(&unsigned, SECRET, NOW_MS).status_code, 401);
+
+        assert_eq!(stored_status(), ProcessingStatus::Uploading);
+    }
+
+    #[test]
+    fn test_unknown_playback_id_and_ignored_events() {
+        let sender = FakeLivepeerSender {
+            secret: SECRET.to_string(),
+        };
+
+        let request = sender.send(&asset_event("asset.ready", "ready"), NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 404);
+
+        let request = sender.send(r#"{"event":"stream.started","payload":{}}"#, NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 200);
+
+        let request = sender.send("not json", NOW_MS);
+        assert_eq!(process_webhook(&request, SECRET, NOW_MS).status_code, 400);
+    }
+
+    #[test]
+    fn test_playback_index() {
+        insert_uploading_video();
+        let video = VIDEOS.with(|videos| videos.borrow().get(&"video123".to_string())).unwrap();
+        assert!(check_playback_id(&video).is_ok());
+
+        let mut other = video.clone();
+        other.video_id = "video456".to_string();
+        assert!(check_playback_id(&other).is_err());
+
+        // Moving the first video off the playback id frees it
+        let mut moved = video.clone();
+        moved.storage_ref = Some(StorageRef::Livepeer {
+            playback_id: "abcdefgh12345678".to_string(),
+        });
+        relink_playback_id(Some(&video), Some(&moved));
+        assert!(check_playback_id(&other).is_ok());
+        assert_eq!(PLAYBACK_IDS.with(|ids| ids.borrow().get(&"abcdefgh12345678".to_string())), Some("video123".to_string()));
+
+        relink_playback_id(Some(&moved), None);
+        assert!(PLAYBACK_IDS.with(|ids| ids.borrow().is_empty()));
+    }
+}
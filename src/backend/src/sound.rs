@@ -0,0 +1,135 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::{borrow::Cow, collections::HashMap};
+
+const MAX_VALUE_SIZE: u32 = 1000;
+const MAX_TITLE_LENGTH: usize = 100;
+const MAX_DURATION_SEC: u32 = 10 * 60;
+
+/// Audio that videos can reuse
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Sound {
+    pub sound_id: String,
+    pub title: String,
+    pub creator_principal: Principal,
+    pub source_video_id: Option<String>, // Video the audio was taken from, if any
+    pub duration_sec: u32,
+    pub use_count: u32, // Videos currently using the sound
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrendingSound {
+    pub sound: Sound,
+    pub recent_uses: u32, // Videos that picked the sound within the trending window
+}
+
+pub fn validate_title(title: &str) -> Result<(), String> {
+    if title.trim().is_empty() || title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(format!(
+            "Sound title must be between 1 and {} characters",
+            MAX_TITLE_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_duration(duration_sec: u32) -> Result<(), String> {
+    if duration_sec == 0 || duration_sec > MAX_DURATION_SEC {
+        return Err(format!(
+            "Sound duration must be between 1 and {} seconds",
+            MAX_DURATION_SEC
+        ));
+    }
+    Ok(())
+}
+
+/// Counts uses newer than `since` per sound, most used first.
+/// Ties go to the sound with the most recent use.
+pub fn rank_by_recent_uses(
+    uses: impl Iterator<Item = (String, u64)>,
+    since: u64,
+) -> Vec<(String, u32)> {
+    let mut counts: HashMap<String, (u32, u64)> = HashMap::new();
+    for (sound_id, used_at) in uses.filter(|(_, used_at)| *used_at >= since) {
+        let entry = counts.entry(sound_id).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 = entry.1.max(used_at);
+    }
+
+    let mut ranked: Vec<(String, (u32, u64))> = counts.into_iter().collect();
+    ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+    ranked
+        .into_iter()
+        .map(|(sound_id, (count, _))| (sound_id, count))
+        .collect()
+}
+
+impl Storable for Sound {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_by_recent_uses() {
+        let uses = vec![
+            ("old".to_string(), 10),
+            ("old".to_string(), 20),
+            ("old".to_string(), 30),
+            ("a".to_string(), 100),
+            ("a".to_string(), 110),
+            ("b".to_string(), 105),
+            ("b".to_string(), 120),
+            ("c".to_string(), 150),
+        ];
+
+        let ranked = rank_by_recent_uses(uses.into_iter(), 100);
+
+        assert_eq!(
+            ranked,
+            vec![
+                ("b".to_string(), 2), // Same count as "a" but used more recently
+                ("a".to_string(), 2),
+                ("c".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_title("Original sound").is_ok());
+        assert!(validate_title(" ").is_err());
+        assert!(validate_duration(30).is_ok());
+        assert!(validate_duration(0).is_err());
+        assert!(validate_duration(601).is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let sound = Sound {
+            sound_id: "SN2".to_string(),
+            title: "Original sound".to_string(),
+            creator_principal: Principal::anonymous(),
+            source_video_id: Some("2NEpo7TZ".to_string()),
+            duration_sec: 15,
+            use_count: 3,
+            created_at: 1234567890,
+        };
+
+        assert_eq!(Sound::from_bytes(sound.to_bytes()), sound);
+    }
+}
@@ -0,0 +1,308 @@
+use candid::{CandidType, Deserialize, Principal};
+
+const MAX_URL_LENGTH: usize = 2048;
+const MAX_ASSET_KEY_LENGTH: usize = 256;
+const MAX_IPFS_PATH_LENGTH: usize = 256;
+const MAX_RENDITIONS: usize = 8;
+const MAX_RENDITION_LABEL_LENGTH: usize = 32;
+
+const BASE58_CHARS: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Where a video's media lives
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub enum StorageRef {
+    Livepeer { playback_id: String },
+    Ipfs { cid: String, path: Option<String> },
+    Canister { canister_id: Principal, asset_key: String },
+    Url { url: String },
+    /// Free-form reference stored before references were typed that matches no
+    /// known kind. Kept as is so the uploader can fix it; never accepted from clients.
+    Unrecognized { value: String },
+}
+
+/// An alternative encoding of the same video, e.g. a 720p transcode
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct Rendition {
+    pub label: String, // e.g. "720p"
+    pub width: u32,
+    pub height: u32,
+    pub bitrate_kbps: Option<u32>,
+    pub storage_ref: StorageRef,
+}
+
+/// Explicit change to a video's storage reference; omit it to leave the reference as is
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub enum StorageRefUpdate {
+    Set(StorageRef),
+    Clear,
+}
+
+impl StorageRef {
+    /// Checks the reference is well formed for its kind
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StorageRef::Livepeer { playback_id } => validate_playback_id(playback_id),
+            StorageRef::Ipfs { cid, path } => {
+                validate_cid(cid)?;
+                if let Some(path) = path {
+                    if path.len() > MAX_IPFS_PATH_LENGTH
+                        || path.split('/').any(|segment| segment == "..")
+                        || path.chars().any(|c| c.is_whitespace() || c.is_control())
+                    {
+                        return Err("Invalid IPFS path".to_string());
+                    }
+                }
+                Ok(())
+            }
+            StorageRef::Canister { asset_key, .. } => {
+                if !asset_key.starts_with('/') || asset_key.len() > MAX_ASSET_KEY_LENGTH {
+                    return Err(format!(
+                        "Asset key must start with '/' and be at most {} characters",
+                        MAX_ASSET_KEY_LENGTH
+                    ));
+                }
+                Ok(())
+            }
+            StorageRef::Url { url } => validate_url(url),
+            StorageRef::Unrecognized { .. } => Err("Unrecognized storage reference".to_string()),
+        }
+    }
+
+    /// Converts a storage reference stored as free-form text before references were
+    /// typed. IPFS references, URLs and Livepeer playback ids are recognized; any
+    /// value that doesn't validate as one of them is flagged as `Unrecognized`.
+    pub fn from_legacy(raw: &str) -> StorageRef {
+        let ipfs = raw
+            .strip_prefix("ipfs://")
+            .or_else(|| raw.strip_prefix("ipfs:"))
+            .or_else(|| raw.strip_prefix("/ipfs/"));
+        let candidate = if let Some(rest) = ipfs {
+            let (cid, path) = match rest.split_once('/') {
+                Some((cid, path)) if !path.is_empty() => (cid, Some(path.to_string())),
+                Some((cid, _)) => (cid, None),
+                None => (rest, None),
+            };
+            StorageRef::Ipfs {
+                cid: cid.to_string(),
+                path,
+            }
+        } else if raw.starts_with("https://") || raw.starts_with("http://") {
+            StorageRef::Url {
+                url: raw.to_string(),
+            }
+        } else {
+            StorageRef::Livepeer {
+                playback_id: raw.to_string(),
+            }
+        };
+
+        match candidate.validate() {
+            Ok(()) => candidate,
+            Err(_) => StorageRef::Unrecognized {
+                value: raw.to_string(),
+            },
+        }
+    }
+}
+
+impl Rendition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.label.is_empty() || self.label.len() > MAX_RENDITION_LABEL_LENGTH {
+            return Err(format!(
+                "Rendition label must be between 1 and {} characters",
+                MAX_RENDITION_LABEL_LENGTH
+            ));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err("Rendition dimensions must be positive".to_string());
+        }
+        self.storage_ref.validate()
+    }
+}
+
+/// Validates a full set of renditions for a video
+pub fn validate_renditions(renditions: &[Rendition]) -> Result<(), String> {
+    if renditions.len() > MAX_RENDITIONS {
+        return Err(format!("A video can have at most {} renditions", MAX_RENDITIONS));
+    }
+    for (index, rendition) in renditions.iter().enumerate() {
+        rendition.validate()?;
+        if renditions[..index].iter().any(|r| r.label == rendition.label) {
+            return Err(format!("Duplicate rendition label: {}", rendition.label));
+        }
+    }
+    Ok(())
+}
+
+/// Livepeer playback ids are short lowercase alphanumeric strings, e.g. "f5eese9wwl88k4g8"
+fn validate_playback_id(playback_id: &str) -> Result<(), String> {
+    let valid_length = (8..=64).contains(&playback_id.len());
+    let valid_chars = playback_id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    if valid_length && valid_chars {
+        Ok(())
+    } else {
+        Err("Invalid Livepeer playback ID".to_string())
+    }
+}
+
+/// Accepts CIDv0 ("Qm" + base58) and CIDv1 in the common multibase encodings
+fn validate_cid(cid: &str) -> Result<(), String> {
+    let valid = if cid.starts_with("Qm") {
+        cid.len() == 46 && cid.chars().all(|c| BASE58_CHARS.contains(c))
+    } else if let Some((prefix, body)) = cid.split_at_checked(1) {
+        let body_valid = match prefix {
+            // base32 lowercase, the default for CIDv1 ("bafy...")
+            "b" => body.chars().all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c)),
+            // base58btc
+            "z" => body.chars().all(|c| BASE58_CHARS.contains(c)),
+            // base36 lowercase, used for IPNS keys
+            "k" => body.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()),
+            // base16 lowercase
+            "f" => body.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+            _ => false,
+        };
+        body_valid && (40..=128).contains(&body.len())
+    } else {
+        false
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err("Invalid IPFS CID".to_string())
+    }
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| "URL must start with http:// or https://".to_string())?;
+
+    if host.is_empty() || host.starts_with('/') {
+        return Err("URL must include a host".to_string());
+    }
+    if url.len() > MAX_URL_LENGTH || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("Invalid URL".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CID_V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+    const CID_V1: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+    #[test]
+    fn test_validate_ipfs() {
+        let valid = StorageRef::Ipfs {
+            cid: CID_V0.to_string(),
+            path: None,
+        };
+        assert!(valid.validate().is_ok());
+
+        let with_path = StorageRef::Ipfs {
+            cid: CID_V1.to_string(),
+            path: Some("videos/clip.mp4".to_string()),
+        };
+        assert!(with_path.validate().is_ok());
+
+        let bad_cid = StorageRef::Ipfs {
+            cid: "Qm123".to_string(),
+            path: None,
+        };
+        assert!(bad_cid.validate().is_err());
+
+        let bad_path = StorageRef::Ipfs {
+            cid: CID_V1.to_string(),
+            path: Some("../secret".to_string()),
+        };
+        assert!(bad_path.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_livepeer_and_url() {
+        let playback = StorageRef::Livepeer {
+            playback_id: "f5eese9wwl88k4g8".to_string(),
+        };
+        assert!(playback.validate().is_ok());
+
+        let bad_playback = StorageRef::Livepeer {
+            playback_id: "not a playback id".to_string(),
+        };
+        assert!(bad_playback.validate().is_err());
+
+        let url = StorageRef::Url {
+            url: "https://example.com/video.mp4".to_string(),
+        };
+        assert!(url.validate().is_ok());
+
+        let bad_url = StorageRef::Url {
+            url: "ftp://example.com/video.mp4".to_string(),
+        };
+        assert!(bad_url.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_renditions() {
+        let rendition = Rendition {
+            label: "720p".to_string(),
+            width: 1280,
+            height: 720,
+            bitrate_kbps: Some(2500),
+            storage_ref: StorageRef::Livepeer {
+                playback_id: "f5eese9wwl88k4g8".to_string(),
+            },
+        };
+        assert!(validate_renditions(std::slice::from_ref(&rendition)).is_ok());
+        assert!(validate_renditions(&[rendition.clone(), rendition]).is_err());
+    }
+
+    #[test]
+    fn test_from_legacy() {
+        assert_eq!(
+            StorageRef::from_legacy(&format!("ipfs:{}", CID_V0)),
+            StorageRef::Ipfs {
+                cid: CID_V0.to_string(),
+                path: None,
+            }
+        );
+        assert_eq!(
+            StorageRef::from_legacy(&format!("ipfs://{}/video.mp4", CID_V1)),
+            StorageRef::Ipfs {
+                cid: CID_V1.to_string(),
+                path: Some("video.mp4".to_string()),
+            }
+        );
+        assert_eq!(
+            StorageRef::from_legacy("https://example.com/video.mp4"),
+            StorageRef::Url {
+                url: "https://example.com/video.mp4".to_string(),
+            }
+        );
+        assert_eq!(
+            StorageRef::from_legacy("f5eese9wwl88k4g8"),
+            StorageRef::Livepeer {
+                playback_id: "f5eese9wwl88k4g8".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_legacy_flags_unknown_values() {
+        for raw in ["s3://bucket/video.mp4", "My Video.mp4", "ipfs:Qm123", ""] {
+            let storage_ref = StorageRef::from_legacy(raw);
+            assert_eq!(
+                storage_ref,
+                StorageRef::Unrecognized {
+                    value: raw.to_string(),
+                }
+            );
+            assert!(storage_ref.validate().is_err());
+        }
+    }
+}
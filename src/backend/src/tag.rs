@@ -0,0 +1,255 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use caseless::default_case_fold_str;
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::{page::Page, video_metadata::VideoMetadata};
+
+pub const MAX_TAG_LENGTH: usize = 50;
+pub const MAX_TAGS_PER_VIDEO: usize = 30;
+
+const MAX_VALUE_SIZE: u32 = 100;
+const KEY_SEPARATOR: char = '\u{0}';
+
+/// An alternative spelling that is stored as its canonical tag
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagAlias {
+    pub alias: String,
+    pub canonical: String,
+}
+
+/// Running totals over the videos carrying a tag, not counting ones in the trash
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct TagStats {
+    pub video_count: u64,
+    pub total_views: u64,
+}
+
+/// A tag often used together with another one
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct RelatedTag {
+    pub tag: String,
+    pub shared_videos: u64, // Videos carrying both tags
+}
+
+/// Everything shown when browsing a tag
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagPage {
+    pub tag: String, // Canonical spelling of the requested tag
+    pub video_count: u64,
+    pub total_views: u64,
+    pub top_videos: Vec<VideoMetadata>,  // Most viewed, not paginated
+    pub recent_videos: Page<VideoMetadata>, // Newest first, paginated by the cursor
+    pub related_tags: Vec<RelatedTag>,
+}
+
+/// Key ordering a tag's entries by descending `rank`, such as a timestamp or view count
+pub fn ranked_key(tag: &str, rank: u64, id: &str) -> String {
+    format!("{}{}{:020}{}{}", tag, KEY_SEPARATOR, u64::MAX - rank, KEY_SEPARATOR, id)
+}
+
+/// Key counting the videos carrying both `tag` and `other`
+pub fn pair_key(tag: &str, other: &str) -> String {
+    format!("{}{}{}", tag, KEY_SEPARATOR, other)
+}
+
+/// Prefix shared by a tag's `ranked_key`s and `pair_key`s
+pub fn tag_prefix(tag: &str) -> String {
+    format!("{}{}", tag, KEY_SEPARATOR)
+}
+
+/// Last part of a `ranked_key` or `pair_key`: the id or the other tag
+pub fn key_suffix(key: &str) -> &str {
+    key.rsplit(KEY_SEPARATOR).next().unwrap_or_default()
+}
+
+/// Canonical form of a tag: case-folded (so "STRASSE" and "straße" match),
+/// without leading `#` and with runs of whitespace collapsed to one space.
+/// May be empty.
+pub fn normalize_tag(tag: &str) -> String {
+    let collapsed = tag
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    default_case_fold_str(&collapsed)
+}
+
+/// Normalizes the tags sent for a video, dropping duplicates and rejecting
+/// tags that are empty or too long
+pub fn validate_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_tag(tag);
+        if tag.is_empty() {
+            return Err("Tags can't be empty".to_string());
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(format!("Tags can be at most {} characters", MAX_TAG_LENGTH));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS_PER_VIDEO {
+        return Err(format!("A video can have at most {} tags", MAX_TAGS_PER_VIDEO));
+    }
+    Ok(normalized)
+}
+
+/// Best-effort normalization for tags stored before they were validated:
+/// empty tags are dropped and long ones truncated instead of rejected
+pub fn normalize_stored_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag: String = normalize_tag(tag).chars().take(MAX_TAG_LENGTH).collect();
+        let tag = tag.trim_end().to_string();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// `#hashtags` in free text, normalized, in order of first appearance.
+/// Hashtags longer than a tag can be are ignored.
+pub fn extract_hashtags(text: &str) -> Vec<String> {
+    let mut hashtags: Vec<String> = Vec::new();
+    for tag in marked_words(text, '#').iter().map(|word| normalize_tag(word)) {
+        if tag.chars().count() <= MAX_TAG_LENGTH && !hashtags.contains(&tag) {
+            hashtags.push(tag);
+        }
+    }
+    hashtags
+}
+
+/// `@mentions` in free text, lowercased, without the `@`
+pub fn extract_mentions(text: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    for mention in marked_words(text, '@').iter().map(|word| word.to_lowercase()) {
+        if !mentions.contains(&mention) {
+            mentions.push(mention);
+        }
+    }
+    mentions
+}
+
+/// Words following `marker` at the start of the text or after a character
+/// that isn't part of a word, so "C#" and "ada@example.com" don't count.
+/// Words are letters, digits and underscores.
+fn marked_words(text: &str, marker: char) -> Vec<String> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == marker && !previous.is_some_and(|p| is_word_char(p) || p == marker) {
+            let mut word = String::new();
+            while let Some(&next) = chars.peek().filter(|next| is_word_char(**next)) {
+                word.push(next);
+                chars.next();
+            }
+            previous = word.chars().last().or(Some(c));
+            if !word.is_empty() {
+                words.push(word);
+            }
+            continue;
+        }
+        previous = Some(c);
+    }
+    words
+}
+
+impl Storable for TagStats {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("Funny"), "funny");
+        assert_eq!(normalize_tag("  #Funny  "), "funny");
+        assert_eq!(normalize_tag("##Street   Food\t"), "street food");
+        assert_eq!(normalize_tag("ÉTÉ"), "été");
+        assert_eq!(normalize_tag("STRASSE"), normalize_tag("straße"));
+        assert_eq!(normalize_tag("ΣΊΣΥΦΟΣ"), normalize_tag("σίσυφος"));
+        assert_eq!(normalize_tag("#"), "");
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        assert_eq!(
+            validate_tags(&tags(&["Funny", "#funny", "cats"])).unwrap(),
+            tags(&["funny", "cats"])
+        );
+        assert!(validate_tags(&tags(&["  # "])).is_err());
+        assert!(validate_tags(&["a".repeat(MAX_TAG_LENGTH + 1)]).is_err());
+        assert!(validate_tags(&["a".repeat(MAX_TAG_LENGTH)]).is_ok());
+
+        let too_many: Vec<String> = (0..=MAX_TAGS_PER_VIDEO).map(|i| i.to_string()).collect();
+        assert!(validate_tags(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_normalize_stored_tags() {
+        let long = format!("{} tail", "a".repeat(MAX_TAG_LENGTH - 1));
+        assert_eq!(
+            normalize_stored_tags(&tags(&["Funny", "", "#FUNNY", &long])),
+            vec!["funny".to_string(), "a".repeat(MAX_TAG_LENGTH - 1)]
+        );
+    }
+
+    #[test]
+    fn test_extract_hashtags() {
+        assert_eq!(
+            extract_hashtags("Sunday #Cooking with #street_food! #cooking again"),
+            tags(&["cooking", "street_food"])
+        );
+        assert_eq!(extract_hashtags("C# tips, ##double and # alone"), Vec::<String>::new());
+        assert_eq!(extract_hashtags(&format!("#{}", "a".repeat(MAX_TAG_LENGTH + 1))), Vec::<String>::new());
+        assert_eq!(extract_hashtags("(#été)"), tags(&["été"]));
+    }
+
+    #[test]
+    fn test_extract_mentions() {
+        assert_eq!(
+            extract_mentions("Duet with @Ada and @0xAbC123, mail ada@example.com"),
+            tags(&["ada", "0xabc123"])
+        );
+    }
+
+    #[test]
+    fn test_ranked_keys_sort_highest_first() {
+        let mut keys = [
+            ranked_key("cats", 5, "a"),
+            ranked_key("cats", 500, "b"),
+            ranked_key("cats", 0, "c"),
+            ranked_key("cat", 1, "d"),
+        ];
+        keys.sort();
+        let ids: Vec<&str> = keys.iter().map(|key| key_suffix(key)).collect();
+        assert_eq!(ids, vec!["d", "b", "a", "c"]);
+        assert!(keys[1].starts_with(&tag_prefix("cats")));
+        assert!(!keys[0].starts_with(&tag_prefix("cats")));
+        assert_eq!(key_suffix(&pair_key("cats", "funny")), "funny");
+    }
+}
@@ -12,10 +12,11 @@ pub struct TipRecord {
     pub amount: u64,
     pub tx_hash: String,
     pub timestamp: u64,
+    pub share_bps: Option<u32>, // Part of the tip this entry records, None for tips recorded before splits
 }
 
 impl Storable for TipRecord {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
@@ -34,7 +35,7 @@ impl Storable for TipRecord {
 pub struct TipRecordList(pub Vec<TipRecord>);
 
 impl Storable for TipRecordList {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.0).unwrap())
     }
 
@@ -42,9 +43,9 @@ impl Storable for TipRecordList {
         Self(Decode!(bytes.as_ref(), Vec<TipRecord>).unwrap())
     }
 
-    // A higher bound since this is a vector
+    // A higher bound since this is a vector, and split tips add an entry per co-creator
     const BOUND: Bound = Bound::Bounded {
-        max_size: 10000,
+        max_size: 100_000,
         is_fixed_size: false,
     };
 }
@@ -64,6 +65,7 @@ mod tests {
             amount: 1000000000000000000, // 1 ETH in wei
             tx_hash: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
             timestamp: 1234567890,
+            share_bps: None,
         };
 
         // Test to_bytes
@@ -95,6 +97,7 @@ mod tests {
                 amount: 1000000000000000000, // 1 ETH in wei
                 tx_hash: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
                 timestamp: 1234567890,
+                share_bps: None,
             },
             TipRecord {
                 from_addr: "0x123456789abcdef0123456789abcdef012345678".to_string(),
@@ -103,14 +106,16 @@ mod tests {
                 amount: 500000000000000000, // 0.5 ETH in wei
                 tx_hash: "0x9876543210fedcba9876543210fedcba9876543210fedcba9876543210fedcba".to_string(),
                 timestamp: 1234567891,
+                share_bps: None,
             },
         ];
 
         // Test to_bytes for vector
-        let bytes = tips.to_bytes();
+        let list = TipRecordList(tips.clone());
+        let bytes = list.to_bytes();
         
         // Test from_bytes for vector
-        let deserialized_tips = Vec::<TipRecord>::from_bytes(bytes);
+        let deserialized_tips = TipRecordList::from_bytes(bytes).0;
         
         // Verify they match
         assert_eq!(tips, deserialized_tips);
@@ -121,7 +126,7 @@ mod tests {
         map.insert("video123".to_string(), tips);
         
         // Verify we can add multiple tips to the same key
-        let mut tips_for_video = map.get("video123".to_string()).unwrap().clone();
+        let mut tips_for_video = map.get("video123").unwrap().clone();
         tips_for_video.push(TipRecord {
             from_addr: "0xfedcba9876543210fedcba9876543210fedcba98".to_string(),
             to_addr: "0xabcdef0123456789abcdef0123456789abcdef01".to_string(),
@@ -129,16 +134,17 @@ mod tests {
             amount: 250000000000000000, // 0.25 ETH in wei
             tx_hash: "0x456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef0123".to_string(),
             timestamp: 1234567892,
+            share_bps: None,
         });
         
         map.insert("video123".to_string(), tips_for_video);
         
         // Verify we have 3 tips now
-        assert_eq!(map.get("video123".to_string()).unwrap().len(), 3);
+        assert_eq!(map.get("video123").unwrap().len(), 3);
         
         // Verify the total amount tipped
         let total_amount: u64 = map
-            .get("video123".to_string())
+            .get("video123")
             .unwrap()
             .iter()
             .map(|tip| tip.amount)
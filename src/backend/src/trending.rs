@@ -0,0 +1,199 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+const MAX_ACTIVITY_SIZE: u32 = 100;
+
+/// Time scale trending scores decay over
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TrendingWindow {
+    Hour,
+    Day,
+    Week,
+}
+
+impl TrendingWindow {
+    pub const ALL: [TrendingWindow; 3] = [TrendingWindow::Hour, TrendingWindow::Day, TrendingWindow::Week];
+
+    /// Activity this old counts for 1/e of fresh activity
+    pub fn seconds(self) -> u64 {
+        match self {
+            TrendingWindow::Hour => 60 * 60,
+            TrendingWindow::Day => 24 * 60 * 60,
+            TrendingWindow::Week => 7 * 24 * 60 * 60,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            TrendingWindow::Hour => 0,
+            TrendingWindow::Day => 1,
+            TrendingWindow::Week => 2,
+        }
+    }
+}
+
+/// Decayed upload and view activity for a tag, one score per window
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TagActivity {
+    pub scores: Vec<f64>, // Indexed like TrendingWindow::ALL
+    pub updated_at: u64,  // Seconds; when the scores were last decayed
+}
+
+impl TagActivity {
+    /// Scores decayed to `now`
+    pub fn scores_at(&self, now: u64) -> Vec<f64> {
+        let elapsed = now.saturating_sub(self.updated_at) as f64;
+        TrendingWindow::ALL
+            .iter()
+            .map(|window| {
+                let score = self.scores.get(window.index()).copied().unwrap_or_default();
+                score * (-elapsed / window.seconds() as f64).exp()
+            })
+            .collect()
+    }
+
+    /// Adds activity of the given weight happening at `now`
+    pub fn record(&mut self, weight: f64, now: u64) {
+        let now = now.max(self.updated_at);
+        self.scores = self.scores_at(now).into_iter().map(|score| score + weight).collect();
+        self.updated_at = now;
+    }
+
+    /// Adds `other`'s activity decayed to `now`, or takes it back when `sign`
+    /// is negative. Scores never drop below zero.
+    pub fn merge(&mut self, other: &TagActivity, sign: f64, now: u64) {
+        let now = now.max(self.updated_at).max(other.updated_at);
+        self.scores = self
+            .scores_at(now)
+            .into_iter()
+            .zip(other.scores_at(now))
+            .map(|(score, other)| (score + sign * other).max(0.0))
+            .collect();
+        self.updated_at = now;
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrendingTag {
+    pub tag: String,
+    pub score: f64,
+}
+
+/// Leaderboard for one window, as of the last refresh
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrendingTags {
+    pub window: TrendingWindow,
+    pub tags: Vec<TrendingTag>, // Highest score first
+    pub refreshed_at: u64,      // Seconds; 0 before the first refresh
+}
+
+/// Leaderboards for every window, indexed like TrendingWindow::ALL
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TrendingBoards {
+    pub boards: Vec<Vec<TrendingTag>>,
+    pub refreshed_at: u64,
+}
+
+impl TrendingBoards {
+    pub fn for_window(&self, window: TrendingWindow, limit: usize) -> TrendingTags {
+        TrendingTags {
+            window,
+            tags: self
+                .boards
+                .get(window.index())
+                .map(|tags| tags.iter().take(limit).cloned().collect())
+                .unwrap_or_default(),
+            refreshed_at: self.refreshed_at,
+        }
+    }
+}
+
+/// Keeps the `size` highest scoring tags, highest first; ties keep tag order
+pub fn top_tags(mut tags: Vec<TrendingTag>, size: usize) -> Vec<TrendingTag> {
+    tags.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+    tags.truncate(size);
+    tags
+}
+
+impl Storable for TagActivity {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_ACTIVITY_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+impl Storable for TrendingBoards {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score_at(activity: &TagActivity, window: TrendingWindow, now: u64) -> f64 {
+        activity.scores_at(now)[window.index()]
+    }
+
+    #[test]
+    fn test_activity_decays_per_window() {
+        let mut activity = TagActivity::default();
+        activity.record(1.0, 1_000);
+        activity.record(1.0, 1_000);
+
+        let hour_later = 1_000 + TrendingWindow::Hour.seconds();
+        let hour_score = score_at(&activity, TrendingWindow::Hour, hour_later);
+        let week_score = score_at(&activity, TrendingWindow::Week, hour_later);
+        assert!((hour_score - 2.0 / std::f64::consts::E).abs() < 1e-9);
+        assert!(week_score > 1.98 && week_score < 2.0);
+
+        // Recording later decays what was there first
+        activity.record(1.0, hour_later);
+        assert!((score_at(&activity, TrendingWindow::Hour, hour_later) - (hour_score + 1.0)).abs() < 1e-9);
+        assert_eq!(activity.updated_at, hour_later);
+
+        // Taking back part of the activity leaves the rest
+        let mut first = TagActivity::default();
+        first.record(2.0, 1_000);
+        activity.merge(&first, -1.0, hour_later);
+        assert!((score_at(&activity, TrendingWindow::Hour, hour_later) - 1.0).abs() < 1e-9);
+        assert!((score_at(&activity, TrendingWindow::Week, hour_later) - 1.0).abs() < 1e-9);
+        activity.merge(&first, 1.0, hour_later);
+        activity.merge(&first, -5.0, hour_later);
+        assert_eq!(activity.scores, vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_top_tags() {
+        let tag = |tag: &str, score: f64| TrendingTag {
+            tag: tag.to_string(),
+            score,
+        };
+        let top = top_tags(vec![tag("b", 1.0), tag("a", 1.0), tag("c", 3.0), tag("d", 0.5)], 3);
+        let names: Vec<&str> = top.iter().map(|t| t.tag.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+
+        let boards = TrendingBoards {
+            boards: vec![top.clone(), vec![], vec![]],
+            refreshed_at: 7,
+        };
+        assert_eq!(boards.for_window(TrendingWindow::Hour, 1).tags, vec![tag("c", 3.0)]);
+        assert!(boards.for_window(TrendingWindow::Week, 10).tags.is_empty());
+    }
+}
@@ -1,7 +1,9 @@
-use candid::{CandidType, Decode, Deserialize, Encode};
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use std::borrow::Cow;
 
+use crate::search_index::tokenize;
+
 const MAX_VALUE_SIZE: u32 = 500;
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
@@ -11,8 +13,52 @@ pub struct UserProfile {
     pub avatar_url: String,
 }
 
+/// A profile found by `search_profiles`, with lightweight stats
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileSearchResult {
+    pub principal: Principal,
+    pub profile: UserProfile,
+    pub follower_count: u64,
+    pub video_count: u64,
+}
+
+/// Follower and video counts kept for each creator so profile search doesn't
+/// recount them per result
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProfileStats {
+    pub follower_count: u64,
+    pub video_count: u64, // Videos not in the trash
+}
+
+impl Storable for ProfileStats {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 100,
+        is_fixed_size: false,
+    };
+}
+
+impl UserProfile {
+    /// Terms the profile can be found by: the words of its name and its address
+    pub fn search_terms(&self) -> Vec<String> {
+        let mut terms = tokenize(&self.name);
+        terms.push(self.evm_address.to_lowercase());
+        terms.retain(|term| !term.is_empty());
+        terms.sort();
+        terms.dedup();
+        terms
+    }
+}
+
 impl Storable for UserProfile {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
@@ -50,4 +96,14 @@ mod tests {
         // Specifically verify the EVM address was preserved correctly
         assert_eq!(profile.evm_address, deserialized_profile.evm_address);
     }
+
+    #[test]
+    fn test_search_terms() {
+        let profile = UserProfile {
+            evm_address: "0xAbC123".to_string(),
+            name: "Ada  Lovelace-King ada".to_string(),
+            avatar_url: String::new(),
+        };
+        assert_eq!(profile.search_terms(), vec!["0xabc123", "ada", "king", "lovelace"]);
+    }
 }
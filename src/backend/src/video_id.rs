@@ -0,0 +1,143 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+
+const MAX_VALUE_SIZE: u32 = 100; // Counter plus a 32 byte salt
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Number of salted hash bytes appended to the counter so ids can't be enumerated
+const ID_TAG_LEN: usize = 4;
+
+/// Persistent state used to mint video ids
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct VideoIdState {
+    pub next_id: u64,
+    pub salt: Vec<u8>, // Random bytes from raw_rand, empty until first use
+}
+
+impl VideoIdState {
+    /// Mints the next video id and advances the counter.
+    ///
+    /// The id is the big-endian counter (leading zero bytes stripped) followed by a
+    /// short tag derived from the salt, encoded as base58. The counter alone makes
+    /// ids collision-free, the tag makes them unpredictable.
+    pub fn mint(&mut self) -> String {
+        let counter = self.next_id;
+        self.next_id += 1;
+
+        let counter_bytes = counter.to_be_bytes();
+        let first_significant = counter_bytes
+            .iter()
+            .position(|b| *b != 0)
+            .unwrap_or(counter_bytes.len() - 1);
+
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        hasher.update(counter_bytes);
+        let tag = hasher.finalize();
+
+        let mut bytes = counter_bytes[first_significant..].to_vec();
+        bytes.extend_from_slice(&tag[..ID_TAG_LEN]);
+        encode_base58(&bytes)
+    }
+}
+
+impl Storable for VideoIdState {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
+
+/// Encodes bytes using the Bitcoin base58 alphabet (URL-safe, no ambiguous characters)
+pub fn encode_base58(input: &[u8]) -> String {
+    let leading_zeros = input.iter().take_while(|b| **b == 0).count();
+
+    // Repeated division of the big-endian number by 58, collecting remainders
+    let mut digits: Vec<u8> = Vec::with_capacity(input.len() * 138 / 100 + 1);
+    for byte in &input[leading_zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = String::with_capacity(leading_zeros + digits.len());
+    encoded.extend(std::iter::repeat_n('1', leading_zeros));
+    encoded.extend(digits.iter().rev().map(|d| BASE58_ALPHABET[*d as usize] as char));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_base58_known_vectors() {
+        assert_eq!(encode_base58(b""), "");
+        assert_eq!(encode_base58(&[0]), "1");
+        assert_eq!(encode_base58(&[0, 0, 1]), "112");
+        assert_eq!(encode_base58(b"hello world"), "StV1DL6CwTryKyV");
+    }
+
+    #[test]
+    fn test_minted_ids_are_unique_and_url_safe() {
+        let mut state = VideoIdState {
+            next_id: 0,
+            salt: vec![7; 32],
+        };
+
+        let ids: Vec<String> = (0..1000).map(|_| state.mint()).collect();
+        let unique: HashSet<&String> = ids.iter().collect();
+
+        assert_eq!(unique.len(), ids.len());
+        assert_eq!(state.next_id, 1000);
+        assert!(ids
+            .iter()
+            .all(|id| id.bytes().all(|b| BASE58_ALPHABET.contains(&b))));
+    }
+
+    #[test]
+    fn test_salt_changes_ids() {
+        let mut first = VideoIdState {
+            next_id: 42,
+            salt: vec![1; 32],
+        };
+        let mut second = VideoIdState {
+            next_id: 42,
+            salt: vec![2; 32],
+        };
+
+        assert_ne!(first.mint(), second.mint());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let state = VideoIdState {
+            next_id: 12345,
+            salt: vec![9; 32],
+        };
+
+        let bytes = state.to_bytes();
+        let deserialized_state = VideoIdState::from_bytes(bytes);
+
+        assert_eq!(state, deserialized_state);
+    }
+}
@@ -2,7 +2,51 @@ use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use std::borrow::Cow;
 
-const MAX_VALUE_SIZE: u32 = 1000; // Increased for video metadata
+use crate::{
+    caption_track::{validate_language, CaptionTrack},
+    chapter::{parse_chapters, validate_chapters, Chapter},
+    clip::ClipSource,
+    credit::{Credit, CreditStatus},
+    processing_status::ProcessingStatus,
+    remix::RemixSource,
+    storage_ref::{Rendition, StorageRef},
+};
+
+const MAX_TITLE_LENGTH: usize = 150;
+const MAX_DESCRIPTION_LENGTH: usize = 5000;
+const MAX_DURATION_SEC: u32 = 12 * 60 * 60;
+const MAX_ASPECT_RATIO: u32 = 4; // Widest (or tallest) shape accepted, as long side : short side
+
+/// Display shape of the video, e.g. 16:9 or 9:16
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct AspectRatio {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AspectRatio {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err("Aspect ratio sides must be greater than zero".to_string());
+        }
+        let (long, short) = (self.width.max(self.height) as u64, self.width.min(self.height) as u64);
+        if long > short * MAX_ASPECT_RATIO as u64 {
+            return Err(format!(
+                "Aspect ratio cannot be wider or taller than {}:1",
+                MAX_ASPECT_RATIO
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Audience the video is suitable for
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ContentRating {
+    General,
+    Teen,
+    Mature,
+}
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
 pub struct VideoMetadata {
@@ -10,28 +54,316 @@ pub struct VideoMetadata {
     pub uploader_principal: Principal,
     pub tags: Vec<String>,
     pub title: String,
-    pub storage_ref: Option<String>, // Reference to chunk storage or IPFS
+    pub storage_ref: Option<StorageRef>,
+    pub renditions: Vec<Rendition>,
     pub timestamp: u64,
+    pub deleted_at: Option<u64>, // Set while the video sits in the trash
+    pub processing_status: Option<ProcessingStatus>, // None for videos created before tracking
+    pub captions: Option<Vec<CaptionTrack>>,         // None until a track is added
+    pub description: Option<String>,
+    pub duration_sec: Option<u32>,
+    pub chapters: Option<Vec<Chapter>>, // Sorted by start time
+    pub aspect_ratio: Option<AspectRatio>,
+    pub primary_language: Option<String>, // BCP 47 tag
+    pub content_rating: Option<ContentRating>,
+    pub allow_comments: Option<bool>, // None for videos created before the flag, treated as true
+    pub content_hash: Option<String>, // Lowercase hex SHA-256 of the uploaded file
+    pub remix_of: Option<RemixSource>, // Set on create for duets, stitches and reactions
+    pub allow_remixes: Option<bool>,   // None is treated as true
+    pub sound_id: Option<String>,      // Reused audio from the sounds library
+    pub credits: Option<Vec<Credit>>,  // Co-creators sharing the video's tips
+    pub clip_of: Option<ClipSource>,   // Set for clips, which play a segment of another video
+    pub mentions: Option<Vec<Principal>>, // Profiles @mentioned in the title or description
+    pub chapters_from_description: Option<bool>, // Chapters were parsed from the description, None is treated as false
+}
+
+/// Optional details passed to `create_video_metadata` and `update_video_metadata`.
+/// Fields left as None are not changed.
+#[derive(CandidType, Deserialize, Debug, Clone, Default)]
+pub struct VideoDetails {
+    pub description: Option<String>, // An empty description clears it
+    pub duration_sec: Option<u32>,
+    pub chapters: Option<Vec<Chapter>>, // An empty list clears the chapters
+    pub aspect_ratio: Option<AspectRatio>,
+    pub primary_language: Option<String>, // An empty tag clears it
+    pub content_rating: Option<ContentRating>,
+    pub allow_comments: Option<bool>,
+    pub allow_remixes: Option<bool>,
+    pub sound_id: Option<String>, // Must exist in the sounds library, an empty id clears it
+}
+
+impl From<&VideoMetadata> for VideoDetails {
+    /// Details setting every field to the video's current value, to re-validate it
+    fn from(metadata: &VideoMetadata) -> Self {
+        VideoDetails {
+            description: Some(metadata.description.clone().unwrap_or_default()),
+            duration_sec: metadata.duration_sec,
+            // Chapters parsed from the description are parsed again from it
+            chapters: match metadata.chapters_parsed() {
+                true => None,
+                false => Some(metadata.chapters.clone().unwrap_or_default()),
+            },
+            aspect_ratio: metadata.aspect_ratio,
+            primary_language: Some(metadata.primary_language.clone().unwrap_or_default()),
+            content_rating: metadata.content_rating,
+            allow_comments: metadata.allow_comments,
+            allow_remixes: metadata.allow_remixes,
+            sound_id: Some(metadata.sound_id.clone().unwrap_or_default()),
+        }
+    }
+}
+
+/// Everything `create_video_metadata` needs for a new video.
+///
+/// `idempotency_key` makes retries of the same upload return the first video.
+/// `content_hash` is the hex SHA-256 of the uploaded file, checked against the
+/// duplicate policy. `remix_of` links duets, stitches and reactions to their
+/// source and `renditions` lists alternative encodings of the same video.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct CreateVideoRequest {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub storage_ref: Option<StorageRef>,
+    pub idempotency_key: Option<String>,
+    pub details: Option<VideoDetails>,
+    pub content_hash: Option<String>,
+    pub remix_of: Option<RemixSource>,
+    pub renditions: Option<Vec<Rendition>>,
+}
+
+/// Shape of video metadata stored before storage references were typed
+#[derive(CandidType, Deserialize)]
+struct LegacyVideoMetadata {
+    video_id: String,
+    uploader_principal: Principal,
+    tags: Vec<String>,
+    title: String,
+    storage_ref: Option<String>,
+    timestamp: u64,
+    deleted_at: Option<u64>,
+}
+
+impl From<LegacyVideoMetadata> for VideoMetadata {
+    fn from(legacy: LegacyVideoMetadata) -> Self {
+        VideoMetadata {
+            video_id: legacy.video_id,
+            uploader_principal: legacy.uploader_principal,
+            tags: legacy.tags,
+            title: legacy.title,
+            storage_ref: legacy
+                .storage_ref
+                .as_deref()
+                .filter(|raw| !raw.is_empty())
+                .map(StorageRef::from_legacy),
+            renditions: Vec::new(),
+            timestamp: legacy.timestamp,
+            deleted_at: legacy.deleted_at,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+}
+
+impl VideoMetadata {
+    /// Whether the video has been soft deleted and should be hidden from listings
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Current processing status; videos created before tracking are treated as ready
+    pub fn status(&self) -> ProcessingStatus {
+        self.processing_status
+            .clone()
+            .unwrap_or(ProcessingStatus::Ready)
+    }
+
+    /// Whether viewers can post comments on the video
+    pub fn comments_allowed(&self) -> bool {
+        self.allow_comments.unwrap_or(true)
+    }
+
+    /// Whether other videos can be created as remixes of this one
+    pub fn remixes_allowed(&self) -> bool {
+        self.allow_remixes.unwrap_or(true)
+    }
+
+    /// Credits the co-creators have accepted
+    pub fn accepted_credits(&self) -> impl Iterator<Item = &Credit> {
+        self.credits
+            .iter()
+            .flatten()
+            .filter(|credit| credit.status == CreditStatus::Accepted)
+    }
+
+    /// Whether the chapters were parsed from the description rather than set by the uploader
+    pub fn chapters_parsed(&self) -> bool {
+        self.chapters_from_description.unwrap_or(false)
+    }
+
+    /// Whether the video should appear in public feeds and search
+    pub fn is_listed(&self) -> bool {
+        !self.is_deleted() && self.status() == ProcessingStatus::Ready
+    }
+
+    /// Applies and validates the given details, and checks the title.
+    ///
+    /// Chapters are checked against the video's duration. Unless the uploader
+    /// has set chapters, timestamps at the start of description lines ("2:30
+    /// Setup") become the chapters, provided there are at least two of them.
+    /// Chapters parsed that way follow later description edits and are cleared
+    /// once the description no longer has them.
+    pub fn apply_details(&mut self, details: VideoDetails) -> Result<(), String> {
+        if self.title.chars().count() > MAX_TITLE_LENGTH {
+            return Err(format!("Title cannot exceed {} characters", MAX_TITLE_LENGTH));
+        }
+        if let Some(duration_sec) = details.duration_sec {
+            if duration_sec == 0 || duration_sec > MAX_DURATION_SEC {
+                return Err(format!(
+                    "Duration must be between 1 and {} seconds",
+                    MAX_DURATION_SEC
+                ));
+            }
+        }
+        if let Some(aspect_ratio) = &details.aspect_ratio {
+            aspect_ratio.validate()?;
+        }
+        let primary_language = match details.primary_language {
+            Some(language) if language.is_empty() => None,
+            Some(language) => {
+                validate_language(&language)?;
+                Some(language)
+            }
+            None => self.primary_language.clone(),
+        };
+        let duration_sec = details.duration_sec.or(self.duration_sec);
+
+        let mut description = self.description.clone();
+        let mut parsed_chapters = None;
+        if let Some(new_description) = details.description {
+            let new_description = new_description.trim();
+            if new_description.chars().count() > MAX_DESCRIPTION_LENGTH {
+                return Err(format!(
+                    "Description cannot exceed {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                ));
+            }
+            let chapters = parse_chapters(new_description);
+            // Markers that don't fit the video are left as plain description text
+            parsed_chapters = Some(match chapters.len() >= 2 {
+                true => validate_chapters(chapters, duration_sec).unwrap_or_default(),
+                false => Vec::new(),
+            });
+            description = Some(new_description.to_string()).filter(|d| !d.is_empty());
+        }
+
+        // The description only owns the chapters while the uploader hasn't set any
+        let description_owns_chapters = self.chapters.is_none() || self.chapters_parsed();
+        let (chapters, from_description) = match (details.chapters, parsed_chapters) {
+            (Some(chapters), _) => (validate_chapters(chapters, duration_sec)?, false),
+            (None, Some(parsed)) if description_owns_chapters => {
+                let from_description = !parsed.is_empty();
+                (parsed, from_description)
+            }
+            // Existing chapters must still fit if the duration changed
+            (None, _) => (
+                validate_chapters(self.chapters.clone().unwrap_or_default(), duration_sec)?,
+                self.chapters_parsed(),
+            ),
+        };
+
+        self.duration_sec = duration_sec;
+        self.description = description;
+        self.chapters = Some(chapters).filter(|c| !c.is_empty());
+        self.chapters_from_description = from_description.then_some(true);
+        self.primary_language = primary_language;
+        if details.aspect_ratio.is_some() {
+            self.aspect_ratio = details.aspect_ratio;
+        }
+        if details.content_rating.is_some() {
+            self.content_rating = details.content_rating;
+        }
+        if details.allow_comments.is_some() {
+            self.allow_comments = details.allow_comments;
+        }
+        if details.allow_remixes.is_some() {
+            self.allow_remixes = details.allow_remixes;
+        }
+        if let Some(sound_id) = details.sound_id {
+            self.sound_id = Some(sound_id).filter(|id| !id.is_empty());
+        }
+        Ok(())
+    }
 }
 
 impl Storable for VideoMetadata {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+        // Legacy entries have no renditions field, so they fail to decode as the
+        // current shape and are migrated by parsing their text storage_ref
+        Decode!(bytes.as_ref(), Self)
+            .or_else(|_| Decode!(bytes.as_ref(), LegacyVideoMetadata).map(VideoMetadata::from))
+            .unwrap()
     }
 
-    const BOUND: Bound = Bound::Bounded {
-        max_size: MAX_VALUE_SIZE,
-        is_fixed_size: false,
-    };
+    // Renditions, caption tracks, a description and chapters that each pass
+    // validation can together outgrow any fixed bound
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Listed video with just a title and tags, for tests across the crate
+#[cfg(test)]
+pub fn test_video(video_id: &str, uploader: Principal, tags: &[&str], timestamp: u64) -> VideoMetadata {
+    VideoMetadata {
+        video_id: video_id.to_string(),
+        uploader_principal: uploader,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        title: format!("Video {}", video_id),
+        storage_ref: None,
+        renditions: vec![],
+        timestamp,
+        deleted_at: None,
+        processing_status: Some(ProcessingStatus::Ready),
+        captions: None,
+        description: None,
+        duration_sec: None,
+        chapters: None,
+        aspect_ratio: None,
+        primary_language: None,
+        content_rating: None,
+        allow_comments: None,
+        content_hash: None,
+        remix_of: None,
+        allow_remixes: None,
+        sound_id: None,
+        credits: None,
+        clip_of: None,
+        mentions: None,
+        chapters_from_description: None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::remix::RemixType;
 
     #[test]
     fn test_serialization() {
@@ -47,8 +379,52 @@ mod tests {
             uploader_principal: principal,
             tags: vec!["funny".to_string(), "short".to_string()],
             title: "Test Video".to_string(),
-            storage_ref: Some("ipfs://QmTest123".to_string()),
+            storage_ref: Some(StorageRef::Ipfs {
+                cid: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+                path: None,
+            }),
+            renditions: vec![Rendition {
+                label: "720p".to_string(),
+                width: 1280,
+                height: 720,
+                bitrate_kbps: Some(2500),
+                storage_ref: StorageRef::Livepeer {
+                    playback_id: "f5eese9wwl88k4g8".to_string(),
+                },
+            }],
             timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: Some(ProcessingStatus::Processing),
+            captions: Some(vec![CaptionTrack {
+                language: "en".to_string(),
+                label: "English".to_string(),
+                is_default: true,
+                url_path: "/captions/video123/en.vtt".to_string(),
+                cue_count: 12,
+            }]),
+            description: Some("0:00 Intro\n1:30 Main".to_string()),
+            duration_sec: Some(240),
+            chapters: Some(vec![
+                Chapter { start_sec: 0, title: "Intro".to_string() },
+                Chapter { start_sec: 90, title: "Main".to_string() },
+            ]),
+            aspect_ratio: Some(AspectRatio { width: 9, height: 16 }),
+            primary_language: Some("en".to_string()),
+            content_rating: Some(ContentRating::General),
+            allow_comments: Some(false),
+            content_hash: Some(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            ),
+            remix_of: Some(RemixSource {
+                parent_video_id: "2NEpo7TZ".to_string(),
+                remix_type: RemixType::Stitch,
+            }),
+            allow_remixes: Some(true),
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
         };
 
         // Test to_bytes
@@ -66,7 +442,15 @@ mod tests {
         assert_eq!(metadata.tags, deserialized_metadata.tags);
         assert_eq!(metadata.title, deserialized_metadata.title);
         assert_eq!(metadata.storage_ref, deserialized_metadata.storage_ref);
+        assert_eq!(metadata.renditions, deserialized_metadata.renditions);
         assert_eq!(metadata.timestamp, deserialized_metadata.timestamp);
+        assert_eq!(metadata.deleted_at, deserialized_metadata.deleted_at);
+        assert_eq!(metadata.processing_status, deserialized_metadata.processing_status);
+        assert_eq!(metadata.captions, deserialized_metadata.captions);
+        assert_eq!(metadata.chapters, deserialized_metadata.chapters);
+        assert_eq!(metadata.aspect_ratio, deserialized_metadata.aspect_ratio);
+        assert_eq!(metadata.allow_comments, deserialized_metadata.allow_comments);
+        assert_eq!(metadata.remix_of, deserialized_metadata.remix_of);
     }
 
     #[test]
@@ -84,7 +468,32 @@ mod tests {
             tags: vec!["funny".to_string(), "short".to_string(), "trending".to_string()],
             title: "Test Video".to_string(),
             storage_ref: None,
+            renditions: vec![],
             timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: Some(ProcessingStatus::Processing),
+            captions: Some(vec![CaptionTrack {
+                language: "en".to_string(),
+                label: "English".to_string(),
+                is_default: true,
+                url_path: "/captions/video123/en.vtt".to_string(),
+                cue_count: 12,
+            }]),
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
         };
 
         // Test to_bytes
@@ -100,4 +509,290 @@ mod tests {
         assert!(metadata.tags.contains(&"short".to_string()));
         assert!(metadata.tags.contains(&"trending".to_string()));
     }
-}
\ No newline at end of file
+
+    fn empty_metadata() -> VideoMetadata {
+        VideoMetadata {
+            video_id: "video123".to_string(),
+            uploader_principal: Principal::anonymous(),
+            tags: vec![],
+            title: "Test Video".to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    fn chapter(start_sec: u32, title: &str) -> Chapter {
+        Chapter {
+            start_sec,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_details_parses_description_chapters() {
+        let mut metadata = empty_metadata();
+
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("Walkthrough\n0:00 Intro\n2:30 Setup\n10:00 Too late".to_string()),
+                duration_sec: Some(300),
+                chapters: None,
+                ..Default::default()
+            })
+            .unwrap();
+
+        // The 10:00 marker is past the end of the video, so nothing is parsed
+        assert_eq!(metadata.chapters, None);
+
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("Walkthrough\n2:30 Setup\n0:00 Intro".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(
+            metadata.chapters,
+            Some(vec![chapter(0, "Intro"), chapter(150, "Setup")])
+        );
+    }
+
+    #[test]
+    fn test_description_chapters_follow_the_description() {
+        let mut metadata = empty_metadata();
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("0:00 Intro\n1:00 Setup".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(metadata.chapters_parsed());
+
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("0:00 Intro\n2:00 Demo".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.chapters, Some(vec![chapter(0, "Intro"), chapter(120, "Demo")]));
+
+        // Editing the timestamps out of the description drops the parsed chapters
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("Just a demo, see 2:00".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.chapters, None);
+        assert!(!metadata.chapters_parsed());
+    }
+
+    #[test]
+    fn test_description_keeps_uploader_chapters() {
+        let mut metadata = empty_metadata();
+        let chapters = vec![chapter(0, "Start"), chapter(30, "Middle")];
+        metadata
+            .apply_details(VideoDetails {
+                chapters: Some(chapters.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Timestamps in a later description don't replace chapters set by hand
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("0:00 Intro\n1:00 Setup".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.chapters, Some(chapters.clone()));
+        assert!(!metadata.chapters_parsed());
+
+        // Nor does removing them from the description
+        metadata
+            .apply_details(VideoDetails {
+                description: Some(String::new()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.chapters, Some(chapters));
+
+        // Setting chapters by hand takes them over from the description
+        let mut parsed = empty_metadata();
+        parsed
+            .apply_details(VideoDetails {
+                description: Some("0:00 Intro\n1:00 Setup".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        parsed
+            .apply_details(VideoDetails {
+                chapters: Some(vec![chapter(0, "Only")]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!parsed.chapters_parsed());
+        assert_eq!(VideoDetails::from(&parsed).chapters, Some(vec![chapter(0, "Only")]));
+    }
+
+    #[test]
+    fn test_apply_details_validates_chapters_against_duration() {
+        let mut metadata = empty_metadata();
+        metadata
+            .apply_details(VideoDetails {
+                chapters: Some(vec![chapter(0, "Intro"), chapter(200, "Outro")]),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Shortening the video below an existing chapter is rejected
+        let result = metadata.apply_details(VideoDetails {
+            duration_sec: Some(100),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        assert_eq!(metadata.duration_sec, None);
+        assert_eq!(metadata.chapters.as_ref().map(Vec::len), Some(2));
+
+        // Explicit chapters win over timestamps in the description
+        metadata
+            .apply_details(VideoDetails {
+                description: Some("0:00 A\n0:10 B".to_string()),
+                duration_sec: Some(100),
+                chapters: Some(vec![]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.chapters, None);
+    }
+
+    #[test]
+    fn test_apply_details_checks_title() {
+        let mut metadata = empty_metadata();
+        metadata.title = "é".repeat(MAX_TITLE_LENGTH);
+        assert!(metadata.apply_details(VideoDetails::default()).is_ok());
+        metadata.title.push('x');
+        assert!(metadata.apply_details(VideoDetails::default()).is_err());
+    }
+
+    #[test]
+    fn test_large_metadata_round_trips() {
+        // Valid fields can add up to far more than a bounded value would hold
+        let mut metadata = empty_metadata();
+        metadata.description = Some("é".repeat(MAX_DESCRIPTION_LENGTH));
+        metadata.chapters = Some((0..100).map(|i| chapter(i * 10, &"é".repeat(100))).collect());
+        metadata.renditions = (0..20)
+            .map(|i| Rendition {
+                label: format!("{}p", i),
+                width: 1280,
+                height: 720,
+                bitrate_kbps: None,
+                storage_ref: StorageRef::Url {
+                    url: format!("https://example.com/{}/{}", i, "a".repeat(2000)),
+                },
+            })
+            .collect();
+
+        let bytes = metadata.to_bytes();
+        assert!(bytes.len() > 64_000);
+        assert_eq!(VideoMetadata::from_bytes(bytes), metadata);
+    }
+
+    #[test]
+    fn test_apply_details_validates_fields() {
+        let mut metadata = empty_metadata();
+
+        let invalid = [
+            VideoDetails { duration_sec: Some(0), ..Default::default() },
+            VideoDetails { duration_sec: Some(13 * 60 * 60), ..Default::default() },
+            VideoDetails {
+                aspect_ratio: Some(AspectRatio { width: 0, height: 9 }),
+                ..Default::default()
+            },
+            VideoDetails {
+                aspect_ratio: Some(AspectRatio { width: 5, height: 1 }),
+                ..Default::default()
+            },
+            VideoDetails { primary_language: Some("english".to_string()), ..Default::default() },
+            VideoDetails { description: Some("x".repeat(5001)), ..Default::default() },
+        ];
+        for details in invalid {
+            assert!(metadata.apply_details(details).is_err());
+        }
+        assert_eq!(metadata, empty_metadata());
+
+        metadata
+            .apply_details(VideoDetails {
+                duration_sec: Some(60),
+                aspect_ratio: Some(AspectRatio { width: 16, height: 9 }),
+                primary_language: Some("pt-BR".to_string()),
+                content_rating: Some(ContentRating::Teen),
+                allow_comments: Some(false),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.primary_language.as_deref(), Some("pt-BR"));
+        assert!(!metadata.comments_allowed());
+
+        // Fields that are not passed keep their value, an empty language clears it
+        metadata
+            .apply_details(VideoDetails {
+                primary_language: Some(String::new()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(metadata.primary_language, None);
+        assert_eq!(metadata.content_rating, Some(ContentRating::Teen));
+        assert_eq!(metadata.duration_sec, Some(60));
+    }
+
+    #[test]
+    fn test_legacy_storage_ref_migration() {
+        let principal_bytes = [
+            10, 116, 101, 115, 116, 45, 112, 114, 105, 110, 99, 105, 112, 97, 108,
+        ];
+        let principal = Principal::from_slice(&principal_bytes);
+
+        let legacy = LegacyVideoMetadata {
+            video_id: "video123".to_string(),
+            uploader_principal: principal,
+            tags: vec!["funny".to_string()],
+            title: "Test Video".to_string(),
+            storage_ref: Some("ipfs:QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+            timestamp: 1234567890,
+            deleted_at: None,
+        };
+        let bytes = Encode!(&legacy).unwrap();
+
+        let migrated = VideoMetadata::from_bytes(Cow::Owned(bytes));
+
+        assert_eq!(migrated.video_id, "video123");
+        assert_eq!(
+            migrated.storage_ref,
+            Some(StorageRef::Ipfs {
+                cid: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+                path: None,
+            })
+        );
+        assert!(migrated.renditions.is_empty());
+    }
+}
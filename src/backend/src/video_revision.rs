@@ -0,0 +1,329 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::{
+    chapter::Chapter,
+    storage_ref::{Rendition, StorageRef},
+    video_metadata::{AspectRatio, ContentRating, VideoMetadata},
+};
+
+/// A single field change recorded in a revision, with the value before and after the edit
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub enum MetadataChange {
+    Title { old: String, new: String },
+    Tags { old: Vec<String>, new: Vec<String> },
+    StorageRef { old: Option<StorageRef>, new: Option<StorageRef> },
+    Renditions { old: Vec<Rendition>, new: Vec<Rendition> },
+    Description { old: Option<String>, new: Option<String> },
+    DurationSec { old: Option<u32>, new: Option<u32> },
+    Chapters { old: Option<Vec<Chapter>>, new: Option<Vec<Chapter>> },
+    AspectRatio { old: Option<AspectRatio>, new: Option<AspectRatio> },
+    PrimaryLanguage { old: Option<String>, new: Option<String> },
+    ContentRating { old: Option<ContentRating>, new: Option<ContentRating> },
+    AllowComments { old: Option<bool>, new: Option<bool> },
+    AllowRemixes { old: Option<bool>, new: Option<bool> },
+    SoundId { old: Option<String>, new: Option<String> },
+    ChaptersFromDescription { old: Option<bool>, new: Option<bool> },
+}
+
+impl MetadataChange {
+    /// Restores the field to its value before this change
+    pub fn undo(&self, metadata: &mut VideoMetadata) {
+        match self {
+            MetadataChange::Title { old, .. } => metadata.title = old.clone(),
+            MetadataChange::Tags { old, .. } => metadata.tags = old.clone(),
+            MetadataChange::StorageRef { old, .. } => metadata.storage_ref = old.clone(),
+            MetadataChange::Renditions { old, .. } => metadata.renditions = old.clone(),
+            MetadataChange::Description { old, .. } => metadata.description = old.clone(),
+            MetadataChange::DurationSec { old, .. } => metadata.duration_sec = *old,
+            MetadataChange::Chapters { old, .. } => metadata.chapters = old.clone(),
+            MetadataChange::AspectRatio { old, .. } => metadata.aspect_ratio = *old,
+            MetadataChange::PrimaryLanguage { old, .. } => metadata.primary_language = old.clone(),
+            MetadataChange::ContentRating { old, .. } => metadata.content_rating = *old,
+            MetadataChange::AllowComments { old, .. } => metadata.allow_comments = *old,
+            MetadataChange::AllowRemixes { old, .. } => metadata.allow_remixes = *old,
+            MetadataChange::SoundId { old, .. } => metadata.sound_id = old.clone(),
+            MetadataChange::ChaptersFromDescription { old, .. } => metadata.chapters_from_description = *old,
+        }
+    }
+}
+
+/// Lists the field changes between two versions of a video's metadata
+pub fn diff_metadata(before: &VideoMetadata, after: &VideoMetadata) -> Vec<MetadataChange> {
+    let mut changes = Vec::new();
+
+    if before.title != after.title {
+        changes.push(MetadataChange::Title {
+            old: before.title.clone(),
+            new: after.title.clone(),
+        });
+    }
+
+    if before.tags != after.tags {
+        changes.push(MetadataChange::Tags {
+            old: before.tags.clone(),
+            new: after.tags.clone(),
+        });
+    }
+
+    if before.storage_ref != after.storage_ref {
+        changes.push(MetadataChange::StorageRef {
+            old: before.storage_ref.clone(),
+            new: after.storage_ref.clone(),
+        });
+    }
+
+    if before.renditions != after.renditions {
+        changes.push(MetadataChange::Renditions {
+            old: before.renditions.clone(),
+            new: after.renditions.clone(),
+        });
+    }
+
+    if before.description != after.description {
+        changes.push(MetadataChange::Description {
+            old: before.description.clone(),
+            new: after.description.clone(),
+        });
+    }
+
+    if before.duration_sec != after.duration_sec {
+        changes.push(MetadataChange::DurationSec {
+            old: before.duration_sec,
+            new: after.duration_sec,
+        });
+    }
+
+    if before.chapters != after.chapters {
+        changes.push(MetadataChange::Chapters {
+            old: before.chapters.clone(),
+            new: after.chapters.clone(),
+        });
+    }
+
+    if before.aspect_ratio != after.aspect_ratio {
+        changes.push(MetadataChange::AspectRatio {
+            old: before.aspect_ratio,
+            new: after.aspect_ratio,
+        });
+    }
+
+    if before.primary_language != after.primary_language {
+        changes.push(MetadataChange::PrimaryLanguage {
+            old: before.primary_language.clone(),
+            new: after.primary_language.clone(),
+        });
+    }
+
+    if before.content_rating != after.content_rating {
+        changes.push(MetadataChange::ContentRating {
+            old: before.content_rating,
+            new: after.content_rating,
+        });
+    }
+
+    if before.allow_comments != after.allow_comments {
+        changes.push(MetadataChange::AllowComments {
+            old: before.allow_comments,
+            new: after.allow_comments,
+        });
+    }
+
+    if before.allow_remixes != after.allow_remixes {
+        changes.push(MetadataChange::AllowRemixes {
+            old: before.allow_remixes,
+            new: after.allow_remixes,
+        });
+    }
+
+    if before.sound_id != after.sound_id {
+        changes.push(MetadataChange::SoundId {
+            old: before.sound_id.clone(),
+            new: after.sound_id.clone(),
+        });
+    }
+
+    if before.chapters_from_description != after.chapters_from_description {
+        changes.push(MetadataChange::ChaptersFromDescription {
+            old: before.chapters_from_description,
+            new: after.chapters_from_description,
+        });
+    }
+
+    changes
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct VideoRevision {
+    pub revision: u32, // Starts at 1, revision 0 is the metadata as created
+    pub editor_principal: Principal,
+    pub timestamp: u64,
+    pub changes: Vec<MetadataChange>,
+    pub reverted_to: Option<u32>, // Set when this revision was produced by a revert
+}
+
+impl Storable for VideoRevision {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    // Holds the old and new value of every changed field, which can add up to
+    // more than twice the size of the metadata
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// Wrapper struct for Vec<VideoRevision>, a video's history as loaded for a revert
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
+pub struct VideoRevisionList(pub Vec<VideoRevision>);
+
+impl VideoRevisionList {
+    /// Number the next appended revision will get
+    pub fn next_revision(&self) -> u32 {
+        self.0.last().map(|r| r.revision + 1).unwrap_or(1)
+    }
+
+    /// Rebuilds the metadata as it was right after `revision` by undoing every later
+    /// revision, newest first. Fails if those revisions are no longer retained.
+    pub fn metadata_at(&self, current: &VideoMetadata, revision: u32) -> Result<VideoMetadata, String> {
+        if revision >= self.next_revision() {
+            return Err("Revision not found".to_string());
+        }
+        let oldest_retained = self.0.first().map(|r| r.revision).unwrap_or(1);
+        if revision + 1 < oldest_retained {
+            return Err("Revision is no longer retained".to_string());
+        }
+
+        let mut metadata = current.clone();
+        for later in self.0.iter().rev().take_while(|r| r.revision > revision) {
+            for change in later.changes.iter().rev() {
+                change.undo(&mut metadata);
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_metadata() -> VideoMetadata {
+        let principal_bytes = [
+            10, 116, 101, 115, 116, 45, 112, 114, 105, 110, 99, 105, 112, 97, 108,
+        ];
+        VideoMetadata {
+            video_id: "video123".to_string(),
+            uploader_principal: Principal::from_slice(&principal_bytes),
+            tags: vec!["funny".to_string()],
+            title: "First Title".to_string(),
+            storage_ref: None,
+            renditions: vec![],
+            timestamp: 1234567890,
+            deleted_at: None,
+            processing_status: None,
+            captions: None,
+            description: None,
+            duration_sec: None,
+            chapters: None,
+            aspect_ratio: None,
+            primary_language: None,
+            content_rating: None,
+            allow_comments: None,
+            content_hash: None,
+            remix_of: None,
+            allow_remixes: None,
+            sound_id: None,
+            credits: None,
+            clip_of: None,
+            mentions: None,
+            chapters_from_description: None,
+        }
+    }
+
+    fn revision(revision: u32, changes: Vec<MetadataChange>) -> VideoRevision {
+        VideoRevision {
+            revision,
+            editor_principal: Principal::anonymous(),
+            timestamp: 1234567890 + revision as u64,
+            changes,
+            reverted_to: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_only_reports_changed_fields() {
+        let before = test_metadata();
+        let mut after = before.clone();
+        after.title = "Second Title".to_string();
+
+        let changes = diff_metadata(&before, &after);
+
+        assert_eq!(
+            changes,
+            vec![MetadataChange::Title {
+                old: "First Title".to_string(),
+                new: "Second Title".to_string(),
+            }]
+        );
+        assert!(diff_metadata(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_metadata_at_undoes_later_revisions() {
+        let original = test_metadata();
+
+        let mut first_edit = original.clone();
+        first_edit.title = "Second Title".to_string();
+        let mut second_edit = first_edit.clone();
+        second_edit.tags = vec!["serious".to_string()];
+        second_edit.storage_ref = Some(StorageRef::Livepeer {
+            playback_id: "f5eese9wwl88k4g8".to_string(),
+        });
+
+        let revisions = VideoRevisionList(vec![
+            revision(1, diff_metadata(&original, &first_edit)),
+            revision(2, diff_metadata(&first_edit, &second_edit)),
+        ]);
+
+        assert_eq!(revisions.next_revision(), 3);
+        assert_eq!(revisions.metadata_at(&second_edit, 2).unwrap(), second_edit);
+        assert_eq!(revisions.metadata_at(&second_edit, 1).unwrap(), first_edit);
+        assert_eq!(revisions.metadata_at(&second_edit, 0).unwrap(), original);
+        assert!(revisions.metadata_at(&second_edit, 3).is_err());
+    }
+
+    #[test]
+    fn test_metadata_at_rejects_dropped_revisions() {
+        let current = test_metadata();
+        let revisions = VideoRevisionList(vec![revision(5, vec![]), revision(6, vec![])]);
+
+        assert!(revisions.metadata_at(&current, 4).is_ok());
+        assert!(revisions.metadata_at(&current, 3).is_err());
+    }
+
+    #[test]
+    fn test_revision_serialization() {
+        let revision = revision(
+            1,
+            vec![
+                MetadataChange::Tags {
+                    old: vec!["funny".to_string()],
+                    new: vec!["funny".to_string(), "short".to_string()],
+                },
+                MetadataChange::StorageRef {
+                    old: None,
+                    new: Some(StorageRef::Url {
+                        url: "https://example.com/video.mp4".to_string(),
+                    }),
+                },
+            ],
+        );
+
+        assert_eq!(VideoRevision::from_bytes(revision.to_bytes()), revision);
+    }
+}
@@ -0,0 +1,56 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use std::borrow::Cow;
+
+use crate::{tip_record::TipRecord, watch_event::WatchEvent};
+
+const MAX_VALUE_SIZE: u32 = 100;
+
+/// Running totals for a video, kept so rankings don't have to read its
+/// whole watch log and tip history
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct VideoStats {
+    pub views: u64,
+    pub likes: u64,
+    pub tips_total: u64, // Sum of tip amounts across all recipients
+}
+
+impl VideoStats {
+    pub fn record_view(&mut self, liked: bool) {
+        self.views += 1;
+        if liked {
+            self.likes += 1;
+        }
+    }
+
+    pub fn record_tips(&mut self, tips: &[TipRecord]) {
+        self.tips_total = tips
+            .iter()
+            .fold(self.tips_total, |total, tip| total.saturating_add(tip.amount));
+    }
+
+    /// Totals for a video's stored history
+    pub fn from_history(events: &[WatchEvent], tips: &[TipRecord]) -> Self {
+        let mut stats = VideoStats::default();
+        for event in events {
+            stats.record_view(event.liked);
+        }
+        stats.record_tips(tips);
+        stats
+    }
+}
+
+impl Storable for VideoStats {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
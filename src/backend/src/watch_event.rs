@@ -15,7 +15,7 @@ pub struct WatchEvent {
 }
 
 impl Storable for WatchEvent {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
@@ -34,7 +34,7 @@ impl Storable for WatchEvent {
 pub struct WatchEventList(pub Vec<WatchEvent>);
 
 impl Storable for WatchEventList {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
         Cow::Owned(Encode!(&self.0).unwrap())
     }
 
@@ -119,10 +119,11 @@ mod tests {
         ];
 
         // Test to_bytes for vector
-        let bytes = events.to_bytes();
+        let list = WatchEventList(events.clone());
+        let bytes = list.to_bytes();
         
         // Test from_bytes for vector
-        let deserialized_events = Vec::<WatchEvent>::from_bytes(bytes);
+        let deserialized_events = WatchEventList::from_bytes(bytes).0;
         
         // Verify they match
         assert_eq!(events, deserialized_events);
@@ -133,7 +134,7 @@ mod tests {
         map.insert("video123".to_string(), events);
         
         // Verify we can add multiple events to the same key
-        let mut events_for_video = map.get("video123".to_string()).unwrap().clone();
+        let mut events_for_video = map.get("video123").unwrap().clone();
         events_for_video.push(WatchEvent {
             user_principal: principal,
             video_id: "video123".to_string(),
@@ -146,6 +147,6 @@ mod tests {
         map.insert("video123".to_string(), events_for_video);
         
         // Verify we have 3 events now
-        assert_eq!(map.get("video123".to_string()).unwrap().len(), 3);
+        assert_eq!(map.get("video123").unwrap().len(), 3);
     }
 }
\ No newline at end of file
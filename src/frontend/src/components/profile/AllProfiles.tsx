@@ -6,36 +6,88 @@ import { UserProfile } from "../../../../backend/declarations/backend.did";
 import { faCircleNotch } from "@fortawesome/free-solid-svg-icons";
 import { useActor } from "../../ic/Actors";
 
+type ProfileEntry = {
+  principal: string;
+  profile: UserProfile;
+  followerCount?: bigint;
+  videoCount?: bigint;
+};
+
 export default function AllProfiles() {
-  const [profiles, setProfiles] = useState<[string, UserProfile][]>([]);
+  const [profiles, setProfiles] = useState<ProfileEntry[]>([]);
+  const [query, setQuery] = useState("");
   const [loading, setLoading] = useState(true);
   const actor = useActor();
 
   useEffect(() => {
     if (!actor) return;
+    let cancelled = false;
+    setLoading(true);
+
     (async () => {
-      // @ts-ignore - Handled by our proxy
-      const response = await actor.list_profiles();
-      if (response && "Ok" in response) {
-        setProfiles(response.Ok);
+      let entries: ProfileEntry[] = [];
+      if (query.trim()) {
+        // @ts-ignore - Handled by our proxy
+        const response = await actor.search_profiles(query, [], []);
+        if (response && "Ok" in response) {
+          entries = response.Ok.items.map((result: any) => ({
+            principal: result.principal.toString(),
+            profile: result.profile,
+            followerCount: result.follower_count,
+            videoCount: result.video_count,
+          }));
+        }
+      } else {
+        // @ts-ignore - Handled by our proxy
+        const response = await actor.list_profiles([], []);
+        if (response && "Ok" in response) {
+          entries = response.Ok.items.map(([principal, profile]: [string, UserProfile]) => ({
+            principal,
+            profile,
+          }));
+        }
+      }
+      if (!cancelled) {
+        setProfiles(entries);
+        setLoading(false);
       }
-      setLoading(false);
     })();
-  }, [actor]);
+
+    return () => {
+      cancelled = true;
+    };
+  }, [actor, query]);
 
   return (
     <div className="w-full max-w-2xl border-zinc-700/50 border-[1px] bg-zinc-900 px-5 py-5 drop-shadow-xl rounded-3xl flex flex-col items-center">
       <div className="flex flex-col items-center w-full gap-10 py-8 md:px-8">
         <div className="text-2xl font-bold">User Profiles</div>
+        <input
+          type="text"
+          placeholder="Search by name or address"
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          className="w-full px-4 py-2 bg-zinc-800 border border-zinc-700 rounded-lg focus:outline-none focus:ring-1 focus:ring-blue-500 text-white"
+        />
         <div className="flex flex-col items-center gap-5">
           {loading && (
             <div className="flex flex-col items-center justify-center w-full h-64">
               <FontAwesomeIcon className="w-4 h-4" icon={faCircleNotch} spin />
             </div>
           )}
+          {!loading && query.trim() && profiles.length === 0 && (
+            <div className="text-zinc-400">No profiles match "{query}"</div>
+          )}
           <div className="grid grid-cols-1 gap-5 md:grid-cols-3">
             {profiles.map((p) => (
-              <ProfileCard key={p[0]} principal={p[0]} profile={p[1]} />
+              <div key={p.principal} className="flex flex-col items-center gap-2">
+                <ProfileCard principal={p.principal} profile={p.profile} />
+                {p.followerCount !== undefined && (
+                  <div className="text-sm text-zinc-400">
+                    {p.followerCount.toString()} followers · {p.videoCount?.toString() ?? "0"} videos
+                  </div>
+                )}
+              </div>
             ))}
           </div>
         </div>
@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useActor } from '../../ic/Actors';
+import { buildSearchQuery } from '../../utils/searchQuery';
 
 interface VideoMetadata {
   video_id: string;
@@ -30,12 +31,10 @@ export function SimpleSearch() {
         const backendActor = actor as any;
         
         if (typeof backendActor.search_videos === 'function') {
-          const searchResults = await backendActor.search_videos(
-            query,
-            [20], // limit to 20 results
-            [0]   // start at offset 0
+          const response = await backendActor.search_videos(
+            buildSearchQuery(query, 20)
           );
-          setResults(searchResults);
+          setResults("Ok" in response ? response.Ok.videos : []);
         } else {
           console.error('search_videos function not found on actor');
           setResults([]);
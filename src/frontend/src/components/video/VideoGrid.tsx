@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from 'react';
 import { useActor } from '../../ic/Actors';
+import { buildSearchQuery } from '../../utils/searchQuery';
 // BackendExtended no longer needed with our proxy
 import { twMerge } from 'tailwind-merge';
 import { formatDistanceToNow } from 'date-fns';
@@ -34,7 +35,8 @@ export function VideoGrid({ tag, className = '', onVideoSelect }: VideoGridProps
         try {
           console.log('Searching videos by tag:', tag);
           // @ts-ignore - Our proxy handles this correctly
-          videoList = await actor.list_videos_by_tag(tag.toLowerCase());
+          const response = await actor.list_videos_by_tag(tag.toLowerCase(), [], []);
+          videoList = "Ok" in response ? response.Ok.items : [];
           console.log(`Found ${videoList.length} videos with tag: ${tag}`);
         } catch (err) {
           console.error(`Error searching for videos with tag ${tag}:`, err);
@@ -52,7 +54,8 @@ export function VideoGrid({ tag, className = '', onVideoSelect }: VideoGridProps
             if (actor.search_videos && typeof actor.search_videos === 'function') {
               console.log("Using search_videos with empty string to get all videos");
               // @ts-ignore - Backend method
-              videoList = await actor.search_videos("", [], []);
+              const response = await actor.search_videos(buildSearchQuery(""));
+              videoList = "Ok" in response ? response.Ok.videos : [];
             } else {
               console.warn("search_videos method not found on actor");
               videoList = [];
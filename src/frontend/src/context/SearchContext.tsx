@@ -1,5 +1,6 @@
 import React, { createContext, useContext, useState } from 'react';
 import { useActor } from '../ic/Actors';
+import { buildSearchQuery } from '../utils/searchQuery';
 
 // Define VideoMetadata interface locally
 interface VideoMetadata {
@@ -37,12 +38,10 @@ export const SearchProvider: React.FC<{ children: React.ReactNode }> = ({ childr
       const backendActor = actor as any;
       
       if (typeof backendActor.search_videos === 'function') {
-        const results = await backendActor.search_videos(
-          searchQuery,
-          [20], // limit to 20 results
-          [0]   // start at offset 0
+        const response = await backendActor.search_videos(
+          buildSearchQuery(searchQuery, 20)
         );
-        setSearchResults(results);
+        setSearchResults("Ok" in response ? response.Ok.videos : []);
       } else {
         console.error('search_videos function not found on actor');
         setSearchResults([]);
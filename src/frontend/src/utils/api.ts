@@ -22,10 +22,29 @@ export function safeStringify(obj: any): string {
 }
 
 /**
- * Get all videos from the backend
+ * Candid encoding of an optional page cursor and limit
+ */
+function pageArgs(cursor?: string, limit?: number): [[] | [string], [] | [number]] {
+  return [cursor ? [cursor] : [], limit ? [limit] : []];
+}
+
+/**
+ * Returns the items of a paginated backend response, throwing on Err
+ */
+function pageItems(response: any): any[] {
+  if (response && "Err" in response) {
+    throw new Error(response.Err);
+  }
+  return response?.Ok?.items ?? [];
+}
+
+/**
+ * Get a page of videos from the backend
  * This function handles the naming discrepancy between backend and frontend
+ * @param cursor - The next_cursor of the previous page, if any
+ * @param limit - Page size; the backend caps it
  */
-export async function getAllVideos(actor: any) {
+export async function getAllVideos(actor: any, cursor?: string, limit?: number) {
   if (!actor) {
     throw new Error("Actor is not available");
   }
@@ -61,7 +80,7 @@ export async function getAllVideos(actor: any) {
     console.log('Trying to call via direct property access');
     const method = actor["list_all_videos"];
     if (method) {
-      return method.apply(actor, []);
+      return pageItems(await method.apply(actor, pageArgs(cursor, limit)));
     }
   } catch (e) {
     console.log('Error calling list_all_videos via apply:', e);
@@ -73,7 +92,7 @@ export async function getAllVideos(actor: any) {
     if (/list.*videos/i.test(key)) {
       console.log(`Found likely method match: ${key}`);
       try {
-        return actor[key]();
+        return pageItems(await actor[key](...pageArgs(cursor, limit)));
       } catch (e) {
         console.log(`Error calling ${key}:`, e);
       }
@@ -87,7 +106,7 @@ export async function getAllVideos(actor: any) {
     if (typeof actor.createActor === 'function') {
       console.log("Actor has createActor method, trying to access inner actor");
       // @ts-ignore - Attempt to access private actor instance
-      return actor._innerActor?.list_all_videos?.();
+      return pageItems(await actor._innerActor?.list_all_videos?.(...pageArgs(cursor, limit)));
     }
   } catch (e) {
     console.log("Error accessing inner actor:", e);
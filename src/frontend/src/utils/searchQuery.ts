@@ -0,0 +1,21 @@
+// Builds the SearchQuery record taken by the backend's search_videos.
+// Candid optionals are encoded as [] (none) or [value] (some).
+export function buildSearchQuery(text: string, limit?: number) {
+  return {
+    text: text ? [text] : [],
+    match_mode: [],
+    uploader: [],
+    uploaded_after: [],
+    uploaded_before: [],
+    min_duration_sec: [],
+    max_duration_sec: [],
+    tags_all: [],
+    tags_any: [],
+    tags_none: [],
+    language: [],
+    content_rating: [],
+    sort: [],
+    cursor: [],
+    limit: limit === undefined ? [] : [limit],
+  };
+}
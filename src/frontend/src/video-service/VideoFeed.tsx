@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from 'react';
 import { useActor } from '../ic/Actors';
+import { buildSearchQuery } from '../utils/searchQuery';
 import { Principal } from '@dfinity/principal';
 import { HLSVideoPlayer } from './HLSVideoPlayer';
 import { useVideoService } from './VideoServiceProvider';
@@ -47,8 +48,8 @@ export function VideoFeed({ tag, className = '', onVideoSelect }: VideoFeedProps
           try {
             console.log('Using backend list_videos_by_tag method');
             // @ts-ignore - Backend API method
-            const response = await actor.list_videos_by_tag(tag.toLowerCase());
-            fetchedVideos = response || [];
+            const response = await actor.list_videos_by_tag(tag.toLowerCase(), [], []);
+            fetchedVideos = response && "Ok" in response ? response.Ok.items : [];
             console.log(`Found ${fetchedVideos.length} videos with tag "${tag}" via backend`);
           } catch (err) {
             console.error('Error with backend list_videos_by_tag call:', err);
@@ -65,7 +66,8 @@ export function VideoFeed({ tag, className = '', onVideoSelect }: VideoFeedProps
             // @ts-ignore - Backend API method
             if (actor.search_videos && typeof actor.search_videos === 'function') {
               // @ts-ignore - Backend API method
-              fetchedVideos = await actor.search_videos("", [], []);
+              const response = await actor.search_videos(buildSearchQuery(""));
+              fetchedVideos = "Ok" in response ? response.Ok.videos : [];
               console.log(`Found ${fetchedVideos.length} videos via search_videos`);
             } 
             else {
@@ -58,10 +58,12 @@ export function VideoGrid({ tag, className = '', onVideoSelect }: VideoGridProps
         
         if (tag && tag !== "all" && tag !== "All") {
           // @ts-ignore - Backend method
-          videoList = await actor.list_videos_by_tag(tag.toLowerCase());
+          const response = await actor.list_videos_by_tag(tag.toLowerCase(), [], []);
+          videoList = "Ok" in response ? response.Ok.items : [];
         } else {
           // @ts-ignore - Backend method
-          videoList = await actor.list_all_videos();
+          const response = await actor.list_all_videos([], []);
+          videoList = "Ok" in response ? response.Ok.items : [];
         }
         
         console.log("Fetched videos:", videoList);
@@ -9,6 +9,14 @@ export interface VideoUploadProps {
   onUploadComplete?: () => void;
 }
 
+// Hex encoded SHA-256 of the file, which the backend uses to catch re-uploads
+async function sha256Hex(file: File): Promise<string> {
+  const digest = await crypto.subtle.digest('SHA-256', await file.arrayBuffer());
+  return Array.from(new Uint8Array(digest))
+    .map(byte => byte.toString(16).padStart(2, '0'))
+    .join('');
+}
+
 export function VideoUpload({ onSuccess, onError, className = '', onUploadComplete }: VideoUploadProps) {
   const [file, setFile] = useState<File | null>(null);
   const [title, setTitle] = useState('');
@@ -54,6 +62,9 @@ export function VideoUpload({ onSuccess, onError, className = '', onUploadComple
       setProgress(10);
       setStatusMessage('Starting upload process...');
 
+      // Hash the file before it leaves the browser
+      const contentHash = await sha256Hex(file);
+
       // 1. Upload to IPFS and create Livepeer asset
       setProgress(20);
       setStatusMessage('Uploading to IPFS...');
@@ -66,42 +77,29 @@ export function VideoUpload({ onSuccess, onError, className = '', onUploadComple
       setProgress(80);
       setStatusMessage('Saving video metadata to IC backend...');
       
-      try {
-        // @ts-ignore  (depending on your .did definitions)
-        const result = await actor.create_video_metadata(
-          videoInfo.id,
-          title,
-          tags.split(',').map(t => t.trim()).filter(Boolean),
-          [`ipfs:${videoInfo.ipfsCid}`] // pass as single-element array
-        );
-  
-        setProgress(100);
-        setStatusMessage('Upload complete!');
-        console.log('[VideoUpload] create_video_metadata result:', result);
-  
-        if (onSuccess) onSuccess(videoInfo.id);
-        if (onUploadComplete) onUploadComplete();
-      } catch (metadataError) {
-        console.warn('[VideoUpload] create_video_metadata error:', metadataError);
-        
-        // Check if this is a "Video ID already exists" error
-        const errorStr = String(metadataError);
-        if (errorStr.includes('Video ID already exists')) {
-          console.log('[VideoUpload] Video ID already exists error handled - proceeding with upload anyway');
-          
-          // Even though metadata creation failed, we still have valid video info
-          // The video is already uploaded to IPFS and Livepeer
-          setProgress(100);
-          setStatusMessage('Upload complete! (Note: Video already exists in the system)');
-          
-          if (onSuccess) onSuccess(videoInfo.id);
-          if (onUploadComplete) onUploadComplete();
-        } else {
-          // For other errors, propagate them
-          throw metadataError;
-        }
+      // The canister mints the video ID; the client ID makes retries of this upload idempotent
+      // @ts-ignore  (depending on your .did definitions)
+      const result = await actor.create_video_metadata({
+        title,
+        tags: tags.split(',').map(t => t.trim()).filter(Boolean),
+        storage_ref: videoInfo.ipfsCid ? [{ Ipfs: { cid: videoInfo.ipfsCid, path: [] } }] : [],
+        idempotency_key: [videoInfo.id],
+        details: [],
+        content_hash: [contentHash],
+        remix_of: [],
+        renditions: [],
+      });
+      console.log('[VideoUpload] create_video_metadata result:', result);
+      if ('Err' in result) {
+        throw new Error(result.Err);
       }
 
+      setProgress(100);
+      setStatusMessage('Upload complete!');
+
+      if (onSuccess) onSuccess(result.Ok.video_id);
+      if (onUploadComplete) onUploadComplete();
+
       // Reset form
       setTimeout(() => {
         setFile(null);